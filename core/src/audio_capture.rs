@@ -1,7 +1,14 @@
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
 use ractor::{Actor, ActorProcessingErr, ActorRef};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 use thiserror::Error;
+use tokio::sync::oneshot;
 
+use crate::audio_devices;
+use crate::config::Settings;
 use crate::types::{AudioCaptureMsg, AudioChunk, CoordinatorMsg};
 
 #[derive(Error, Debug)]
@@ -12,7 +19,7 @@ pub enum AudioCaptureError {
     StreamError(String),
 }
 
-// This will be our thread-local context that manages the CPAL stream
+// The open stream, kept on the audio thread
 struct StreamContext {
     _stream: cpal::Stream, // The underscore prevents "unused" warnings
 }
@@ -28,9 +35,17 @@ impl Drop for StreamContext {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AudioState {
     Started,
+    Reconnecting,
     Stopped,
 }
 
+// How often we check that the stream is still delivering audio
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+// Backoff between reconnection attempts after the device disappears
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(10);
+
 pub struct AudioCaptureActor {
     // Empty struct, all state is in AudioCaptureState
 }
@@ -38,46 +53,111 @@ pub struct AudioCaptureActor {
 pub struct AudioCaptureState {
     audio_state: AudioState,
     coordinator: ActorRef<CoordinatorMsg>,
-    // We'll store the stream context in a thread-local variable
-    // and just track the state here
+    config: Arc<Settings>,
+    // Bumped every time a stream is opened or capture is stopped, so that
+    // errors, timers and audio belonging to an old stream can be ignored
+    generation: u64,
+    current_generation: Arc<AtomicU64>, // Shared with the stream callbacks
+    // Incremented by the data callback, used to detect stalled streams
+    callback_count: Arc<AtomicU64>,
+    last_callback_count: u64,
+    reconnect_attempts: u32,
+    audio_thread: mpsc::Sender<StreamCommand>,
+}
+
+// Everything the stream callbacks need to report back
+struct StreamHooks {
+    coordinator: ActorRef<CoordinatorMsg>,
+    capture: ActorRef<AudioCaptureMsg>,
+    generation: u64,
+    current_generation: Arc<AtomicU64>,
+    callback_count: Arc<AtomicU64>,
 }
 
-thread_local! {
-    static STREAM_CONTEXT: std::cell::RefCell<Option<StreamContext>> = std::cell::RefCell::new(None);
+// cpal streams can't move between threads, and the actor runs on whichever
+// tokio worker picks it up, so streams are opened and dropped on a thread of
+// their own
+enum StreamCommand {
+    Open {
+        hooks: StreamHooks,
+        preferred_device: Option<String>,
+        opened: oneshot::Sender<Result<String, AudioCaptureError>>, // The device's name
+    },
+    Close,
+}
+
+fn spawn_audio_thread() -> Result<mpsc::Sender<StreamCommand>, AudioCaptureError> {
+    let (commands, receiver) = mpsc::channel::<StreamCommand>();
+    std::thread::Builder::new()
+        .name("audio-capture".to_string())
+        .spawn(move || {
+            let mut context: Option<StreamContext> = None;
+            // Ends when the actor stops and drops the sender, closing the stream
+            for command in receiver {
+                // Either way the old stream goes first
+                drop(context.take());
+                if let StreamCommand::Open {
+                    hooks,
+                    preferred_device,
+                    opened,
+                } = command
+                {
+                    let result = start_capture(hooks, preferred_device.as_deref());
+                    let _ = opened.send(result.map(|(stream, device_name)| {
+                        context = Some(StreamContext { _stream: stream });
+                        device_name
+                    }));
+                }
+            }
+        })
+        .map_err(|e| {
+            AudioCaptureError::InitError(format!("Failed to start audio thread: {}", e))
+        })?;
+    Ok(commands)
 }
 
 #[ractor::async_trait]
 impl Actor for AudioCaptureActor {
     type Msg = AudioCaptureMsg;
     type State = AudioCaptureState;
-    type Arguments = ActorRef<CoordinatorMsg>;
+    type Arguments = (ActorRef<CoordinatorMsg>, Arc<Settings>);
 
     async fn pre_start(
         &self,
         _myself: ActorRef<Self::Msg>,
-        coordinator: Self::Arguments,
+        (coordinator, config): Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
         tracing::info!("AudioCaptureActor started");
 
         Ok(AudioCaptureState {
             audio_state: AudioState::Stopped,
             coordinator,
+            config,
+            generation: 0,
+            current_generation: Arc::new(AtomicU64::new(0)),
+            callback_count: Arc::new(AtomicU64::new(0)),
+            last_callback_count: 0,
+            reconnect_attempts: 0,
+            audio_thread: spawn_audio_thread()?,
         })
     }
 
     async fn handle(
         &self,
-        _myself: ActorRef<Self::Msg>,
+        myself: ActorRef<Self::Msg>,
         message: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
         match message {
             AudioCaptureMsg::Start => {
                 if state.audio_state == AudioState::Stopped {
-                    match self.start_capture(state.coordinator.clone()) {
-                        Ok(()) => {
+                    match self.open_stream(&myself, state).await {
+                        Ok(device_name) => {
                             state.audio_state = AudioState::Started;
-                            tracing::info!("Audio capture started");
+                            tracing::info!("Audio capture started on {}", device_name);
+                            state
+                                .coordinator
+                                .send_message(CoordinatorMsg::InputDeviceOpened(device_name))?;
                             // Send status update back to coordinator
                             state
                                 .coordinator
@@ -100,12 +180,11 @@ impl Actor for AudioCaptureActor {
                 }
             }
             AudioCaptureMsg::Stop => {
-                if state.audio_state == AudioState::Started {
-                    // Stop the stream by dropping it
-                    STREAM_CONTEXT.with(|context| {
-                        *context.borrow_mut() = None;
-                    });
-
+                if state.audio_state != AudioState::Stopped {
+                    // Invalidate any pending health checks, reconnection
+                    // attempts and audio, and stop the stream by dropping it
+                    next_generation(state);
+                    let _ = state.audio_thread.send(StreamCommand::Close);
                     state.audio_state = AudioState::Stopped;
                     tracing::info!("Audio capture stopped");
 
@@ -119,70 +198,253 @@ impl Actor for AudioCaptureActor {
                     tracing::info!("Audio capture already stopped");
                 }
             }
+            AudioCaptureMsg::StreamFailed { generation, error } => {
+                if generation == state.generation && state.audio_state == AudioState::Started {
+                    self.handle_stream_lost(&myself, state, error)?;
+                }
+            }
+            AudioCaptureMsg::HealthCheck { generation } => {
+                if generation == state.generation && state.audio_state == AudioState::Started {
+                    let count = state.callback_count.load(Ordering::Relaxed);
+                    if count == state.last_callback_count {
+                        // No data since the last check, the device is most likely gone
+                        self.handle_stream_lost(
+                            &myself,
+                            state,
+                            "Audio stream stopped delivering data".to_string(),
+                        )?;
+                    } else {
+                        state.last_callback_count = count;
+                        schedule_health_check(&myself, generation);
+                    }
+                }
+            }
+            AudioCaptureMsg::Reconnect { generation } => {
+                if generation == state.generation && state.audio_state == AudioState::Reconnecting {
+                    state.reconnect_attempts += 1;
+                    tracing::info!(
+                        "Trying to reopen audio input (attempt {})",
+                        state.reconnect_attempts
+                    );
+
+                    match self.open_stream(&myself, state).await {
+                        Ok(device_name) => {
+                            tracing::info!("Audio capture resumed on {}", device_name);
+                            state.audio_state = AudioState::Started;
+                            state.reconnect_attempts = 0;
+                            state
+                                .coordinator
+                                .send_message(CoordinatorMsg::InputDeviceOpened(
+                                    device_name.clone(),
+                                ))?;
+                            state
+                                .coordinator
+                                .send_message(CoordinatorMsg::UpdateStatus(format!(
+                                    "Audio capture resumed on {}",
+                                    device_name
+                                )))?;
+                        }
+                        Err(e) => {
+                            let delay = reconnect_delay(state.reconnect_attempts);
+                            tracing::warn!(
+                                "Failed to reopen audio input: {} (retrying in {:?})",
+                                e,
+                                delay
+                            );
+                            state
+                                .coordinator
+                                .send_message(CoordinatorMsg::UpdateStatus(format!(
+                                    "Waiting for audio device (attempt {})...",
+                                    state.reconnect_attempts
+                                )))?;
+                            let generation = state.generation;
+                            myself.send_after(delay, move || AudioCaptureMsg::Reconnect {
+                                generation,
+                            });
+                        }
+                    }
+                }
+            }
         }
         Ok(())
     }
 }
 
 impl AudioCaptureActor {
-    fn start_capture(
+    // Open a new stream, tagging it with a fresh generation, and start
+    // monitoring it. Returns the name of the device that was opened.
+    async fn open_stream(
         &self,
-        coordinator: ActorRef<CoordinatorMsg>,
-    ) -> Result<(), AudioCaptureError> {
-        // Get default host
-        let host = cpal::default_host();
-
-        // Get default input device
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| AudioCaptureError::InitError("No input device found".to_string()))?;
-
-        tracing::info!(
-            "Using input device: {}",
-            device.name().unwrap_or_else(|_| "Unknown".to_string())
-        );
-
-        // Get supported config
-        let config = device
-            .default_input_config()
-            .map_err(|e| AudioCaptureError::InitError(format!("Default config error: {}", e)))?;
-
-        tracing::info!("Using input config: {:?}", config);
-
-        // Clone the coordinator ref for the closure
-        let coordinator_ref = coordinator.clone();
-
-        // Build the stream
-        let stream = device
-            .build_input_stream(
-                &config.into(),
-                move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    // Create a copy of the data
-                    let chunk = AudioChunk(data.to_vec());
-
-                    // Send to coordinator
-                    if let Err(e) = coordinator_ref.send_message(CoordinatorMsg::AudioChunk(chunk))
-                    {
-                        tracing::error!("Failed to send audio chunk: {}", e);
-                    }
-                },
-                move |err| {
-                    tracing::error!("Audio stream error: {}", err);
-                },
-                None,
-            )
-            .map_err(|e| AudioCaptureError::StreamError(format!("Stream error: {}", e)))?;
-
-        // Start the stream
-        stream
-            .play()
-            .map_err(|e| AudioCaptureError::StreamError(format!("Failed to play stream: {}", e)))?;
-
-        // Store the stream in our thread-local
-        STREAM_CONTEXT.with(|context| {
-            *context.borrow_mut() = Some(StreamContext { _stream: stream });
-        });
+        myself: &ActorRef<AudioCaptureMsg>,
+        state: &mut AudioCaptureState,
+    ) -> Result<String, AudioCaptureError> {
+        next_generation(state);
+        state.callback_count.store(0, Ordering::Relaxed);
+        state.last_callback_count = 0;
+
+        let hooks = StreamHooks {
+            coordinator: state.coordinator.clone(),
+            capture: myself.clone(),
+            generation: state.generation,
+            current_generation: state.current_generation.clone(),
+            callback_count: state.callback_count.clone(),
+        };
+
+        let thread_gone = || AudioCaptureError::InitError("The audio thread exited".to_string());
+        let (opened, result) = oneshot::channel();
+        state
+            .audio_thread
+            .send(StreamCommand::Open {
+                hooks,
+                preferred_device: state.config.input_device.clone(),
+                opened,
+            })
+            .map_err(|_| thread_gone())?;
+        let device_name = result.await.map_err(|_| thread_gone())??;
+
+        schedule_health_check(myself, state.generation);
+        Ok(device_name)
+    }
+
+    // Tear down a dead stream and start trying to reopen the device
+    fn handle_stream_lost(
+        &self,
+        myself: &ActorRef<AudioCaptureMsg>,
+        state: &mut AudioCaptureState,
+        reason: String,
+    ) -> Result<(), ActorProcessingErr> {
+        tracing::error!("Audio input lost: {}", reason);
 
+        next_generation(state);
+        let _ = state.audio_thread.send(StreamCommand::Close);
+        state.audio_state = AudioState::Reconnecting;
+        state.reconnect_attempts = 0;
+        state
+            .coordinator
+            .send_message(CoordinatorMsg::AudioDeviceLost(reason))?;
+
+        let generation = state.generation;
+        myself.send_after(reconnect_delay(0), move || AudioCaptureMsg::Reconnect {
+            generation,
+        });
         Ok(())
     }
 }
+
+fn next_generation(state: &mut AudioCaptureState) {
+    state.generation += 1;
+    state
+        .current_generation
+        .store(state.generation, Ordering::Relaxed);
+}
+
+// Open the preferred (or default) input device and start streaming from it.
+// Returns the stream and the name of the device that was opened.
+fn start_capture(
+    hooks: StreamHooks,
+    preferred_device: Option<&str>,
+) -> Result<(cpal::Stream, String), AudioCaptureError> {
+    let (device, device_name) = audio_devices::open_input_device(preferred_device)?;
+
+    tracing::info!("Using input device: {}", device_name);
+
+    // Get supported config
+    let config = device
+        .default_input_config()
+        .map_err(|e| AudioCaptureError::InitError(format!("Default config error: {}", e)))?;
+
+    tracing::info!("Using input config: {:?}", config);
+
+    let sample_format = config.sample_format();
+    let stream_config: cpal::StreamConfig = config.into();
+
+    // Build the stream, converting whatever the device delivers to f32
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => build_input_stream::<f32>(&device, &stream_config, hooks),
+        cpal::SampleFormat::I16 => build_input_stream::<i16>(&device, &stream_config, hooks),
+        cpal::SampleFormat::U16 => build_input_stream::<u16>(&device, &stream_config, hooks),
+        cpal::SampleFormat::I32 => build_input_stream::<i32>(&device, &stream_config, hooks),
+        other => Err(AudioCaptureError::InitError(format!(
+            "Unsupported sample format: {:?}",
+            other
+        ))),
+    }?;
+
+    // Start the stream
+    stream
+        .play()
+        .map_err(|e| AudioCaptureError::StreamError(format!("Failed to play stream: {}", e)))?;
+
+    Ok((stream, device_name))
+}
+
+fn schedule_health_check(myself: &ActorRef<AudioCaptureMsg>, generation: u64) {
+    myself.send_after(HEALTH_CHECK_INTERVAL, move || {
+        AudioCaptureMsg::HealthCheck { generation }
+    });
+}
+
+// Exponential backoff: 0.5s, 1s, 2s, ... capped at RECONNECT_MAX_DELAY
+fn reconnect_delay(attempt: u32) -> Duration {
+    RECONNECT_INITIAL_DELAY
+        .saturating_mul(1u32 << attempt.min(16))
+        .min(RECONNECT_MAX_DELAY)
+}
+
+// Build an input stream for a device-native sample type, forwarding f32 chunks
+// tagged with the stream's rate and channel count
+fn build_input_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    hooks: StreamHooks,
+) -> Result<cpal::Stream, AudioCaptureError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let sample_rate = config.sample_rate.0;
+    let channels = config.channels;
+    let StreamHooks {
+        coordinator,
+        capture,
+        generation,
+        current_generation,
+        callback_count,
+    } = hooks;
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                // Audio from a stream that's being replaced or stopped
+                if current_generation.load(Ordering::Relaxed) != generation {
+                    return;
+                }
+                callback_count.fetch_add(1, Ordering::Relaxed);
+
+                // Convert to f32 and copy the data
+                let samples: Vec<f32> = data.iter().map(|&s| s.to_sample::<f32>()).collect();
+                let chunk = AudioChunk::new(samples, sample_rate, channels);
+
+                // Send to coordinator
+                if let Err(e) = coordinator.send_message(CoordinatorMsg::AudioChunk(chunk)) {
+                    tracing::error!("Failed to send audio chunk: {}", e);
+                }
+            },
+            move |err| {
+                tracing::error!("Audio stream error: {}", err);
+
+                // A missing device means the stream is gone for good. Other
+                // backend errors may be transient; the health check catches
+                // streams that stop delivering data
+                if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                    let _ = capture.send_message(AudioCaptureMsg::StreamFailed {
+                        generation,
+                        error: err.to_string(),
+                    });
+                }
+            },
+            None,
+        )
+        .map_err(|e| AudioCaptureError::StreamError(format!("Stream error: {}", e)))
+}
@@ -0,0 +1,129 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+
+use crate::audio_capture::AudioCaptureError;
+
+/// A range of stream configurations an input device supports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportedInputConfig {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+}
+
+/// Description of an input device, as reported by its host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputDeviceInfo {
+    pub host: String,
+    pub name: String,
+    pub is_default: bool,
+    pub default_sample_rate: Option<u32>,
+    pub default_channels: Option<u16>,
+    pub supported_configs: Vec<SupportedInputConfig>,
+}
+
+/// Names of the audio hosts available on this platform (ALSA, JACK, WASAPI, ...).
+pub fn list_hosts() -> Vec<String> {
+    cpal::available_hosts()
+        .into_iter()
+        .map(|id| id.name().to_string())
+        .collect()
+}
+
+/// Enumerate the input devices of every available host.
+///
+/// Hosts that fail to initialize (e.g. JACK without a running server) are
+/// skipped rather than treated as errors.
+pub fn list_input_devices() -> Vec<InputDeviceInfo> {
+    let mut devices = Vec::new();
+
+    for host_id in cpal::available_hosts() {
+        let host = match cpal::host_from_id(host_id) {
+            Ok(host) => host,
+            Err(e) => {
+                tracing::debug!("Skipping audio host {}: {}", host_id.name(), e);
+                continue;
+            }
+        };
+
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+        let host_devices = match host.input_devices() {
+            Ok(host_devices) => host_devices,
+            Err(e) => {
+                tracing::warn!("Failed to list input devices for {}: {}", host_id.name(), e);
+                continue;
+            }
+        };
+
+        for device in host_devices {
+            let Ok(name) = device.name() else {
+                continue;
+            };
+
+            let default_config = device.default_input_config().ok();
+            let supported_configs = device
+                .supported_input_configs()
+                .map(|configs| {
+                    configs
+                        .map(|c| SupportedInputConfig {
+                            channels: c.channels(),
+                            min_sample_rate: c.min_sample_rate().0,
+                            max_sample_rate: c.max_sample_rate().0,
+                            sample_format: c.sample_format().to_string(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            devices.push(InputDeviceInfo {
+                host: host_id.name().to_string(),
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+                default_sample_rate: default_config.as_ref().map(|c| c.sample_rate().0),
+                default_channels: default_config.as_ref().map(|c| c.channels()),
+                supported_configs,
+            });
+        }
+    }
+
+    devices
+}
+
+/// Open the input device with the given name, falling back to the default
+/// input device if it's not set or can't be found.
+///
+/// Returns the device together with its name.
+pub fn open_input_device(
+    preferred: Option<&str>,
+) -> Result<(cpal::Device, String), AudioCaptureError> {
+    if let Some(preferred) = preferred.filter(|name| !name.is_empty()) {
+        match find_input_device(preferred) {
+            Some(device) => return Ok((device, preferred.to_string())),
+            None => tracing::warn!(
+                "Input device '{}' not found, falling back to default device",
+                preferred
+            ),
+        }
+    }
+
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| AudioCaptureError::InitError("No input device found".to_string()))?;
+    let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+    Ok((device, name))
+}
+
+// Look for a device by name, checking the default host before the others
+fn find_input_device(name: &str) -> Option<cpal::Device> {
+    let default_host_id = cpal::default_host().id();
+    let mut host_ids = cpal::available_hosts();
+    host_ids.sort_by_key(|id| *id != default_host_id);
+
+    host_ids
+        .into_iter()
+        .filter_map(|id| cpal::host_from_id(id).ok())
+        .filter_map(|host| host.input_devices().ok())
+        .flatten()
+        .find(|device| device.name().map(|n| n == name).unwrap_or(false))
+}
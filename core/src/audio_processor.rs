@@ -1,97 +1,31 @@
-use nnnoiseless::DenoiseState;
 use ractor::{Actor, ActorProcessingErr, ActorRef};
-use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
-use std::thread;
-use std::time::{Duration, Instant};
-use webrtc_vad;
 
-use crate::config::Settings;
+use crate::config::{ActivationMode, Settings};
+use crate::denoise::Denoiser;
+use crate::resampler::AudioConverter;
+use crate::segmenter::{SegmentEvent, UtteranceSegmenter};
 use crate::types::{AudioChunk, AudioProcessorMsg, CoordinatorMsg, TranscriberMsg};
-
-// Message types for VAD thread communication
-enum VadRequest {
-    ProcessChunk(Vec<i16>, u32), // Samples, sample rate
-    SetMode(webrtc_vad::VadMode),
-    Shutdown,
-}
-
-enum VadResponse {
-    Result(bool), // true = voice detected, false = silence
-    Error(String),
-}
+use crate::vad::{VadEvent, VoiceActivityDetector};
+use crate::wakeword::WakeWordDetector;
 
 pub struct AudioProcessorActor {}
 
 pub struct AudioProcessorState {
-    denoise_state: Option<nnnoiseless::DenoiseState<'static>>,
-    vad_sender: Option<Sender<VadRequest>>,
-    vad_receiver: Option<Receiver<VadResponse>>,
+    converter: AudioConverter,
+    denoiser: Option<Denoiser>,
+    vad: Option<VoiceActivityDetector>,
+    segmenter: UtteranceSegmenter,
     coordinator: ActorRef<CoordinatorMsg>,
     transcriber: ActorRef<TranscriberMsg>,
     sample_rate: u32,
     config: Arc<Settings>,
-    silence_start: Option<Instant>,
-    is_silent: bool,
-    frames_since_reporting: usize,
-}
-
-// Frame size for VAD processing (30ms at 16kHz = 480 samples)
-const VAD_FRAME_SIZE_MS: usize = 30; // 30ms frames
-
-// Spawn a new thread for VAD processing
-fn spawn_vad_thread(
-    mode: webrtc_vad::VadMode,
-) -> Result<(Sender<VadRequest>, Receiver<VadResponse>), String> {
-    // Create channels for communication
-    let (req_tx, req_rx) = mpsc::channel::<VadRequest>();
-    let (resp_tx, resp_rx) = mpsc::channel::<VadResponse>();
-
-    // Spawn thread
-    thread::spawn(move || {
-        // Initialize VAD - Vad::new() directly returns a Vad instance (not Result<Vad>)
-        let mut vad = webrtc_vad::Vad::new();
-
-        // Set initial mode - set_mode() doesn't return a Result
-        vad.set_mode(mode);
-
-        // Process requests
-        loop {
-            match req_rx.recv() {
-                Ok(VadRequest::ProcessChunk(samples, _sample_rate)) => {
-                    match vad.is_voice_segment(&samples) {
-                        Ok(has_voice) => {
-                            if let Err(e) = resp_tx.send(VadResponse::Result(has_voice)) {
-                                eprintln!("Failed to send VAD result: {:?}", e);
-                            }
-                        }
-                        Err(e) => {
-                            if let Err(e) =
-                                resp_tx.send(VadResponse::Error(format!("VAD error: {:?}", e)))
-                            {
-                                eprintln!("Failed to send VAD error: {:?}", e);
-                            }
-                        }
-                    }
-                }
-                Ok(VadRequest::SetMode(new_mode)) => {
-                    // set_mode doesn't return a result
-                    vad.set_mode(new_mode);
-                }
-                Ok(VadRequest::Shutdown) => {
-                    break;
-                }
-                Err(e) => {
-                    eprintln!("VAD thread error: {:?}", e);
-                    break;
-                }
-            }
-        }
-
-        tracing::info!("VAD thread shutting down");
-    });
-
-    Ok((req_tx, resp_rx))
+    silence_ms: u32,                // Time spent outside speech since it last ended
+    silence_reported: bool,         // Whether SilenceDetected(true) was sent for this silence
+    heard_speech: bool,             // Speech was found since capture started
+    silence_timeout_reported: bool, // Whether SilenceTimeout was sent for this silence
+    wake_word: Option<WakeWordDetector>,
+    wake_word_armed: bool, // Speech goes to the wake word spotter, not the transcriber
 }
 
 #[ractor::async_trait]
@@ -112,28 +46,46 @@ impl Actor for AudioProcessorActor {
     ) -> Result<Self::State, ActorProcessingErr> {
         tracing::info!("AudioProcessorActor started");
 
-        // Initialize the noise reduction state if enabled
-        let denoise_state = if config.enable_denoise {
-            // DenoiseState::new() returns a Box<DenoiseState>
-            Some(*nnnoiseless::DenoiseState::new())
+        // Initialize the noise reduction stage if enabled
+        let denoiser = if config.enable_denoise {
+            match Denoiser::new(sample_rate) {
+                Ok(denoiser) => Some(denoiser),
+                Err(e) => {
+                    tracing::error!("Failed to initialize denoiser: {}", e);
+                    None
+                }
+            }
         } else {
             None
         };
 
-        // Initialize the VAD thread if enabled
-        let (vad_sender, vad_receiver) = if config.enable_vad {
-            match spawn_vad_thread(config.vad_mode.into()) {
-                Ok((sender, receiver)) => {
-                    tracing::info!("VAD thread started with mode: {:?}", config.vad_mode);
-                    (Some(sender), Some(receiver))
-                }
+        // Initialize the VAD if enabled
+        let vad = if config.enable_vad {
+            let vad = VoiceActivityDetector::new(sample_rate, &config);
+            tracing::info!(
+                "VAD started with mode: {:?}, {} ms frames",
+                config.vad_mode,
+                vad.frame_ms()
+            );
+            Some(vad)
+        } else {
+            None
+        };
+
+        // Load the wake word spotter if it's the activation mode
+        let wake_word = if config.activation_mode == ActivationMode::WakeWord {
+            match WakeWordDetector::new(&config, sample_rate) {
+                Ok(detector) => Some(detector),
                 Err(e) => {
-                    tracing::error!("Failed to start VAD thread: {}", e);
-                    (None, None)
+                    tracing::error!("Failed to initialize wake word detector: {}", e);
+                    coordinator
+                        .send_message(CoordinatorMsg::WakeWordUnavailable(e.to_string()))
+                        .ok();
+                    None
                 }
             }
         } else {
-            (None, None)
+            None
         };
 
         coordinator
@@ -144,16 +96,20 @@ impl Actor for AudioProcessorActor {
             .ok();
 
         Ok(AudioProcessorState {
-            denoise_state,
-            vad_sender,
-            vad_receiver,
+            converter: AudioConverter::new(sample_rate),
+            denoiser,
+            vad,
+            segmenter: UtteranceSegmenter::new(sample_rate, config.max_utterance_ms),
             coordinator,
             transcriber,
             sample_rate,
             config,
-            silence_start: None,
-            is_silent: false,
-            frames_since_reporting: 0,
+            silence_ms: 0,
+            silence_reported: false,
+            heard_speech: false,
+            silence_timeout_reported: false,
+            wake_word,
+            wake_word_armed: false,
         })
     }
 
@@ -165,152 +121,177 @@ impl Actor for AudioProcessorActor {
     ) -> Result<(), ActorProcessingErr> {
         match message {
             AudioProcessorMsg::ProcessChunk(chunk) => {
-                // Apply denoising if enabled
-                let processed_chunk = if let Some(denoise_state) = &mut state.denoise_state {
-                    let mut output = vec![0.0; chunk.0.len()];
-                    let _ = denoise_state.process_frame(&mut output, &chunk.0);
-                    AudioChunk(output)
-                } else {
-                    chunk
+                // Downmix and resample to the pipeline's format first
+                let chunk = match state.converter.process(chunk) {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        tracing::error!("Failed to convert audio chunk: {}", e);
+                        return Ok(());
+                    }
                 };
 
-                // Apply VAD if enabled
-                if let (Some(vad_sender), Some(vad_receiver)) =
-                    (&state.vad_sender, &state.vad_receiver)
-                {
-                    // Check if we have enough samples for a VAD frame
-                    let samples_per_frame = (state.sample_rate as usize * VAD_FRAME_SIZE_MS) / 1000;
-
-                    if processed_chunk.0.len() >= samples_per_frame {
-                        // Convert f32 samples to i16 for VAD
-                        let i16_samples: Vec<i16> = processed_chunk
-                            .0
-                            .iter()
-                            .take(samples_per_frame)
-                            .map(|&s| (s * 32767.0) as i16)
-                            .collect();
-
-                        // Send chunk to VAD thread
-                        if let Err(e) = vad_sender
-                            .send(VadRequest::ProcessChunk(i16_samples, state.sample_rate))
-                        {
-                            tracing::error!("Failed to send data to VAD thread: {:?}", e);
-                        } else {
-                            // Get VAD result
-                            match vad_receiver.recv() {
-                                Ok(VadResponse::Result(has_voice)) => {
-                                    state.frames_since_reporting += 1;
-
-                                    // Update silence tracking
-                                    if !has_voice {
-                                        // No voice detected
-                                        if !state.is_silent {
-                                            // First silent frame
-                                            state.silence_start = Some(Instant::now());
-                                        } else if let Some(silence_start) = state.silence_start {
-                                            // Already in silence, check duration
-                                            let silence_duration = silence_start.elapsed();
-                                            let threshold = Duration::from_millis(
-                                                state.config.silence_threshold_ms as u64,
-                                            );
-
-                                            if silence_duration > threshold
-                                                && state.frames_since_reporting > 10
-                                            {
-                                                // Silent for too long, notify coordinator
-                                                state.coordinator.send_message(
-                                                    CoordinatorMsg::SilenceDetected(true),
-                                                )?;
-                                                state.frames_since_reporting = 0;
-                                            }
-                                        }
-                                        state.is_silent = true;
-                                    } else {
-                                        // Voice detected
-                                        if state.is_silent && state.frames_since_reporting > 10 {
-                                            // Transition from silence to voice
-                                            state.coordinator.send_message(
-                                                CoordinatorMsg::SilenceDetected(false),
-                                            )?;
-                                            state.frames_since_reporting = 0;
-                                        }
-                                        state.is_silent = false;
-                                        state.silence_start = None;
-                                    }
-                                }
-                                Ok(VadResponse::Error(e)) => {
-                                    tracing::error!("VAD error: {}", e);
-                                }
-                                Err(e) => {
-                                    tracing::error!("Failed to receive VAD result: {:?}", e);
-                                }
+                // The resampler may still be buffering a partial block
+                if chunk.is_empty() {
+                    return Ok(());
+                }
+
+                // Apply denoising if enabled
+                let (processed_chunk, voice_probabilities) =
+                    if let Some(denoiser) = &mut state.denoiser {
+                        let denoised = match denoiser.process(&chunk.samples) {
+                            Ok(denoised) => denoised,
+                            Err(e) => {
+                                tracing::error!("Failed to denoise audio chunk: {}", e);
+                                return Ok(());
                             }
+                        };
+                        (
+                            AudioChunk::mono(denoised.samples, chunk.sample_rate),
+                            denoised.voice_probabilities,
+                        )
+                    } else {
+                        (chunk, Vec::new())
+                    };
+
+                // Without VAD everything goes to the transcriber (or the spotter)
+                let Some(vad) = &mut state.vad else {
+                    // Wait until a full denoiser frame has made it through
+                    if processed_chunk.is_empty() {
+                        return Ok(());
+                    }
+                    if state.wake_word_armed {
+                        let detected = state
+                            .wake_word
+                            .as_mut()
+                            .and_then(|detector| detector.process(&processed_chunk.samples));
+                        if let Some(phrase) = detected {
+                            self.wake_word_detected(state, phrase)?;
                         }
+                        return Ok(());
                     }
-                } else {
-                    // Also check for simple energy-based voice detection if no VAD
-                    if state.config.enable_vad {
-                        let energy = processed_chunk
-                            .0
-                            .iter()
-                            .map(|&sample| sample * sample)
-                            .sum::<f32>()
-                            / processed_chunk.0.len() as f32;
-
-                        let has_voice = energy > state.config.vad_energy_threshold;
-
-                        state.frames_since_reporting += 1;
-
-                        // Update silence tracking
-                        if !has_voice {
-                            // No voice detected
-                            if !state.is_silent {
-                                // First silent frame
-                                state.silence_start = Some(Instant::now());
-                            } else if let Some(silence_start) = state.silence_start {
-                                // Already in silence, check duration
-                                let silence_duration = silence_start.elapsed();
-                                let threshold =
-                                    Duration::from_millis(state.config.silence_threshold_ms as u64);
-
-                                if silence_duration > threshold && state.frames_since_reporting > 10
-                                {
-                                    // Silent for too long, notify coordinator
-                                    state
-                                        .coordinator
-                                        .send_message(CoordinatorMsg::SilenceDetected(true))?;
-                                    state.frames_since_reporting = 0;
-                                }
-                            }
-                            state.is_silent = true;
-                        } else {
-                            // Voice detected
-                            if state.is_silent && state.frames_since_reporting > 10 {
-                                // Transition from silence to voice
-                                state
-                                    .coordinator
-                                    .send_message(CoordinatorMsg::SilenceDetected(false))?;
-                                state.frames_since_reporting = 0;
-                            }
-                            state.is_silent = false;
-                            state.silence_start = None;
+                    state
+                        .transcriber
+                        .send_message(TranscriberMsg::ProcessAudioChunk(processed_chunk))?;
+                    return Ok(());
+                };
+
+                let frames = vad.process(&processed_chunk.samples, &voice_probabilities);
+                let frame_ms = vad.frame_ms();
+
+                for frame in frames {
+                    if matches!(frame.event, Some(VadEvent::SpeechStart { .. })) {
+                        state.silence_ms = 0;
+                        state.silence_reported = false;
+                        // Waiting for the wake word isn't part of a session
+                        state.heard_speech = !state.wake_word_armed;
+                        state.silence_timeout_reported = false;
+                        state
+                            .coordinator
+                            .send_message(CoordinatorMsg::SilenceDetected(false))?;
+                    }
+
+                    if !frame.in_speech {
+                        state.silence_ms = state.silence_ms.saturating_add(frame_ms);
+                        if !state.silence_reported
+                            && state.silence_ms >= state.config.silence_threshold_ms
+                        {
+                            // Silent for too long, notify coordinator
+                            state
+                                .coordinator
+                                .send_message(CoordinatorMsg::SilenceDetected(true))?;
+                            state.silence_reported = true;
+                        }
+
+                        // Silence before the first word doesn't end a session
+                        if state.heard_speech
+                            && !state.silence_timeout_reported
+                            && state.silence_ms >= state.config.auto_stop_silence_ms
+                        {
+                            state
+                                .coordinator
+                                .send_message(CoordinatorMsg::SilenceTimeout)?;
+                            state.silence_timeout_reported = true;
                         }
                     }
+
+                    // Only speech (plus pre-roll and hangover) reaches the transcriber
+                    for event in state.segmenter.push(frame) {
+                        self.handle_segment_event(state, event)?;
+                    }
+                }
+            }
+            AudioProcessorMsg::Flush => {
+                // Finish whatever the user was saying when capture stopped.
+                // Without VAD every chunk already went to the transcriber, so
+                // only the end of the utterance is left to send
+                if state.vad.is_none() {
+                    if !state.wake_word_armed {
+                        state
+                            .transcriber
+                            .send_message(TranscriberMsg::FinalizeUtterance)?;
+                    }
+                } else if let Some(event) = state.segmenter.finish() {
+                    self.handle_segment_event(state, event)?;
                 }
 
-                // Forward the processed audio to the transcriber
-                state
-                    .transcriber
-                    .send_message(TranscriberMsg::ProcessAudioChunk(processed_chunk))?;
+                // Audio still buffered in the converter and denoiser would
+                // otherwise be glued to the start of the next capture
+                state.converter.reset();
+                if let Some(denoiser) = &mut state.denoiser {
+                    denoiser.reset();
+                }
+                if let Some(vad) = &mut state.vad {
+                    vad.reset();
+                }
+                state.segmenter.reset();
+
+                // The next start decides whether to arm again
+                state.wake_word_armed = false;
+                if let Some(detector) = &mut state.wake_word {
+                    detector.reset();
+                }
+
+                // The next capture is a new session
+                state.silence_ms = 0;
+                state.silence_reported = false;
+                state.heard_speech = false;
+                state.silence_timeout_reported = false;
+            }
+            AudioProcessorMsg::ArmWakeWord(armed) => {
+                let Some(detector) = &mut state.wake_word else {
+                    tracing::warn!("Ignoring ArmWakeWord without a wake word detector");
+                    return Ok(());
+                };
+                detector.reset();
+
+                if armed {
+                    // Let the transcriber finish what was said before arming
+                    if let Some(event) = state.segmenter.finish() {
+                        self.handle_segment_event(state, event)?;
+                    }
+                } else {
+                    // Drop the rest of the wake word utterance, keeping the clock
+                    state.segmenter.finish();
+                }
+                state.wake_word_armed = armed;
+
+                state.silence_ms = 0;
+                state.silence_reported = false;
+                state.heard_speech = false;
+                state.silence_timeout_reported = false;
             }
             AudioProcessorMsg::Shutdown => {
                 tracing::info!("AudioProcessorActor shutting down");
 
-                // Shutdown VAD thread if running
-                if let Some(vad_sender) = &state.vad_sender {
-                    let _ = vad_sender.send(VadRequest::Shutdown);
-                    tracing::info!("Sent shutdown signal to VAD thread");
+                // Drop any buffered audio
+                state.converter.reset();
+                if let Some(denoiser) = &mut state.denoiser {
+                    denoiser.reset();
+                }
+                if let Some(vad) = &mut state.vad {
+                    vad.reset();
                 }
+                state.segmenter.reset();
             }
         }
         Ok(())
@@ -325,3 +306,88 @@ impl Actor for AudioProcessorActor {
         Ok(())
     }
 }
+
+impl AudioProcessorActor {
+    // Stream speech to the transcriber and tell it when each utterance ends
+    fn handle_segment_event(
+        &self,
+        state: &mut AudioProcessorState,
+        event: SegmentEvent,
+    ) -> Result<(), ActorProcessingErr> {
+        if state.wake_word_armed {
+            return self.handle_wake_word_segment(state, event);
+        }
+
+        match event {
+            SegmentEvent::Started { start } => {
+                tracing::debug!("Utterance started at {:?}", start);
+                state
+                    .coordinator
+                    .send_message(CoordinatorMsg::UtteranceStarted(start))?;
+            }
+            SegmentEvent::Audio(samples) => {
+                state
+                    .transcriber
+                    .send_message(TranscriberMsg::ProcessAudioChunk(AudioChunk::mono(
+                        samples,
+                        state.sample_rate,
+                    )))?;
+            }
+            SegmentEvent::Ended(utterance) => {
+                tracing::debug!(
+                    "Utterance ended: {:?} - {:?}",
+                    utterance.start,
+                    utterance.end
+                );
+                state
+                    .transcriber
+                    .send_message(TranscriberMsg::FinalizeUtterance)?;
+                state
+                    .coordinator
+                    .send_message(CoordinatorMsg::UtteranceEnded(utterance))?;
+            }
+        }
+        Ok(())
+    }
+
+    // While armed, speech only goes to the wake word spotter
+    fn handle_wake_word_segment(
+        &self,
+        state: &mut AudioProcessorState,
+        event: SegmentEvent,
+    ) -> Result<(), ActorProcessingErr> {
+        let Some(detector) = &mut state.wake_word else {
+            return Ok(());
+        };
+
+        let detected = match event {
+            SegmentEvent::Started { .. } => None,
+            SegmentEvent::Audio(samples) => detector.process(&samples),
+            SegmentEvent::Ended(_) => detector.finish(),
+        };
+
+        if let Some(phrase) = detected {
+            self.wake_word_detected(state, phrase)?;
+        }
+        Ok(())
+    }
+
+    fn wake_word_detected(
+        &self,
+        state: &mut AudioProcessorState,
+        phrase: String,
+    ) -> Result<(), ActorProcessingErr> {
+        // Disarm right away so the next utterance is dictation
+        state.wake_word_armed = false;
+        if let Some(detector) = &mut state.wake_word {
+            detector.reset();
+        }
+        // The rest of the wake word utterance isn't dictation
+        state.segmenter.finish();
+
+        state
+            .coordinator
+            .send_message(CoordinatorMsg::WakeWordDetected(phrase))?;
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
-use regex::Regex;
 use std::collections::HashMap;
 use std::process::Command as ProcessCommand;
+use std::str::FromStr;
 use std::sync::Arc;
 use thiserror::Error;
 use tracing::{debug, info, warn};
 
-use crate::config::CommandAction;
+use crate::config::{CommandAction, EditAction};
+use crate::formatting::parse_number;
+use crate::hotkey::Hotkey;
 use crate::types::KeyboardOutputMsg;
 
 #[derive(Error, Debug)]
@@ -17,82 +19,702 @@ pub enum CommandError {
     ParseError(String),
 }
 
+/// A command trigger such as `go to line {n:number}`.
+///
+/// Literal words must be said as written (case and punctuation don't
+/// matter). Slots are written `{name:type}`, where the type is `number`
+/// (digits or spoken, like "forty two"), `one_of(a,b,...)` or `text` (any
+/// words, the default). Words said after the trigger are available to
+/// templates as `{args}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trigger {
+    parts: Vec<TriggerPart>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TriggerPart {
+    Word(String),
+    Slot { name: String, kind: SlotKind },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SlotKind {
+    Number,
+    OneOf(Vec<Vec<String>>), // Options, as normalized words
+    Text,
+}
+
+// A word of the transcription, with the form literal words are matched against
+struct SpokenWord<'a> {
+    original: &'a str,
+    normalized: String,
+}
+
+impl Trigger {
+    /// Names of the slots, in the order they appear.
+    pub fn slot_names(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            TriggerPart::Slot { name, .. } => Some(name.as_str()),
+            TriggerPart::Word(_) => None,
+        })
+    }
+
+    fn literal_words(&self) -> usize {
+        self.parts
+            .iter()
+            .filter(|part| matches!(part, TriggerPart::Word(_)))
+            .count()
+    }
+
+    // Match the start of `words`, returning the slot values and how many
+    // words the trigger took
+    fn match_start(&self, words: &[SpokenWord]) -> Option<(Vec<(String, String)>, usize)> {
+        let mut values = Vec::new();
+        let used = match_parts(&self.parts, words, &mut values)?;
+        Some((values, used))
+    }
+}
+
+fn match_parts(
+    parts: &[TriggerPart],
+    words: &[SpokenWord],
+    values: &mut Vec<(String, String)>,
+) -> Option<usize> {
+    let Some((part, rest)) = parts.split_first() else {
+        return Some(0);
+    };
+
+    match part {
+        TriggerPart::Word(expected) => {
+            if words.first()?.normalized != *expected {
+                return None;
+            }
+            match_parts(rest, &words[1..], values).map(|used| used + 1)
+        }
+        TriggerPart::Slot { name, kind } => {
+            // Try each way the slot could be filled until the rest matches
+            for (len, value) in slot_candidates(kind, words, rest.is_empty()) {
+                values.push((name.clone(), value));
+                if let Some(used) = match_parts(rest, &words[len..], values) {
+                    return Some(used + len);
+                }
+                values.pop();
+            }
+            None
+        }
+    }
+}
+
+// Possible slot values at the start of `words`, as (words taken, value) in
+// order of preference
+fn slot_candidates(kind: &SlotKind, words: &[SpokenWord], last: bool) -> Vec<(usize, String)> {
+    match kind {
+        SlotKind::Number => {
+            let Some(first) = words.first() else {
+                return Vec::new();
+            };
+            if !first.normalized.is_empty() && first.normalized.chars().all(|c| c.is_ascii_digit())
+            {
+                return vec![(1, first.normalized.clone())];
+            }
+            // Longest first, so "forty two" isn't read as "forty"
+            let normalized: Vec<&str> = words.iter().map(|word| word.normalized.as_str()).collect();
+            let Some((_, longest)) = parse_number(&normalized) else {
+                return Vec::new();
+            };
+            (1..=longest)
+                .rev()
+                .filter_map(|len| match parse_number(&normalized[..len]) {
+                    Some((value, used)) if used == len => Some((len, value.to_string())),
+                    _ => None,
+                })
+                .collect()
+        }
+        SlotKind::OneOf(options) => options
+            .iter()
+            .filter(|option| {
+                option.len() <= words.len()
+                    && option
+                        .iter()
+                        .zip(words)
+                        .all(|(expected, word)| word.normalized == *expected)
+            })
+            .map(|option| (option.len(), option.join(" ")))
+            .collect(),
+        // As few words as the rest of the trigger allows, or all of them at
+        // the end
+        SlotKind::Text if last => match words.len() {
+            0 => Vec::new(),
+            len => vec![(len, join_original(words))],
+        },
+        SlotKind::Text => (1..=words.len())
+            .map(|len| (len, join_original(&words[..len])))
+            .collect(),
+    }
+}
+
+impl FromStr for Trigger {
+    type Err = CommandError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| {
+            CommandError::ParseError(format!("invalid trigger '{}': {}", spec, reason))
+        };
+
+        let mut parts = Vec::new();
+        let mut rest = spec.trim();
+        while !rest.is_empty() {
+            if let Some(slot) = rest.strip_prefix('{') {
+                let end = slot
+                    .find('}')
+                    .ok_or_else(|| invalid("missing '}'".to_string()))?;
+                let (name, kind) = match slot[..end].split_once(':') {
+                    Some((name, kind)) => (name.trim(), kind.trim()),
+                    None => (slot[..end].trim(), "text"),
+                };
+
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(invalid(format!("bad slot name '{}'", name)));
+                }
+                if name == ARGS_SLOT {
+                    return Err(invalid(format!(
+                        "'{}' is reserved for the words after the trigger",
+                        ARGS_SLOT
+                    )));
+                }
+                if parts.iter().any(
+                    |part| matches!(part, TriggerPart::Slot { name: other, .. } if other == name),
+                ) {
+                    return Err(invalid(format!("slot '{}' appears twice", name)));
+                }
+
+                let kind = parse_slot_kind(kind).map_err(invalid)?;
+                parts.push(TriggerPart::Slot {
+                    name: name.to_string(),
+                    kind,
+                });
+                rest = slot[end + 1..].trim_start();
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '{')
+                    .unwrap_or(rest.len());
+                let word = &rest[..end];
+                if word.contains('}') {
+                    return Err(invalid("'}' without '{'".to_string()));
+                }
+                let normalized = normalize(word);
+                if normalized.is_empty() {
+                    return Err(invalid(format!("'{}' can't be spoken", word)));
+                }
+                parts.push(TriggerPart::Word(normalized));
+                rest = rest[end..].trim_start();
+            }
+        }
+
+        if parts.is_empty() {
+            return Err(invalid("empty".to_string()));
+        }
+        Ok(Trigger { parts })
+    }
+}
+
+fn parse_slot_kind(kind: &str) -> Result<SlotKind, String> {
+    match kind {
+        "number" => return Ok(SlotKind::Number),
+        "text" => return Ok(SlotKind::Text),
+        _ => {}
+    }
+
+    let options = kind
+        .strip_prefix("one_of(")
+        .and_then(|options| options.strip_suffix(')'))
+        .ok_or_else(|| {
+            format!(
+                "unknown slot type '{}' (use number, text or one_of(...))",
+                kind
+            )
+        })?;
+    let options: Vec<Vec<String>> = options
+        .split(',')
+        .map(|option| option.split_whitespace().map(normalize).collect())
+        .collect();
+    if options
+        .iter()
+        .any(|option: &Vec<String>| option.is_empty() || option.iter().any(String::is_empty))
+    {
+        return Err(format!("empty option in '{}'", kind));
+    }
+    Ok(SlotKind::OneOf(options))
+}
+
+// Placeholder for the words said after the trigger
+const ARGS_SLOT: &str = "args";
+
+/// Parse a command's trigger and check its template only uses slots the
+/// trigger has.
+pub fn validate_command(spec: &str, action: &CommandAction) -> Result<Trigger, CommandError> {
+    let trigger: Trigger = spec.parse()?;
+    let template = match action {
+        CommandAction::Type(template) | CommandAction::Exec(template) => template,
+        CommandAction::Edit(_) | CommandAction::Keys(_) => return Ok(trigger),
+    };
+    for placeholder in placeholders(template) {
+        if placeholder != ARGS_SLOT && !trigger.slot_names().any(|name| name == placeholder) {
+            return Err(CommandError::ParseError(format!(
+                "command '{}' uses {{{}}}, which isn't a slot of its trigger",
+                spec, placeholder
+            )));
+        }
+    }
+    Ok(trigger)
+}
+
+/// The configured commands with their triggers parsed, ready for matching.
+pub struct CommandSet {
+    // Sorted by trigger, so ties between matches don't depend on HashMap order
+    commands: Vec<(String, Trigger, CommandAction)>,
+}
+
+impl CommandSet {
+    /// Parse the commands, skipping any that are invalid.
+    pub fn new(commands: &HashMap<String, CommandAction>) -> Self {
+        let mut commands: Vec<_> = commands
+            .iter()
+            .filter_map(|(spec, action)| match validate_command(spec, action) {
+                Ok(trigger) => Some((spec.clone(), trigger, action.clone())),
+                Err(e) => {
+                    warn!("Ignoring command: {}", e);
+                    None
+                }
+            })
+            .collect();
+        commands.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        Self { commands }
+    }
+}
+
+// Names in {braces} that look like slot names
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+    template.split('{').skip(1).filter_map(|after| {
+        let name = &after[..after.find('}')?];
+        (!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+            .then_some(name)
+    })
+}
+
+// Substitute slot values and args in one pass, so braces in what was said
+// are left alone
+fn fill_template(template: &str, values: &[(String, String)], args: &str) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            let value = if name == ARGS_SLOT {
+                Some(args)
+            } else {
+                values
+                    .iter()
+                    .find(|(slot, _)| slot == name)
+                    .map(|(_, value)| value.as_str())
+            };
+            value.map(|value| (value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                filled.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+// Lowercase, without punctuation around the word ("Line," is "line")
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn join_original(words: &[SpokenWord]) -> String {
+    words
+        .iter()
+        .map(|word| word.original)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The trigger a transcription matched
+struct CommandMatch<'a> {
+    rank: usize, // Literal words in the trigger
+    trigger: &'a str,
+    action: &'a CommandAction,
+    values: Vec<(String, String)>, // Slot names and values
+    used: usize,                   // Words the trigger took
+}
+
 /// Process a transcription to check for commands.
 /// Returns Some(()) if a command was executed, or None if the text should be typed normally.
 pub fn process_command(
     text: &str,
-    commands: &HashMap<String, CommandAction>,
+    commands: &CommandSet,
     keyboard_output_fn: impl Fn(KeyboardOutputMsg) -> Result<(), Box<dyn std::error::Error>>,
 ) -> Result<Option<()>, CommandError> {
-    // Check if the text matches any command trigger
-    for (trigger, action) in commands {
-        // Escape special regex characters in the trigger pattern
-        let pattern = format!(r"(?i)^\s*{}\s*(.*)$", regex::escape(trigger));
-
-        // Parse the regex and check if it matches
-        match Regex::new(&pattern) {
-            Ok(regex) => {
-                if let Some(captures) = regex.captures(text) {
-                    // Extract arguments from the capture group
-                    let args = captures.get(1).map_or("", |m| m.as_str()).trim();
-
-                    info!(
-                        "Command trigger '{}' matched with args: '{}'",
-                        trigger, args
-                    );
-
-                    // Execute the command action
-                    match action {
-                        CommandAction::Type(template) => {
-                            // Substitute args into template
-                            let output_text = template.replace("{args}", args);
-                            debug!("Typing: {}", output_text);
-
-                            // Send to keyboard output
-                            keyboard_output_fn(KeyboardOutputMsg::TypeText(output_text))
-                                .map_err(|e| CommandError::ExecutionError(e.to_string()))?;
-                        }
-                        CommandAction::Exec(template) => {
-                            // Substitute args into template
-                            let command_str = template.replace("{args}", args);
-                            debug!("Executing: {}", command_str);
-
-                            // Split the command string into program and arguments
-                            let parts: Vec<&str> = command_str.split_whitespace().collect();
-                            if parts.is_empty() {
-                                return Err(CommandError::ExecutionError(
-                                    "Empty command".to_string(),
-                                ));
-                            }
-
-                            // Spawn a thread to execute the command
-                            let program = parts[0].to_string();
-                            let args: Vec<String> =
-                                parts[1..].iter().map(|s| s.to_string()).collect();
-
-                            std::thread::spawn(move || {
-                                match ProcessCommand::new(&program).args(&args).spawn() {
-                                    Ok(mut child) => match child.wait() {
-                                        Ok(status) => debug!("Command exited with: {}", status),
-                                        Err(e) => warn!("Failed to wait for command: {}", e),
-                                    },
-                                    Err(e) => warn!("Failed to execute command: {}", e),
-                                }
-                            });
-                        }
-                    }
-
-                    // Return that we handled a command
-                    return Ok(Some(()));
-                }
+    let words: Vec<SpokenWord> = text
+        .split_whitespace()
+        .map(|word| SpokenWord {
+            original: word,
+            normalized: normalize(word),
+        })
+        .collect();
+
+    // Check if the text matches any command trigger. The most specific one
+    // wins, so "go to line {n:number}" beats "go to {place}".
+    let mut best: Option<CommandMatch> = None;
+    for (spec, trigger, action) in &commands.commands {
+        let Some((values, used)) = trigger.match_start(&words) else {
+            continue;
+        };
+        let rank = trigger.literal_words();
+        let better = match &best {
+            None => true,
+            // Ties go to the first trigger in order
+            Some(best) => rank > best.rank,
+        };
+        if better {
+            best = Some(CommandMatch {
+                rank,
+                trigger: spec,
+                action,
+                values,
+                used,
+            });
+        }
+    }
+    let Some(CommandMatch {
+        trigger,
+        action,
+        values,
+        used,
+        ..
+    }) = best
+    else {
+        // No command matched
+        return Ok(None);
+    };
+
+    // Words after the trigger
+    let args = join_original(&words[used..]);
+    info!(
+        "Command trigger '{}' matched with slots {:?} and args: '{}'",
+        trigger, values, args
+    );
+
+    // Execute the command action
+    match action {
+        CommandAction::Type(template) => {
+            // Substitute slots and args into template
+            let output_text = fill_template(template, &values, &args);
+            debug!("Typing: {}", output_text);
+
+            // Send to keyboard output
+            keyboard_output_fn(KeyboardOutputMsg::TypeText(output_text))
+                .map_err(|e| CommandError::ExecutionError(e.to_string()))?;
+        }
+        CommandAction::Exec(template) => {
+            // Substitute slots and args into template
+            let command_str = fill_template(template, &values, &args);
+            debug!("Executing: {}", command_str);
+
+            // Split the command string into program and arguments
+            let parts: Vec<&str> = command_str.split_whitespace().collect();
+            if parts.is_empty() {
+                return Err(CommandError::ExecutionError("Empty command".to_string()));
             }
-            Err(e) => {
-                warn!("Invalid regex pattern for trigger '{}': {}", trigger, e);
-                continue;
+
+            // Spawn a thread to execute the command
+            let program = parts[0].to_string();
+            let args: Vec<String> = parts[1..].iter().map(|s| s.to_string()).collect();
+
+            std::thread::spawn(
+                move || match ProcessCommand::new(&program).args(&args).spawn() {
+                    Ok(mut child) => match child.wait() {
+                        Ok(status) => debug!("Command exited with: {}", status),
+                        Err(e) => warn!("Failed to wait for command: {}", e),
+                    },
+                    Err(e) => warn!("Failed to execute command: {}", e),
+                },
+            );
+        }
+        CommandAction::Edit(edit) => {
+            debug!("Editing: {:?}", edit);
+            run_edit(*edit, &args, &keyboard_output_fn)?;
+        }
+        CommandAction::Keys(keys) => {
+            debug!("Pressing: {}", keys);
+            keyboard_output_fn(KeyboardOutputMsg::PressKeys(keys.keys().to_vec()))
+                .map_err(|e| CommandError::ExecutionError(e.to_string()))?;
+        }
+    }
+
+    // Return that we handled a command
+    Ok(Some(()))
+}
+
+fn run_edit(
+    edit: EditAction,
+    args: &str,
+    keyboard_output_fn: impl Fn(KeyboardOutputMsg) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), CommandError> {
+    let send =
+        |msg| keyboard_output_fn(msg).map_err(|e| CommandError::ExecutionError(e.to_string()));
+    let keys = |spec: &str, times: usize| -> Result<KeyboardOutputMsg, CommandError> {
+        let keys = spec
+            .parse::<Hotkey>()
+            .map_err(|e| CommandError::ParseError(e.to_string()))?;
+        Ok(KeyboardOutputMsg::PressKeys(vec![keys; times]))
+    };
+
+    match edit {
+        EditAction::NewLine | EditAction::NewParagraph => {
+            let lines = if edit == EditAction::NewLine { 1 } else { 2 };
+            send(keys("Enter", lines)?)?;
+            // Dictation can carry on right after the trigger
+            if !args.is_empty() {
+                send(KeyboardOutputMsg::TypeText(args.to_string()))?;
             }
         }
+        EditAction::ScratchThat => send(KeyboardOutputMsg::ScratchThat)?,
+        EditAction::Undo => send(keys("Ctrl+Z", 1)?)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn commands(commands: &[(&str, CommandAction)]) -> CommandSet {
+        let commands: HashMap<String, CommandAction> = commands
+            .iter()
+            .map(|(spec, action)| (spec.to_string(), action.clone()))
+            .collect();
+        CommandSet::new(&commands)
+    }
+
+    fn typed(template: &str) -> CommandAction {
+        CommandAction::Type(template.to_string())
+    }
+
+    // What a transcription types, or None if no command matched
+    fn run(commands: &CommandSet, text: &str) -> Option<String> {
+        let sent = RefCell::new(Vec::new());
+        let matched = process_command(text, commands, |msg| {
+            sent.borrow_mut().push(msg);
+            Ok(())
+        })
+        .unwrap();
+        matched?;
+        match sent.into_inner().as_slice() {
+            [KeyboardOutputMsg::TypeText(text)] => Some(text.clone()),
+            sent => panic!("unexpected output {:?}", sent),
+        }
+    }
+
+    fn parse_error(spec: &str) -> String {
+        match spec.parse::<Trigger>() {
+            Ok(trigger) => panic!("'{}' parsed as {:?}", spec, trigger),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_literal_words_and_slots() {
+        let trigger: Trigger =
+            "Go to, line {n:number} in {file} {app:one_of(firefox, visual studio code)}"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            trigger.parts,
+            vec![
+                TriggerPart::Word("go".to_string()),
+                TriggerPart::Word("to".to_string()),
+                TriggerPart::Word("line".to_string()),
+                TriggerPart::Slot {
+                    name: "n".to_string(),
+                    kind: SlotKind::Number
+                },
+                TriggerPart::Word("in".to_string()),
+                TriggerPart::Slot {
+                    name: "file".to_string(),
+                    kind: SlotKind::Text
+                },
+                TriggerPart::Slot {
+                    name: "app".to_string(),
+                    kind: SlotKind::OneOf(vec![
+                        vec!["firefox".to_string()],
+                        vec![
+                            "visual".to_string(),
+                            "studio".to_string(),
+                            "code".to_string()
+                        ],
+                    ])
+                },
+            ]
+        );
+        assert_eq!(
+            trigger.slot_names().collect::<Vec<_>>(),
+            ["n", "file", "app"]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_triggers() {
+        assert!(parse_error("go to {line").contains("missing '}'"));
+        assert!(parse_error("go to line}").contains("'}' without '{'"));
+        assert!(parse_error("go {n:integer}").contains("unknown slot type 'integer'"));
+        assert!(parse_error("go {}").contains("bad slot name"));
+        assert!(parse_error("go {my slot}").contains("bad slot name"));
+        assert!(parse_error("search {args}").contains("reserved"));
+        assert!(parse_error("{a} and {a}").contains("appears twice"));
+        assert!(parse_error("{app:one_of(firefox,,vim)}").contains("empty option"));
+        assert!(parse_error("go -- now").contains("can't be spoken"));
+        assert!(parse_error("  ").contains("empty"));
+    }
+
+    #[test]
+    fn number_slots_take_digits_and_spoken_numbers() {
+        let commands = commands(&[("go to line {n:number}", typed("line {n}"))]);
+        assert_eq!(run(&commands, "go to line 17"), Some("line 17".to_string()));
+        assert_eq!(
+            run(&commands, "Go to line forty two."),
+            Some("line 42".to_string())
+        );
+        assert_eq!(
+            run(&commands, "go to line one hundred and five"),
+            Some("line 105".to_string())
+        );
+        assert_eq!(
+            run(&commands, "go to line seventeen"),
+            Some("line 17".to_string())
+        );
+        assert_eq!(
+            run(&commands, "go to line ten"),
+            Some("line 10".to_string())
+        );
+        assert_eq!(run(&commands, "go to line the end"), None);
+        assert_eq!(run(&commands, "go to line"), None);
+    }
+
+    #[test]
+    fn number_slots_give_up_words_the_rest_of_the_trigger_needs() {
+        let commands = commands(&[("indent {n:number} one", typed("{n}"))]);
+        assert_eq!(run(&commands, "indent twenty one"), Some("20".to_string()));
+        assert_eq!(
+            run(&commands, "indent twenty one one"),
+            Some("21".to_string())
+        );
+    }
+
+    #[test]
+    fn one_of_slots_only_take_their_options() {
+        let commands = commands(&[(
+            "switch to {app:one_of(firefox,visual studio code)}",
+            typed("{app}"),
+        )]);
+        assert_eq!(
+            run(&commands, "switch to Firefox"),
+            Some("firefox".to_string())
+        );
+        assert_eq!(
+            run(&commands, "switch to visual studio code"),
+            Some("visual studio code".to_string())
+        );
+        assert_eq!(run(&commands, "switch to visual studio"), None);
+        assert_eq!(run(&commands, "switch to chrome"), None);
+    }
+
+    #[test]
+    fn text_slots_stop_where_the_rest_of_the_trigger_matches() {
+        let commands = commands(&[("rename {old} to {new}", typed("{old} -> {new}"))]);
+        assert_eq!(
+            run(&commands, "rename old name to new name"),
+            Some("old name -> new name".to_string())
+        );
+        assert_eq!(run(&commands, "rename to something"), None);
+    }
+
+    #[test]
+    fn words_after_the_trigger_are_args() {
+        let commands = commands(&[
+            ("search for", typed("search: {args}")),
+            ("go to line {n:number}", typed("{n}/{args}")),
+        ]);
+        assert_eq!(
+            run(&commands, "Search for rust docs"),
+            Some("search: rust docs".to_string())
+        );
+        assert_eq!(run(&commands, "search for"), Some("search: ".to_string()));
+        assert_eq!(
+            run(&commands, "go to line 3 please"),
+            Some("3/please".to_string())
+        );
+        // Whole words only
+        assert_eq!(run(&commands, "searching for rust"), None);
+        assert_eq!(run(&commands, "please search for rust"), None);
+    }
+
+    #[test]
+    fn most_specific_trigger_wins() {
+        let commands = commands(&[
+            ("go to {place}", typed("place {place}")),
+            ("go to line {n:number}", typed("line {n}")),
+        ]);
+        assert_eq!(run(&commands, "go to line 4"), Some("line 4".to_string()));
+        assert_eq!(
+            run(&commands, "go to the kitchen"),
+            Some("place the kitchen".to_string())
+        );
+    }
+
+    #[test]
+    fn templates_are_filled_in_one_pass() {
+        let values = vec![
+            ("a".to_string(), "{b}".to_string()),
+            ("b".to_string(), "{args}".to_string()),
+        ];
+        assert_eq!(
+            fill_template("{a} {b} {args} {c} {", &values, "{a}"),
+            "{b} {args} {a} {c} {"
+        );
+    }
+
+    #[test]
+    fn templates_may_only_use_their_triggers_slots() {
+        assert!(validate_command("open {file}", &typed("{file} {args}")).is_ok());
+        assert!(validate_command("open {file}", &typed("{{not a slot}} {}")).is_ok());
+        let error = validate_command("open {file}", &typed("{path}"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("{path}"), "{}", error);
     }
 
-    // No command matched
-    Ok(None)
+    #[test]
+    fn invalid_commands_are_skipped() {
+        let commands = commands(&[
+            ("open {file}", typed("{path}")),
+            ("go to {", typed("")),
+            ("hello", typed("hi")),
+        ]);
+        assert_eq!(commands.commands.len(), 1);
+        assert_eq!(run(&commands, "hello"), Some("hi".to_string()));
+    }
 }
@@ -5,26 +5,143 @@ use std::sync::Arc;
 
 use config::{Config, ConfigError, File};
 use dirs::config_dir;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use tracing::{error, info};
+use whisperkey_protocol::IpcFormat;
+
+use crate::hotkey::KeySequence;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)] // Fields missing from older config files fall back to defaults
 pub struct Settings {
     pub model_path: Option<String>,
+    pub transcriber_path: Option<String>, // Transcriber executable (None = search for it)
+    pub ipc_format: IpcFormat,            // Transcriber wire format (Json for debugging)
+    pub transcriber_startup_timeout_ms: u32, // Time allowed for the model to load
+    pub transcriber_max_restarts: u32,    // Consecutive crashes before giving up
+    pub stub_script: Option<String>,      // Script for the stub backend (None = built in)
+    pub transcription_backend: TranscriptionBackend,
+    pub whisper_model: String, // Whisper model size and quantization, e.g. "base.en-q5_1"
+    pub whisper_threads: u32,  // Threads for Whisper (0 = use all cores)
+    pub language: String,      // Whisper language code, or "auto" to detect
+    pub input_device: Option<String>, // Preferred input device name (None = system default)
     pub enable_denoise: bool,
     pub enable_vad: bool,
     pub vad_mode: VadMode,
     pub vad_energy_threshold: f32, // Energy threshold for VAD (0.0 to 1.0)
+    pub vad_frame_ms: u32,         // VAD frame length: 10, 20 or 30 ms
+    pub vad_speech_start_ms: u32,  // Voiced audio needed before speech starts
+    pub vad_speech_end_ms: u32,    // Hangover: unvoiced audio needed before speech ends
+    pub vad_pre_roll_ms: u32,      // Audio kept from before speech starts
+    pub max_utterance_ms: u32,     // Longer utterances are split
     pub silence_threshold_ms: u32, // Time in ms to consider silence
+    pub activation_mode: ActivationMode,
+    pub auto_stop_silence_ms: u32, // Silence after speech that ends an AutoStop or WakeWord session
+    pub max_session_secs: u32, // AutoStop and WakeWord sessions end after this long (0 = no limit)
+    pub wake_phrases: Vec<String>, // Phrases that start dictation in WakeWord mode
+    pub wake_word_sensitivity: f32, // 0.0 to 1.0, higher accepts less certain matches
+    pub wake_word_model_path: Option<String>, // Vosk model for wake phrases (None = model_path)
+    pub hotkey: Option<String>, // Global hotkey, e.g. "Ctrl+Alt+Space" (None = disabled)
+    pub hotkey_mode: HotkeyMode,
+    pub hotkey_backend: HotkeyBackend,
     pub enable_keyboard_output: bool, // Enable keyboard output typing
     pub keyboard_output_delay_ms: u32, // Delay before typing begins
+    pub typing_chunk_chars: u32,      // Characters typed per step (0 = all at once)
+    pub typing_chunk_delay_ms: u32,   // Pause between steps, when typing can be cancelled
+    pub abort_hotkey: Option<String>, // Global hotkey that cancels typing (None = disabled)
+    pub live_typing: bool, // Type partial results while speaking, corrected as they change
+    pub live_typing_max_rewrite_chars: u32, // Most characters a partial result may backspace
+    pub format_capitalize: bool, // Capitalize sentences and "I"
+    pub format_spoken_punctuation: bool, // Type "comma", "period", ... as punctuation
+    pub format_spacing: bool, // Separate consecutive utterances with a space
+    pub format_numbers: bool, // Type "twenty three" as "23"
+    pub injection_backend: InjectionBackend,
+    pub output_method: OutputMethod,
+    pub paste_shortcut: PasteShortcut,
+    pub restore_clipboard: bool, // Put the previous clipboard text back after pasting
+    #[serde(deserialize_with = "deserialize_commands")]
     pub commands: HashMap<String, CommandAction>, // Command triggers and actions
+    #[serde(skip)]
+    pub unreadable_commands: HashMap<String, toml::Value>, // Kept as written so saving doesn't drop them
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum CommandAction {
-    Type(String), // Template for text to type
-    Exec(String), // Template for command to execute
+    Type(String),      // Template for text to type
+    Exec(String),      // Template for command to execute
+    Edit(EditAction),  // Built-in editing action
+    Keys(KeySequence), // Keys to press, e.g. "ctrl+shift+t" or "{enter}{tab}"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum EditAction {
+    NewLine,      // Enter, then type anything said after the trigger
+    NewParagraph, // Enter twice, then type anything said after the trigger
+    ScratchThat,  // Delete the text typed last
+    Undo,         // Ctrl+Z
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TranscriptionBackend {
+    Vosk,    // Streaming, with partial results
+    Whisper, // Transcribes each utterance once it ends
+    Stub,    // Replays scripted text without a model, for testing
+}
+
+impl TranscriptionBackend {
+    // Value for the transcriber's --backend flag
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            TranscriptionBackend::Vosk => "vosk",
+            TranscriptionBackend::Whisper => "whisper",
+            TranscriptionBackend::Stub => "stub",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ActivationMode {
+    Manual,   // Listen until stopped with the UI or the hotkey
+    AutoStop, // Also stop once the speaker has been quiet for a while
+    WakeWord, // Always listen for a wake phrase, dictate until quiet, then wait again
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum HotkeyMode {
+    PushToTalk, // Listen while the hotkey is held
+    Toggle,     // Each press starts or stops listening
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum HotkeyBackend {
+    Auto,  // evdev if /dev/input is readable, otherwise X11
+    Evdev, // Read keyboards directly, works everywhere but needs the 'input' group
+    X11,   // Key grab on the X11 root window
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum InjectionBackend {
+    Auto,            // Pick one for the session type (Wayland, X11 or console)
+    Enigo,           // X11 and XWayland windows
+    Uinput,          // Virtual input device, needs write access to /dev/uinput
+    VirtualKeyboard, // Wayland zwp_virtual_keyboard_v1 (wlroots compositors, KDE)
+    InputMethod,     // Wayland zwp_input_method_v2, text fields only
+    Wtype,           // The wtype command (same protocol as VirtualKeyboard)
+    Ydotool,         // The ydotool command, needs ydotoold running
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum OutputMethod {
+    Type,  // Simulate a key press for every character
+    Paste, // Put the text on the clipboard and send the paste shortcut
+    Auto,  // Paste long or non-ASCII text, type the rest
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum PasteShortcut {
+    CtrlV,
+    CtrlShiftV, // Most terminals
+    ShiftInsert,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
@@ -66,16 +183,81 @@ impl Default for Settings {
             CommandAction::Exec("start https://www.google.com/search?q={args}".to_string()),
         );
 
+        // Editing while dictating
+        commands.insert(
+            "new line".to_string(),
+            CommandAction::Edit(EditAction::NewLine),
+        );
+        commands.insert(
+            "new paragraph".to_string(),
+            CommandAction::Edit(EditAction::NewParagraph),
+        );
+        commands.insert(
+            "scratch that".to_string(),
+            CommandAction::Edit(EditAction::ScratchThat),
+        );
+        commands.insert(
+            "delete that".to_string(),
+            CommandAction::Edit(EditAction::ScratchThat),
+        );
+        commands.insert("undo".to_string(), CommandAction::Edit(EditAction::Undo));
+
+        // Shortcuts that work in most applications
+        for (trigger, keys) in [("next tab", "ctrl+tab"), ("save file", "ctrl+s")] {
+            commands.insert(
+                trigger.to_string(),
+                CommandAction::Keys(keys.parse().expect("default key sequences are valid")),
+            );
+        }
+
         Self {
             model_path: None,
+            transcriber_path: None,
+            ipc_format: IpcFormat::Binary,
+            transcriber_startup_timeout_ms: 60000, // Large Whisper models load slowly
+            transcriber_max_restarts: 5,
+            stub_script: None,
+            transcription_backend: TranscriptionBackend::Vosk,
+            whisper_model: "base.en".to_string(),
+            whisper_threads: 0,
+            language: "en".to_string(),
+            input_device: None,
             enable_denoise: true,
             enable_vad: true,
             vad_mode: VadMode::Quality,
             vad_energy_threshold: 0.01, // Default threshold (lower values are more sensitive)
+            vad_frame_ms: 30,
+            vad_speech_start_ms: 90,
+            vad_speech_end_ms: 450,
+            vad_pre_roll_ms: 300,
+            max_utterance_ms: 30000,
             silence_threshold_ms: 1000, // 1 second of silence
+            activation_mode: ActivationMode::Manual,
+            auto_stop_silence_ms: 2000,
+            max_session_secs: 300, // 5 minutes
+            wake_phrases: vec!["hey computer".to_string()],
+            wake_word_sensitivity: 0.5,
+            wake_word_model_path: None,
+            hotkey: None,
+            hotkey_mode: HotkeyMode::Toggle,
+            hotkey_backend: HotkeyBackend::Auto,
             enable_keyboard_output: false, // Disabled by default for safety
             keyboard_output_delay_ms: 500, // 500ms delay by default
+            typing_chunk_chars: 16,
+            typing_chunk_delay_ms: 20,
+            abort_hotkey: None,
+            live_typing: false,
+            live_typing_max_rewrite_chars: 24,
+            format_capitalize: true,
+            format_spoken_punctuation: true,
+            format_spacing: true,
+            format_numbers: false,
+            injection_backend: InjectionBackend::Auto,
+            output_method: OutputMethod::Type,
+            paste_shortcut: PasteShortcut::CtrlV,
+            restore_clipboard: true,
             commands,
+            unreadable_commands: HashMap::new(),
         }
     }
 }
@@ -119,17 +301,48 @@ pub fn load_config() -> Result<Arc<Settings>, ConfigError> {
         .add_source(File::from(config_file))
         .build()?;
 
-    // Deserialize the config
-    let settings: Settings = s.try_deserialize()?;
-    Ok(Arc::new(settings))
+    Ok(Arc::new(read_settings(s)?))
+}
+
+// Deserialize the config, holding on to commands that don't parse. Commands
+// that parse but are invalid stay in `commands` and are skipped by
+// `CommandSet`, so the settings window never saves a command away
+fn read_settings(config: Config) -> Result<Settings, ConfigError> {
+    let mut settings: Settings = config.clone().try_deserialize()?;
+    settings.unreadable_commands = config
+        .get::<HashMap<String, toml::Value>>("commands")
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(trigger, _)| !settings.commands.contains_key(trigger))
+        .collect();
+    Ok(settings)
+}
+
+// Read each command on its own, so one that doesn't parse (e.g. a misspelled
+// key) is skipped rather than failing the whole config
+fn deserialize_commands<'de, D>(deserializer: D) -> Result<HashMap<String, CommandAction>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let commands = HashMap::<String, toml::Value>::deserialize(deserializer)?;
+    Ok(commands
+        .into_iter()
+        .filter_map(
+            |(trigger, action)| match CommandAction::deserialize(action) {
+                Ok(action) => Some((trigger, action)),
+                Err(e) => {
+                    error!("Ignoring command '{}': {}", trigger, e);
+                    None
+                }
+            },
+        )
+        .collect())
 }
 
 pub fn save_config(settings: &Settings) -> Result<(), ConfigError> {
     let config_file = get_config_file_path();
 
-    // Serialize the settings to TOML
-    let toml = toml::to_string_pretty(settings)
-        .map_err(|e| ConfigError::Message(format!("Failed to serialize config: {}", e)))?;
+    let toml = to_toml(settings)?;
 
     // Write the config file
     fs::write(&config_file, toml)
@@ -138,3 +351,98 @@ pub fn save_config(settings: &Settings) -> Result<(), ConfigError> {
     info!("Config saved to {:?}", config_file);
     Ok(())
 }
+
+// Serialize the settings, putting back any commands that didn't parse
+fn to_toml(settings: &Settings) -> Result<String, ConfigError> {
+    let mut value = toml::Value::try_from(settings)
+        .map_err(|e| ConfigError::Message(format!("Failed to serialize config: {}", e)))?;
+    if let Some(commands) = value
+        .get_mut("commands")
+        .and_then(toml::Value::as_table_mut)
+    {
+        for (trigger, action) in &settings.unreadable_commands {
+            commands.insert(trigger.clone(), action.clone());
+        }
+    }
+    toml::to_string_pretty(&value)
+        .map_err(|e| ConfigError::Message(format!("Failed to serialize config: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::FileFormat;
+
+    fn parse(toml: &str) -> Settings {
+        let config = Config::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()
+            .unwrap();
+        read_settings(config).unwrap()
+    }
+
+    #[test]
+    fn bad_commands_only_lose_themselves() {
+        let settings = parse(
+            r#"
+            enable_keyboard_output = false
+
+            [commands]
+            "save file" = { Keys = "ctrl+s" }
+            "next tab" = { Keys = "ctrl+nokey" }
+            "new line" = { Edit = "NewLine" }
+            "greet" = { Type = "hello" }
+            "dance" = { Dance = "now" }
+            "#,
+        );
+        assert!(!settings.enable_keyboard_output);
+
+        let mut triggers: Vec<&str> = settings.commands.keys().map(String::as_str).collect();
+        triggers.sort();
+        assert_eq!(triggers, ["greet", "new line", "save file"]);
+        let mut unreadable: Vec<&str> = settings
+            .unreadable_commands
+            .keys()
+            .map(String::as_str)
+            .collect();
+        unreadable.sort();
+        assert_eq!(unreadable, ["dance", "next tab"]);
+        assert_eq!(
+            settings.commands["save file"],
+            CommandAction::Keys("ctrl+s".parse().unwrap())
+        );
+        assert_eq!(
+            settings.commands["new line"],
+            CommandAction::Edit(EditAction::NewLine)
+        );
+    }
+
+    #[test]
+    fn default_commands_round_trip() {
+        let defaults = Settings::default();
+        let saved = to_toml(&defaults).unwrap();
+        assert_eq!(parse(&saved).commands, defaults.commands);
+    }
+
+    #[test]
+    fn bad_commands_survive_saving() {
+        let settings = parse(
+            r#"
+            [commands]
+            "save file" = { Keys = "ctrl+s" }
+            "next tab" = { Keys = "ctrl+nokey" }
+            "dance" = { Dance = "now" }
+            "open {file}" = { Type = "{path}" }
+            "#,
+        );
+        // Parses, but only `CommandSet` checks its slots
+        assert!(settings.commands.contains_key("open {file}"));
+        let saved = parse(&to_toml(&settings).unwrap());
+        assert_eq!(saved.commands, settings.commands);
+        assert_eq!(saved.unreadable_commands, settings.unreadable_commands);
+        assert_eq!(
+            saved.unreadable_commands["dance"]["Dance"].as_str(),
+            Some("now")
+        );
+    }
+}
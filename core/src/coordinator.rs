@@ -1,20 +1,40 @@
-use ractor::{Actor, ActorProcessingErr, ActorRef};
+use ractor::{Actor, ActorCell, ActorProcessingErr, ActorRef, SupervisionEvent};
+use std::cell::Cell;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
 
 use crate::{
     audio_capture::AudioCaptureActor,
     audio_processor::AudioProcessorActor,
-    command,
-    config::{self, Settings},
+    command::{self, CommandSet},
+    config::{self, ActivationMode, HotkeyMode, Settings},
+    formatting::TextFormatter,
+    hotkey::{self, Hotkey, HotkeyListener},
     keyboard_output::KeyboardOutputActor,
     transcriber::TranscriberActor,
     types::{
-        AppOutput, AudioCaptureMsg, AudioProcessorMsg, CoordinatorMsg, KeyboardOutputMsg,
-        TranscriberMsg,
+        AppOutput, AudioCaptureMsg, AudioProcessorMsg, Component, CoordinatorMsg, HotkeyAction,
+        IpcHello, KeyboardOutputMsg, ListeningState, TranscriberMsg,
     },
 };
 
+// A child that fails more often than this within the window is given up on
+const MAX_CHILD_RESTARTS: usize = 3;
+const CHILD_RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+// Backends don't report utterances that transcribe to nothing, so stop
+// waiting for a result after this long
+const PROCESSING_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Error, Debug)]
+#[error("can't go from {from} to {to}")]
+pub struct InvalidTransition {
+    pub from: ListeningState,
+    pub to: ListeningState,
+}
+
 pub struct Coordinator {
     // Empty struct, state is in CoordinatorState
 }
@@ -25,8 +45,310 @@ pub struct CoordinatorState {
     audio_processor: Option<ActorRef<AudioProcessorMsg>>,
     transcriber: Option<ActorRef<TranscriberMsg>>,
     keyboard_output: Option<ActorRef<KeyboardOutputMsg>>,
-    sample_rate: u32,      // Add sample rate for transcriber
-    config: Arc<Settings>, // Configuration loaded from file
+    sample_rate: u32,                      // Add sample rate for transcriber
+    config: Arc<Settings>,                 // Configuration loaded from file
+    transcriber_info: Option<IpcHello>,    // Capabilities from the transcriber handshake
+    transcriber_restarts: u32,             // Times the transcriber process was restarted
+    transcriber_last_exit: Option<String>, // Exit status of the last crashed process
+    model_path: Option<PathBuf>,           // Kept to restart the transcriber actor
+    listening_state: ListeningState,
+    capture_active: bool,          // Capture was started and not stopped since
+    in_utterance: bool,            // The VAD is inside an utterance
+    utterance_count: u64,          // Utterances ended so far, to match processing timeouts
+    pending_typing: usize,         // TypeText messages the keyboard hasn't finished
+    live_partial: Option<String>,  // Last partial typed live, until its final result
+    formatter: TextFormatter,      // Remembers how the last typed utterance ended
+    commands: CommandSet,          // Command triggers, parsed once from the config
+    session: u64,                  // Capture sessions started so far, to match session timeouts
+    keyboard_output_enabled: bool, // Restarted keyboard output keeps the toggle
+    wake_word_available: bool,     // WakeWord mode and the spotter loaded
+    wake_word_armed: bool,         // The audio processor is waiting for the wake word
+    child_restarts: Vec<(Component, Instant)>, // Recent supervisor restarts
+    _hotkey_listeners: Vec<HotkeyListener>, // Dropping them stops the listeners
+}
+
+impl CoordinatorState {
+    // Move to a new listening state, telling the UI about it
+    fn transition(&mut self, next: ListeningState) -> Result<(), InvalidTransition> {
+        if !self.listening_state.can_transition_to(next) {
+            return Err(InvalidTransition {
+                from: self.listening_state,
+                to: next,
+            });
+        }
+        tracing::debug!("Listening state: {} -> {}", self.listening_state, next);
+        self.listening_state = next;
+        (self.ui_sender)(AppOutput::StateChanged(next));
+        Ok(())
+    }
+
+    // Transitions driven by events rather than the user can only be logged
+    fn transition_or_log(&mut self, next: ListeningState) {
+        if let Err(e) = self.transition(next) {
+            tracing::warn!("Ignoring listening state change: {}", e);
+        }
+    }
+
+    // The state to settle in once nothing is pending
+    fn resting_state(&self) -> ListeningState {
+        if self.capture_active && self.wake_word_armed {
+            ListeningState::WaitingForWakeWord
+        } else if self.capture_active {
+            ListeningState::Listening
+        } else {
+            ListeningState::Idle
+        }
+    }
+
+    // Start a dictation session, capped in case the silence timeout never
+    // fires (e.g. a noisy room)
+    fn start_session(&mut self, myself: &ActorRef<CoordinatorMsg>) {
+        self.session += 1;
+        // The cursor may be somewhere else entirely by now
+        self.formatter.reset();
+        if self.config.activation_mode != ActivationMode::Manual && self.config.max_session_secs > 0
+        {
+            let session = self.session;
+            myself.send_after(
+                Duration::from_secs(self.config.max_session_secs as u64),
+                move || CoordinatorMsg::SessionTimeout { session },
+            );
+        }
+    }
+
+    // End a dictation session: back to waiting for the wake word if we can,
+    // otherwise stop capture. Returns whether it went back to waiting.
+    fn end_session(
+        &mut self,
+        myself: &ActorRef<CoordinatorMsg>,
+    ) -> Result<bool, ActorProcessingErr> {
+        if !self.wake_word_available {
+            myself.send_message(CoordinatorMsg::StopListening)?;
+            return Ok(false);
+        }
+
+        self.set_wake_word_armed(true)?;
+        if self.listening_state == ListeningState::Listening {
+            self.transition_or_log(ListeningState::WaitingForWakeWord);
+        }
+        Ok(true)
+    }
+
+    fn set_wake_word_armed(&mut self, armed: bool) -> Result<(), ActorProcessingErr> {
+        if let Some(audio_processor) = &self.audio_processor {
+            audio_processor.send_message(AudioProcessorMsg::ArmWakeWord(armed))?;
+        }
+        self.wake_word_armed = armed;
+        Ok(())
+    }
+
+    fn reject(&self, request: &str, error: InvalidTransition) {
+        tracing::warn!("Rejected {}: {}", request, error);
+        (self.ui_sender)(AppOutput::UpdateStatus(format!(
+            "Can't {} while {}",
+            request,
+            self.listening_state.to_string().to_lowercase()
+        )));
+    }
+
+    // Which of our children a supervision event is about
+    fn component_of(&self, cell: &ActorCell) -> Option<Component> {
+        let id = cell.get_id();
+        if self.audio_capture.as_ref().map(|a| a.get_id()) == Some(id) {
+            Some(Component::AudioCapture)
+        } else if self.audio_processor.as_ref().map(|a| a.get_id()) == Some(id) {
+            Some(Component::AudioProcessor)
+        } else if self.transcriber.as_ref().map(|a| a.get_id()) == Some(id) {
+            Some(Component::Transcriber)
+        } else if self.keyboard_output.as_ref().map(|a| a.get_id()) == Some(id) {
+            Some(Component::KeyboardOutput)
+        } else {
+            None
+        }
+    }
+
+    fn clear(&mut self, component: Component) {
+        match component {
+            Component::AudioCapture => self.audio_capture = None,
+            Component::AudioProcessor => self.audio_processor = None,
+            Component::Transcriber => {
+                self.transcriber = None;
+                self.transcriber_info = None;
+            }
+            Component::KeyboardOutput => {
+                self.keyboard_output = None;
+                // Whatever it was typing is gone
+                self.pending_typing = 0;
+                self.live_partial = None;
+                if self.listening_state == ListeningState::Typing {
+                    let next = self.resting_state();
+                    self.transition_or_log(next);
+                }
+            }
+        }
+    }
+
+    // Record a restart, returning false once the component has used up its budget
+    fn allow_restart(&mut self, component: Component) -> bool {
+        let now = Instant::now();
+        self.child_restarts
+            .retain(|(_, at)| now.duration_since(*at) < CHILD_RESTART_WINDOW);
+        let recent = self
+            .child_restarts
+            .iter()
+            .filter(|(c, _)| *c == component)
+            .count();
+        if recent >= MAX_CHILD_RESTARTS {
+            return false;
+        }
+        self.child_restarts.push((component, now));
+        true
+    }
+}
+
+fn spawn_error(component: Component, e: impl std::fmt::Display) -> ActorProcessingErr {
+    ActorProcessingErr::from(std::io::Error::other(format!(
+        "Failed to start {} actor: {}",
+        component.to_string().to_lowercase(),
+        e
+    )))
+}
+
+// Children are linked to the coordinator, so their failures arrive in
+// handle_supervisor_evt. These are used both at startup and for restarts.
+impl Coordinator {
+    async fn spawn_audio_capture(
+        myself: &ActorRef<CoordinatorMsg>,
+        config: &Arc<Settings>,
+    ) -> Result<ActorRef<AudioCaptureMsg>, ActorProcessingErr> {
+        let (actor, _) = Actor::spawn_linked(
+            None,
+            AudioCaptureActor {},
+            (myself.clone(), config.clone()),
+            myself.get_cell(),
+        )
+        .await
+        .map_err(|e| spawn_error(Component::AudioCapture, e))?;
+        Ok(actor)
+    }
+
+    async fn spawn_transcriber(
+        myself: &ActorRef<CoordinatorMsg>,
+        sample_rate: u32,
+        model_path: Option<PathBuf>,
+        config: &Arc<Settings>,
+    ) -> Result<ActorRef<TranscriberMsg>, ActorProcessingErr> {
+        let (actor, _) = Actor::spawn_linked(
+            None,
+            TranscriberActor {},
+            (myself.clone(), sample_rate, model_path, config.clone()),
+            myself.get_cell(),
+        )
+        .await
+        .map_err(|e| spawn_error(Component::Transcriber, e))?;
+        Ok(actor)
+    }
+
+    async fn spawn_audio_processor(
+        myself: &ActorRef<CoordinatorMsg>,
+        transcriber: ActorRef<TranscriberMsg>,
+        sample_rate: u32,
+        config: &Arc<Settings>,
+    ) -> Result<ActorRef<AudioProcessorMsg>, ActorProcessingErr> {
+        let (actor, _) = Actor::spawn_linked(
+            None,
+            AudioProcessorActor {},
+            (myself.clone(), transcriber, sample_rate, config.clone()),
+            myself.get_cell(),
+        )
+        .await
+        .map_err(|e| spawn_error(Component::AudioProcessor, e))?;
+        Ok(actor)
+    }
+
+    async fn spawn_keyboard_output(
+        myself: &ActorRef<CoordinatorMsg>,
+        config: &Arc<Settings>,
+    ) -> Result<ActorRef<KeyboardOutputMsg>, ActorProcessingErr> {
+        let (actor, _) = Actor::spawn_linked(
+            None,
+            KeyboardOutputActor {},
+            (myself.clone(), config.clone()),
+            myself.get_cell(),
+        )
+        .await
+        .map_err(|e| spawn_error(Component::KeyboardOutput, e))?;
+        Ok(actor)
+    }
+
+    // Respawn a failed child with its original arguments and rewire the refs
+    async fn restart_component(
+        &self,
+        myself: &ActorRef<CoordinatorMsg>,
+        state: &mut CoordinatorState,
+        component: Component,
+    ) -> Result<(), ActorProcessingErr> {
+        match component {
+            Component::AudioCapture => {
+                let audio_capture = Self::spawn_audio_capture(myself, &state.config).await?;
+                if state.capture_active {
+                    audio_capture.send_message(AudioCaptureMsg::Start)?;
+                }
+                state.audio_capture = Some(audio_capture);
+            }
+            Component::Transcriber => {
+                let transcriber = Self::spawn_transcriber(
+                    myself,
+                    state.sample_rate,
+                    state.model_path.clone(),
+                    &state.config,
+                )
+                .await?;
+                state.transcriber = Some(transcriber.clone());
+
+                // The audio processor holds the transcriber's ref, so it has to
+                // be replaced too. Taking it out of the state first means its
+                // termination event is ignored.
+                if let Some(old_processor) = state.audio_processor.take() {
+                    old_processor.stop(Some("transcriber restarted".to_string()));
+                }
+                let audio_processor = Self::spawn_audio_processor(
+                    myself,
+                    transcriber,
+                    state.sample_rate,
+                    &state.config,
+                )
+                .await?;
+                if state.wake_word_armed {
+                    audio_processor.send_message(AudioProcessorMsg::ArmWakeWord(true))?;
+                }
+                state.audio_processor = Some(audio_processor);
+            }
+            Component::AudioProcessor => {
+                let Some(transcriber) = state.transcriber.clone() else {
+                    return Err(spawn_error(component, "transcriber is not running"));
+                };
+                let audio_processor = Self::spawn_audio_processor(
+                    myself,
+                    transcriber,
+                    state.sample_rate,
+                    &state.config,
+                )
+                .await?;
+                if state.wake_word_armed {
+                    audio_processor.send_message(AudioProcessorMsg::ArmWakeWord(true))?;
+                }
+                state.audio_processor = Some(audio_processor);
+            }
+            Component::KeyboardOutput => {
+                let keyboard_output = Self::spawn_keyboard_output(myself, &state.config).await?;
+                keyboard_output
+                    .send_message(KeyboardOutputMsg::Enable(state.keyboard_output_enabled))?;
+                state.keyboard_output = Some(keyboard_output);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[ractor::async_trait]
@@ -57,74 +379,50 @@ impl Actor for Coordinator {
 
         // Log model path if provided
         if let Some(path) = &model_path {
-            tracing::info!("Using Vosk model at: {:?}", path);
+            tracing::info!("Using model at: {:?}", path);
         } else {
             tracing::warn!("No model path provided, using default");
         }
 
-        // Use a fixed sample rate for now - could be configurable later
+        // Pipeline sample rate - the audio processor converts captured audio
+        // (whatever the device's rate and channel count) to 16 kHz mono
         let sample_rate = 16000; // 16 kHz is common for speech recognition
 
-        // Spawn the audio capture actor
-        let (audio_capture, _) = Actor::spawn(None, AudioCaptureActor {}, myself.clone())
-            .await
-            .map_err(|e| {
-                ActorProcessingErr::from(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to start audio capture actor: {}", e),
-                ))
-            })?;
-
-        // Spawn the transcriber actor with model path
-        let (transcriber, _) = Actor::spawn(
-            None,
-            TranscriberActor {},
-            (myself.clone(), sample_rate, model_path),
-        )
-        .await
-        .map_err(|e| {
-            ActorProcessingErr::from(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to start transcriber actor: {}", e),
-            ))
-        })?;
-
-        // Spawn the audio processor actor
-        let (audio_processor, _) = Actor::spawn(
-            None,
-            AudioProcessorActor {},
-            (
-                myself.clone(),
-                transcriber.clone(),
-                sample_rate,
-                config.clone(),
-            ),
-        )
-        .await
-        .map_err(|e| {
-            ActorProcessingErr::from(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to start audio processor actor: {}", e),
-            ))
-        })?;
-
-        // Spawn the keyboard output actor
-        let (keyboard_output, _) = Actor::spawn(
-            None,
-            KeyboardOutputActor {},
-            (myself.clone(), config.clone()),
-        )
-        .await
-        .map_err(|e| {
-            ActorProcessingErr::from(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to start keyboard output actor: {}", e),
-            ))
-        })?;
+        // Spawn the children, linked so failures come back to us
+        let audio_capture = Self::spawn_audio_capture(&myself, &config).await?;
+        let transcriber =
+            Self::spawn_transcriber(&myself, sample_rate, model_path.clone(), &config).await?;
+        let audio_processor =
+            Self::spawn_audio_processor(&myself, transcriber.clone(), sample_rate, &config).await?;
+        let keyboard_output = Self::spawn_keyboard_output(&myself, &config).await?;
 
         // Send initial status to UI
         (ui_sender)(AppOutput::UpdateStatus("Initialized".to_string()));
 
+        let hotkey_listeners = [
+            (config.hotkey.as_deref(), HotkeyAction::Dictation),
+            (config.abort_hotkey.as_deref(), HotkeyAction::Abort),
+        ]
+        .into_iter()
+        .filter_map(|(spec, action)| {
+            start_hotkey_listener(&myself, spec, action, &config, &ui_sender)
+        })
+        .collect();
+
+        if config.activation_mode == ActivationMode::AutoStop && !config.enable_vad {
+            tracing::warn!("Auto stop needs VAD to detect silence, only the session limit applies");
+        }
+
+        // Always listening, so start waiting for the wake word right away
+        if config.activation_mode == ActivationMode::WakeWord {
+            if !config.enable_vad {
+                tracing::warn!(
+                    "Wake word mode without VAD can't end dictation on silence, only the session limit applies"
+                );
+            }
+            myself.send_message(CoordinatorMsg::WaitForWakeWord)?;
+        }
+
         // Return initial state
         Ok(CoordinatorState {
             ui_sender,
@@ -133,13 +431,31 @@ impl Actor for Coordinator {
             transcriber: Some(transcriber),
             keyboard_output: Some(keyboard_output),
             sample_rate,
+            transcriber_info: None,
+            transcriber_restarts: 0,
+            transcriber_last_exit: None,
+            model_path,
+            listening_state: ListeningState::Idle,
+            capture_active: false,
+            in_utterance: false,
+            utterance_count: 0,
+            pending_typing: 0,
+            live_partial: None,
+            formatter: TextFormatter::new(&config),
+            commands: CommandSet::new(&config.commands),
+            session: 0,
+            keyboard_output_enabled: config.enable_keyboard_output,
+            wake_word_available: config.activation_mode == ActivationMode::WakeWord,
+            wake_word_armed: false,
+            child_restarts: Vec::new(),
+            _hotkey_listeners: hotkey_listeners,
             config,
         })
     }
 
     async fn handle(
         &self,
-        _myself: ActorRef<Self::Msg>,
+        myself: ActorRef<Self::Msg>,
         message: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
@@ -150,11 +466,46 @@ impl Actor for Coordinator {
             }
             CoordinatorMsg::StartListening => {
                 tracing::info!("Coordinator: StartListening received");
+                // Starting while waiting for the wake word skips it
+                if state.capture_active && state.wake_word_armed {
+                    state.set_wake_word_armed(false)?;
+                    state.start_session(&myself);
+                    if state.listening_state == ListeningState::WaitingForWakeWord {
+                        state.transition_or_log(ListeningState::Listening);
+                    }
+                    (state.ui_sender)(AppOutput::UpdateStatus("Listening...".to_string()));
+                    return Ok(());
+                }
+                if state.capture_active {
+                    let error = InvalidTransition {
+                        from: state.listening_state,
+                        to: ListeningState::Listening,
+                    };
+                    state.reject("start listening", error);
+                    return Ok(());
+                }
                 if let Some(audio_capture) = &state.audio_capture {
                     audio_capture.send_message(AudioCaptureMsg::Start)?;
-                    (state.ui_sender)(AppOutput::UpdateStatus(
-                        "Starting audio capture...".to_string(),
-                    ));
+                    state.capture_active = true;
+
+                    // With a result still pending we get there once it's done
+                    let next = if state.wake_word_available {
+                        state.set_wake_word_armed(true)?;
+                        ListeningState::WaitingForWakeWord
+                    } else {
+                        state.start_session(&myself);
+                        ListeningState::Listening
+                    };
+                    if state.listening_state == ListeningState::Idle {
+                        state.transition_or_log(next);
+                    }
+                    let status = if state.transcriber_info.is_some() {
+                        "Starting audio capture..."
+                    } else {
+                        // The transcriber drops audio until its model has loaded
+                        "Starting audio capture (transcriber not ready yet)..."
+                    };
+                    (state.ui_sender)(AppOutput::UpdateStatus(status.to_string()));
                 } else {
                     tracing::error!("Audio capture actor not available");
                     (state.ui_sender)(AppOutput::UpdateStatus(
@@ -164,8 +515,40 @@ impl Actor for Coordinator {
             }
             CoordinatorMsg::StopListening => {
                 tracing::info!("Coordinator: StopListening received");
+                if !state.capture_active {
+                    let error = InvalidTransition {
+                        from: state.listening_state,
+                        to: ListeningState::Idle,
+                    };
+                    state.reject("stop listening", error);
+                    return Ok(());
+                }
                 if let Some(audio_capture) = &state.audio_capture {
                     audio_capture.send_message(AudioCaptureMsg::Stop)?;
+                    state.capture_active = false;
+                    // The flush disarms the audio processor too
+                    state.wake_word_armed = false;
+
+                    // Make sure the last utterance gets transcribed
+                    if let Some(audio_processor) = &state.audio_processor {
+                        audio_processor.send_message(AudioProcessorMsg::Flush)?;
+                    }
+
+                    match state.listening_state {
+                        ListeningState::Listening if state.in_utterance => {
+                            // The flush ends the utterance, wait for its result
+                            state.transition_or_log(ListeningState::Processing);
+                            let utterance = state.utterance_count + 1;
+                            myself.send_after(PROCESSING_TIMEOUT, move || {
+                                CoordinatorMsg::ProcessingTimeout { utterance }
+                            });
+                        }
+                        ListeningState::Listening | ListeningState::WaitingForWakeWord => {
+                            state.transition_or_log(ListeningState::Idle);
+                        }
+                        _ => {}
+                    }
+
                     (state.ui_sender)(AppOutput::UpdateStatus(
                         "Stopping audio capture...".to_string(),
                     ));
@@ -178,8 +561,13 @@ impl Actor for Coordinator {
             }
             CoordinatorMsg::AudioChunk(chunk) => {
                 // Log less frequently to avoid flooding
-                if chunk.0.len() % 1000 == 0 {
-                    tracing::debug!("Coordinator: Received audio chunk, size: {}", chunk.0.len());
+                if chunk.samples.len() % 1000 == 0 {
+                    tracing::debug!(
+                        "Coordinator: Received audio chunk, size: {} ({} Hz, {} ch)",
+                        chunk.samples.len(),
+                        chunk.sample_rate,
+                        chunk.channels
+                    );
                 }
 
                 // Forward to audio processor if available
@@ -194,17 +582,52 @@ impl Actor for Coordinator {
                 // Forward status updates from actors to the UI
                 (state.ui_sender)(AppOutput::UpdateStatus(status));
             }
+            CoordinatorMsg::PartialTranscription(text) => {
+                (state.ui_sender)(AppOutput::UpdateStatus(format!("Partial: {}", text)));
+                if state.config.live_typing && state.keyboard_output_enabled {
+                    if let Some(keyboard_output) = &state.keyboard_output {
+                        let preview = state.formatter.preview(&text);
+                        keyboard_output.send_message(KeyboardOutputMsg::LivePartial(preview))?;
+                        state.live_partial = Some(text);
+                    }
+                }
+            }
             CoordinatorMsg::TranscriptionResult(transcription) => {
                 tracing::info!("Received transcription result: {}", transcription.0);
 
                 // Forward to UI
                 (state.ui_sender)(AppOutput::UpdateTranscription(transcription.0.clone()));
 
+                // Count what gets typed, so we know when typing is over
+                let typed = Cell::new(0);
+
+                // Text typed live from partials is corrected by the final
+                // text, or erased before a command's output
+                let live = Cell::new(state.live_partial.take().is_some());
+
                 // Process for commands
                 if let Some(keyboard_output) = &state.keyboard_output {
+                    let erase_live = || -> Result<(), ractor::MessagingErr<KeyboardOutputMsg>> {
+                        if live.replace(false) {
+                            keyboard_output
+                                .send_message(KeyboardOutputMsg::LiveFinal(String::new()))?;
+                            typed.set(typed.get() + 1);
+                        }
+                        Ok(())
+                    };
+
                     // Create a function to send messages to the keyboard output actor
                     let keyboard_sender =
                         |msg: KeyboardOutputMsg| -> Result<(), Box<dyn std::error::Error>> {
+                            erase_live()?;
+                            if matches!(
+                                msg,
+                                KeyboardOutputMsg::TypeText(_)
+                                    | KeyboardOutputMsg::PressKeys(_)
+                                    | KeyboardOutputMsg::ScratchThat
+                            ) {
+                                typed.set(typed.get() + 1);
+                            }
                             keyboard_output
                                 .send_message(msg)
                                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
@@ -213,18 +636,25 @@ impl Actor for Coordinator {
                     // Check if transcription matches a command
                     match command::process_command(
                         &transcription.0,
-                        &state.config.commands,
+                        &state.commands,
                         keyboard_sender,
                     ) {
                         Ok(Some(_)) => {
                             // Command was executed, do nothing further
                             tracing::info!("Command was executed");
+                            // Whatever it did, the next utterance starts afresh
+                            state.formatter.reset();
                         }
                         Ok(None) => {
                             // No command matched, type the text if keyboard output is enabled
-                            if state.config.enable_keyboard_output {
-                                keyboard_output
-                                    .send_message(KeyboardOutputMsg::TypeText(transcription.0))?;
+                            if live.replace(false) {
+                                let text = state.formatter.format(&transcription.0);
+                                keyboard_output.send_message(KeyboardOutputMsg::LiveFinal(text))?;
+                                typed.set(typed.get() + 1);
+                            } else if state.keyboard_output_enabled {
+                                let text = state.formatter.format(&transcription.0);
+                                keyboard_output.send_message(KeyboardOutputMsg::TypeText(text))?;
+                                typed.set(typed.get() + 1);
                             }
                         }
                         Err(e) => {
@@ -236,6 +666,163 @@ impl Actor for Coordinator {
                             )));
                         }
                     }
+                    erase_live()?;
+                }
+
+                if typed.get() > 0 {
+                    state.pending_typing += typed.get();
+                    if state.listening_state != ListeningState::Typing {
+                        state.transition_or_log(ListeningState::Typing);
+                    }
+                } else if state.listening_state == ListeningState::Processing {
+                    let next = state.resting_state();
+                    state.transition_or_log(next);
+                }
+            }
+            CoordinatorMsg::SilenceTimeout => {
+                if state.config.activation_mode != ActivationMode::Manual
+                    && state.capture_active
+                    && !state.wake_word_armed
+                {
+                    tracing::info!(
+                        "{} ms of silence, ending the session",
+                        state.config.auto_stop_silence_ms
+                    );
+                    let status = if state.end_session(&myself)? {
+                        "Dictation ended after silence, waiting for the wake word"
+                    } else {
+                        "Stopped listening after silence"
+                    };
+                    (state.ui_sender)(AppOutput::UpdateStatus(status.to_string()));
+                }
+            }
+            CoordinatorMsg::SessionTimeout { session } => {
+                if session == state.session && state.capture_active && !state.wake_word_armed {
+                    tracing::warn!(
+                        "Session reached {} s, ending it",
+                        state.config.max_session_secs
+                    );
+                    let limit = state.config.max_session_secs;
+                    let status = if state.end_session(&myself)? {
+                        format!(
+                            "Dictation ended after the {} s session limit, waiting for the wake word",
+                            limit
+                        )
+                    } else {
+                        format!("Stopped listening after the {} s session limit", limit)
+                    };
+                    (state.ui_sender)(AppOutput::UpdateStatus(status));
+                }
+            }
+            CoordinatorMsg::HotkeyPressed(HotkeyAction::Abort) => {
+                tracing::debug!("Abort hotkey pressed");
+                myself.send_message(CoordinatorMsg::CancelTyping)?;
+            }
+            CoordinatorMsg::HotkeyReleased(HotkeyAction::Abort) => {}
+            CoordinatorMsg::HotkeyPressed(HotkeyAction::Dictation) => {
+                tracing::debug!("Hotkey pressed");
+                // Go through the same checks as the UI buttons
+                match state.config.hotkey_mode {
+                    // Waiting for the wake word counts as not dictating yet
+                    HotkeyMode::PushToTalk if !state.capture_active || state.wake_word_armed => {
+                        myself.send_message(CoordinatorMsg::StartListening)?;
+                    }
+                    HotkeyMode::PushToTalk => {}
+                    HotkeyMode::Toggle if state.capture_active && !state.wake_word_armed => {
+                        state.end_session(&myself)?;
+                    }
+                    HotkeyMode::Toggle => {
+                        myself.send_message(CoordinatorMsg::StartListening)?;
+                    }
+                }
+            }
+            CoordinatorMsg::HotkeyReleased(HotkeyAction::Dictation) => {
+                tracing::debug!("Hotkey released");
+                if state.config.hotkey_mode == HotkeyMode::PushToTalk
+                    && state.capture_active
+                    && !state.wake_word_armed
+                {
+                    state.end_session(&myself)?;
+                }
+            }
+            CoordinatorMsg::WaitForWakeWord => {
+                if state.wake_word_available && !state.capture_active {
+                    myself.send_message(CoordinatorMsg::StartListening)?;
+                }
+            }
+            CoordinatorMsg::WakeWordDetected(phrase) => {
+                // Stale if the user started or stopped in the meantime
+                if !state.wake_word_armed {
+                    tracing::debug!("Ignoring wake phrase '{}' while not waiting", phrase);
+                    return Ok(());
+                }
+                // The audio processor disarmed itself when it heard it
+                state.wake_word_armed = false;
+                state.start_session(&myself);
+                if state.listening_state == ListeningState::WaitingForWakeWord {
+                    state.transition_or_log(ListeningState::Listening);
+                }
+                (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                    "Heard \"{}\", listening...",
+                    phrase
+                )));
+            }
+            CoordinatorMsg::WakeWordUnavailable(reason) => {
+                tracing::error!("Wake word unavailable: {}", reason);
+                state.wake_word_available = false;
+                if state.wake_word_armed {
+                    myself.send_message(CoordinatorMsg::StopListening)?;
+                }
+                (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                    "Wake word unavailable ({}), use Start or the hotkey instead",
+                    reason
+                )));
+            }
+            CoordinatorMsg::CancelTyping => {
+                if state.pending_typing == 0 {
+                    return Ok(());
+                }
+                tracing::info!("Cancelling typing");
+                if let Some(keyboard_output) = &state.keyboard_output {
+                    keyboard_output.send_message(KeyboardOutputMsg::Cancel)?;
+                }
+            }
+            CoordinatorMsg::TypingProgress { item, typed, total } => {
+                tracing::trace!("Typing item {}: {}/{} characters", item, typed, total);
+                if typed < total {
+                    (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                        "Typing... {}/{} characters",
+                        typed, total
+                    )));
+                }
+            }
+            CoordinatorMsg::TypingFinished { item, completed } => {
+                if !completed {
+                    tracing::debug!("Typing item {} didn't complete", item);
+                }
+                state.pending_typing = state.pending_typing.saturating_sub(1);
+                if state.pending_typing == 0 && state.listening_state == ListeningState::Typing {
+                    let next = state.resting_state();
+                    state.transition_or_log(next);
+                }
+            }
+            CoordinatorMsg::ProcessingTimeout { utterance } => {
+                if utterance == state.utterance_count
+                    && state.listening_state == ListeningState::Processing
+                {
+                    tracing::debug!("No transcription for utterance {}", utterance);
+                    // The last partial is the best there is for text typed live
+                    if let (Some(partial), Some(keyboard_output)) =
+                        (state.live_partial.take(), &state.keyboard_output)
+                    {
+                        let text = state.formatter.format(&partial);
+                        keyboard_output.send_message(KeyboardOutputMsg::LiveFinal(text))?;
+                        state.pending_typing += 1;
+                        state.transition_or_log(ListeningState::Typing);
+                        return Ok(());
+                    }
+                    let next = state.resting_state();
+                    state.transition_or_log(next);
                 }
             }
             CoordinatorMsg::SilenceDetected(is_silence) => {
@@ -247,10 +834,169 @@ impl Actor for Coordinator {
                 }
             }
             CoordinatorMsg::ToggleKeyboardOutput(enable) => {
+                state.keyboard_output_enabled = enable;
                 if let Some(keyboard_output) = &state.keyboard_output {
                     keyboard_output.send_message(KeyboardOutputMsg::Enable(enable))?;
                 }
             }
+            CoordinatorMsg::InputDeviceOpened(name) => {
+                tracing::info!("Audio capture using input device: {}", name);
+                (state.ui_sender)(AppOutput::InputDeviceChanged(name));
+            }
+            CoordinatorMsg::TranscriberReady(info) => {
+                if !info.sample_rates.contains(&state.sample_rate) {
+                    tracing::warn!(
+                        "Transcriber expects {:?} Hz, pipeline runs at {} Hz",
+                        info.sample_rates,
+                        state.sample_rate
+                    );
+                }
+                if state.transcriber_restarts > 0 {
+                    tracing::info!(
+                        "Transcriber recovered after {} restarts (last exit: {})",
+                        state.transcriber_restarts,
+                        state.transcriber_last_exit.as_deref().unwrap_or("none")
+                    );
+                }
+                (state.ui_sender)(AppOutput::TranscriberReady(info.clone()));
+                state.transcriber_info = Some(info);
+            }
+            CoordinatorMsg::TranscriberRestarting {
+                attempt,
+                max_attempts,
+                total_restarts,
+                last_exit,
+                delay,
+            } => {
+                tracing::warn!(
+                    "Transcriber exited ({}), restart {} of {} in {:?} ({} restarts in total)",
+                    last_exit,
+                    attempt,
+                    max_attempts,
+                    delay,
+                    total_restarts
+                );
+                (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                    "Transcriber crashed ({}), restarting ({}/{})...",
+                    last_exit, attempt, max_attempts
+                )));
+                state.transcriber_info = None;
+                state.transcriber_restarts = total_restarts;
+                state.transcriber_last_exit = Some(last_exit);
+            }
+            CoordinatorMsg::TranscriberFailed {
+                restarts,
+                last_exit,
+            } => {
+                tracing::error!(
+                    "Transcriber gave up after {} restarts, last exit: {}",
+                    restarts,
+                    last_exit
+                );
+                (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                    "Transcriber keeps crashing ({}), giving up. Check the model and restart the app.",
+                    last_exit
+                )));
+                state.transcriber_info = None;
+                state.transcriber_restarts = restarts;
+                state.transcriber_last_exit = Some(last_exit);
+            }
+            CoordinatorMsg::UtteranceStarted(start) => {
+                tracing::debug!("Utterance started at {:?}", start);
+                state.in_utterance = true;
+                (state.ui_sender)(AppOutput::UpdateStatus("Listening...".to_string()));
+            }
+            CoordinatorMsg::UtteranceEnded(utterance) => {
+                tracing::info!(
+                    "Utterance ended ({:.1}s of speech)",
+                    utterance.duration().as_secs_f32()
+                );
+                state.in_utterance = false;
+                state.utterance_count += 1;
+                if matches!(
+                    state.listening_state,
+                    ListeningState::Listening | ListeningState::Typing
+                ) {
+                    state.transition_or_log(ListeningState::Processing);
+                }
+                let utterance_id = state.utterance_count;
+                myself.send_after(PROCESSING_TIMEOUT, move || {
+                    CoordinatorMsg::ProcessingTimeout {
+                        utterance: utterance_id,
+                    }
+                });
+                (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                    "Transcribing {:.1}s of speech...",
+                    utterance.duration().as_secs_f32()
+                )));
+            }
+            CoordinatorMsg::AudioDeviceLost(reason) => {
+                tracing::warn!("Audio input lost: {}", reason);
+                (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                    "Audio device lost ({}), reconnecting...",
+                    reason
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        event: SupervisionEvent,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match event {
+            SupervisionEvent::ActorFailed(cell, error) => {
+                let Some(component) = state.component_of(&cell) else {
+                    return Ok(());
+                };
+                tracing::error!("{} actor failed: {}", component, error);
+                state.clear(component);
+                (state.ui_sender)(AppOutput::ComponentFailed {
+                    component,
+                    error: error.to_string(),
+                });
+
+                if !state.allow_restart(component) {
+                    tracing::error!("{} keeps failing, not restarting it", component);
+                    (state.ui_sender)(AppOutput::UpdateStatus(format!(
+                        "{} keeps failing, giving up. Restart the app.",
+                        component
+                    )));
+                    return Ok(());
+                }
+
+                match self.restart_component(&myself, state, component).await {
+                    Ok(()) => {
+                        tracing::info!("{} actor restarted", component);
+                        (state.ui_sender)(AppOutput::ComponentRecovered(component));
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to restart {} actor: {}", component, e);
+                        (state.ui_sender)(AppOutput::ComponentFailed {
+                            component,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
+            SupervisionEvent::ActorTerminated(cell, _, reason) => {
+                // A child stopping itself (e.g. a transcriber with an
+                // incompatible protocol version) won't do better on a restart
+                let Some(component) = state.component_of(&cell) else {
+                    return Ok(());
+                };
+                let reason = reason.unwrap_or_else(|| "stopped".to_string());
+                tracing::warn!("{} actor stopped: {}", component, reason);
+                state.clear(component);
+                (state.ui_sender)(AppOutput::ComponentFailed {
+                    component,
+                    error: reason,
+                });
+            }
+            _ => {}
         }
         Ok(())
     }
@@ -279,3 +1025,46 @@ impl Actor for Coordinator {
         Ok(())
     }
 }
+
+// Parse a configured hotkey and start listening for it. Failures are
+// reported but not fatal, the UI buttons still work.
+fn start_hotkey_listener(
+    myself: &ActorRef<CoordinatorMsg>,
+    spec: Option<&str>,
+    action: HotkeyAction,
+    config: &Settings,
+    ui_sender: &Arc<dyn Fn(AppOutput) + Send + Sync + 'static>,
+) -> Option<HotkeyListener> {
+    let spec = spec.filter(|spec| !spec.trim().is_empty())?;
+    let name = match action {
+        HotkeyAction::Dictation => "Hotkey",
+        HotkeyAction::Abort => "Abort hotkey",
+    };
+
+    let result = spec.parse::<Hotkey>().and_then(|hotkey| {
+        let listener =
+            hotkey::spawn_listener(&hotkey, config.hotkey_backend, action, myself.clone())?;
+        Ok((hotkey, listener))
+    });
+    match result {
+        Ok((hotkey, listener)) => {
+            if action == HotkeyAction::Dictation {
+                tracing::info!(
+                    "{} {} ({:?}) active via {}",
+                    name,
+                    hotkey,
+                    config.hotkey_mode,
+                    listener.backend()
+                );
+            } else {
+                tracing::info!("{} {} active via {}", name, hotkey, listener.backend());
+            }
+            Some(listener)
+        }
+        Err(e) => {
+            tracing::error!("{} disabled: {}", name, e);
+            (ui_sender)(AppOutput::UpdateStatus(format!("{} disabled: {}", name, e)));
+            None
+        }
+    }
+}
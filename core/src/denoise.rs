@@ -0,0 +1,138 @@
+use nnnoiseless::DenoiseState;
+use rubato::{FftFixedIn, Resampler};
+
+use crate::resampler::{resample_blocks, ResampleError};
+
+// RNNoise only works on 48 kHz audio
+pub const DENOISE_SAMPLE_RATE: u32 = 48000;
+
+// RNNoise expects samples in the i16 range rather than [-1.0, 1.0]
+const I16_SCALE: f32 = 32767.0;
+
+/// Output of one call to [`Denoiser::process`].
+#[derive(Debug, Default, Clone)]
+pub struct DenoisedAudio {
+    /// Denoised samples at the pipeline's sample rate.
+    pub samples: Vec<f32>,
+    /// RNNoise voice probability (0.0 to 1.0) for every 10 ms frame that was
+    /// completed during this call, in order.
+    pub voice_probabilities: Vec<f32>,
+}
+
+/// Streaming RNNoise stage.
+///
+/// Splits the incoming stream into `DenoiseState::FRAME_SIZE` frames at
+/// 48 kHz, carrying leftover samples between calls, and converts to and from
+/// the pipeline's sample rate and scale around the denoiser.
+pub struct Denoiser {
+    state: Box<DenoiseState<'static>>,
+    // Pipeline rate -> 48 kHz and back, None when the pipeline already runs at 48 kHz
+    upsampler: Option<FftFixedIn<f32>>,
+    downsampler: Option<FftFixedIn<f32>>,
+    // Pipeline-rate samples waiting for a full upsampler block
+    input_buffer: Vec<f32>,
+    // 48 kHz samples waiting for a full RNNoise frame
+    frame_buffer: Vec<f32>,
+    // Denoised 48 kHz samples waiting for a full downsampler block
+    output_buffer: Vec<f32>,
+}
+
+impl Denoiser {
+    pub fn new(sample_rate: u32) -> Result<Self, ResampleError> {
+        let (upsampler, downsampler) = if sample_rate == DENOISE_SAMPLE_RATE {
+            (None, None)
+        } else {
+            // 10 ms blocks in both directions, matching the RNNoise frame length
+            let upsampler = FftFixedIn::<f32>::new(
+                sample_rate as usize,
+                DENOISE_SAMPLE_RATE as usize,
+                (sample_rate as usize / 100).max(1),
+                1,
+                1,
+            )
+            .map_err(|e| ResampleError::InitError(e.to_string()))?;
+            let downsampler = FftFixedIn::<f32>::new(
+                DENOISE_SAMPLE_RATE as usize,
+                sample_rate as usize,
+                DenoiseState::FRAME_SIZE,
+                1,
+                1,
+            )
+            .map_err(|e| ResampleError::InitError(e.to_string()))?;
+            (Some(upsampler), Some(downsampler))
+        };
+
+        Ok(Self {
+            state: DenoiseState::new(),
+            upsampler,
+            downsampler,
+            input_buffer: Vec::new(),
+            frame_buffer: Vec::new(),
+            output_buffer: Vec::new(),
+        })
+    }
+
+    /// Denoise mono samples at the pipeline's sample rate.
+    ///
+    /// Output is delayed by up to a frame or two because of the buffering, so
+    /// the returned samples may be empty for very small inputs.
+    pub fn process(&mut self, samples: &[f32]) -> Result<DenoisedAudio, ResampleError> {
+        // Bring the input up to 48 kHz
+        match &mut self.upsampler {
+            Some(upsampler) => {
+                self.input_buffer.extend_from_slice(samples);
+                let resampled = resample_blocks(upsampler, &mut self.input_buffer)?;
+                self.frame_buffer.extend(resampled);
+            }
+            None => self.frame_buffer.extend_from_slice(samples),
+        }
+
+        // Denoise every complete frame
+        let mut voice_probabilities = Vec::new();
+        let mut input_frame = [0.0f32; DenoiseState::FRAME_SIZE];
+        let mut output_frame = [0.0f32; DenoiseState::FRAME_SIZE];
+        let mut denoised = Vec::with_capacity(self.frame_buffer.len());
+
+        while self.frame_buffer.len() >= DenoiseState::FRAME_SIZE {
+            for (dst, src) in input_frame
+                .iter_mut()
+                .zip(self.frame_buffer.drain(..DenoiseState::FRAME_SIZE))
+            {
+                *dst = src * I16_SCALE;
+            }
+
+            let probability = self.state.process_frame(&mut output_frame, &input_frame);
+            voice_probabilities.push(probability);
+
+            denoised.extend(output_frame.iter().map(|&s| s / I16_SCALE));
+        }
+
+        // And back down to the pipeline rate
+        let samples = match &mut self.downsampler {
+            Some(downsampler) => {
+                self.output_buffer.extend(denoised);
+                resample_blocks(downsampler, &mut self.output_buffer)?
+            }
+            None => denoised,
+        };
+
+        Ok(DenoisedAudio {
+            samples,
+            voice_probabilities,
+        })
+    }
+
+    /// Drop buffered audio and start over with a fresh RNNoise state.
+    pub fn reset(&mut self) {
+        self.state = DenoiseState::new();
+        self.input_buffer.clear();
+        self.frame_buffer.clear();
+        self.output_buffer.clear();
+        if let Some(upsampler) = &mut self.upsampler {
+            upsampler.reset();
+        }
+        if let Some(downsampler) = &mut self.downsampler {
+            downsampler.reset();
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use crate::config::Settings;
+
+// Spoken punctuation and what it's typed as, longest phrases first so
+// "question mark" isn't read as the word "question"
+const SPOKEN_PUNCTUATION: &[(&[&str], &str)] = &[
+    (&["exclamation", "point"], "!"),
+    (&["exclamation", "mark"], "!"),
+    (&["question", "mark"], "?"),
+    (&["full", "stop"], "."),
+    (&["period"], "."),
+    (&["comma"], ","),
+    (&["colon"], ":"),
+    (&["semicolon"], ";"),
+];
+
+const UNITS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const TEENS: &[&str] = &[
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: &[&str] = &[
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: &[(&str, u64)] = &[("thousand", 1_000), ("million", 1_000_000)];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Punctuation(&'static str), // Attached to the word before it
+}
+
+/// Formats dictated text before it's typed.
+///
+/// Each stage is toggled in [`Settings`]. The formatter remembers how the
+/// last utterance ended, so the next one is spaced and capitalized to
+/// follow it.
+pub struct TextFormatter {
+    capitalize: bool,
+    punctuation: bool,
+    spacing: bool,
+    numbers: bool,
+    sentence_start: bool, // The next word starts a sentence
+    after_text: bool,     // Something was typed this session, so words need a space first
+}
+
+impl TextFormatter {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            capitalize: config.format_capitalize,
+            punctuation: config.format_spoken_punctuation,
+            spacing: config.format_spacing,
+            numbers: config.format_numbers,
+            sentence_start: true,
+            after_text: false,
+        }
+    }
+
+    /// Forget the previous utterance, e.g. for a new session where the
+    /// cursor may have moved.
+    pub fn reset(&mut self) {
+        self.sentence_start = true;
+        self.after_text = false;
+    }
+
+    /// Format an utterance that's about to be typed.
+    pub fn format(&mut self, text: &str) -> String {
+        let formatted = self.preview(text);
+        if !formatted.trim().is_empty() {
+            self.sentence_start = ends_sentence(formatted.trim_end());
+            self.after_text = !formatted.ends_with(char::is_whitespace);
+        }
+        formatted
+    }
+
+    /// Format without remembering the utterance, for partial results.
+    pub fn preview(&self, text: &str) -> String {
+        if !(self.capitalize || self.punctuation || self.spacing || self.numbers) {
+            return text.to_string();
+        }
+
+        let mut tokens: Vec<Token> = text
+            .split_whitespace()
+            .map(|word| Token::Word(word.to_string()))
+            .collect();
+        if self.punctuation {
+            tokens = spoken_punctuation(tokens);
+        }
+        if self.numbers {
+            tokens = numbers(tokens);
+        }
+        if self.capitalize {
+            capitalize(&mut tokens, self.sentence_start);
+        }
+
+        let mut formatted = String::new();
+        for token in tokens {
+            match token {
+                Token::Word(word) => {
+                    if !formatted.is_empty() || (self.spacing && self.after_text) {
+                        formatted.push(' ');
+                    }
+                    formatted.push_str(&word);
+                }
+                Token::Punctuation(mark) => formatted.push_str(mark),
+            }
+        }
+        formatted
+    }
+}
+
+fn spoken_punctuation(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result = Vec::with_capacity(tokens.len());
+    let mut index = 0;
+    while index < tokens.len() {
+        let phrase = SPOKEN_PUNCTUATION.iter().find(|(phrase, _)| {
+            phrase.len() <= tokens.len() - index
+                && phrase
+                    .iter()
+                    .zip(&tokens[index..])
+                    .all(|(spoken, token)| is_word(token, spoken))
+        });
+        match phrase {
+            Some((phrase, mark)) => {
+                result.push(Token::Punctuation(mark));
+                index += phrase.len();
+            }
+            None => {
+                result.push(tokens[index].clone());
+                index += 1;
+            }
+        }
+    }
+    result
+}
+
+fn numbers(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result = Vec::with_capacity(tokens.len());
+    let mut index = 0;
+    while index < tokens.len() {
+        let words: Vec<&str> = tokens[index..]
+            .iter()
+            .map_while(|token| match token {
+                Token::Word(word) => Some(word.as_str()),
+                Token::Punctuation(_) => None,
+            })
+            .collect();
+        match parse_number(&words) {
+            // Numbers under ten read better as words
+            Some((value, used)) if used > 1 || value >= 10 => {
+                result.push(Token::Word(value.to_string()));
+                index += used;
+            }
+            _ => {
+                result.push(tokens[index].clone());
+                index += 1;
+            }
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumberWord {
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale(u64),
+    And,
+}
+
+// Parse number words from the start of `words`, returning the value and
+// how many words it took
+pub(crate) fn parse_number(words: &[&str]) -> Option<(u64, usize)> {
+    let mut total = 0;
+    let mut current = 0;
+    let mut last_scale = u64::MAX;
+    let mut last: Option<NumberWord> = None;
+    let mut used = 0;
+
+    for (index, word) in words.iter().enumerate() {
+        let word = word.to_lowercase();
+        let (kind, value) = if let Some(value) = position(UNITS, &word) {
+            (NumberWord::Unit, value)
+        } else if let Some(value) = position(TEENS, &word) {
+            (NumberWord::Teen, value + 10)
+        } else if let Some(value) = position(TENS, &word) {
+            (NumberWord::Tens, (value + 2) * 10)
+        } else if word == "hundred" {
+            (NumberWord::Hundred, 100)
+        } else if let Some(&(_, scale)) = SCALES.iter().find(|(name, _)| *name == word) {
+            (NumberWord::Scale(scale), scale)
+        } else if word == "and" {
+            (NumberWord::And, 0)
+        } else {
+            break;
+        };
+
+        // Two numbers said one after the other, like "three four", aren't
+        // one number
+        let follows = match (last, kind) {
+            (None, NumberWord::Unit | NumberWord::Teen | NumberWord::Tens) => true,
+            (Some(NumberWord::Tens), NumberWord::Unit) => true,
+            (
+                Some(NumberWord::Hundred | NumberWord::Scale(_) | NumberWord::And),
+                NumberWord::Unit | NumberWord::Teen | NumberWord::Tens,
+            ) => true,
+            (Some(NumberWord::Unit | NumberWord::Teen), NumberWord::Hundred) => current < 100,
+            (
+                Some(NumberWord::Unit | NumberWord::Teen | NumberWord::Tens | NumberWord::Hundred),
+                NumberWord::Scale(scale),
+            ) => scale < last_scale,
+            (Some(NumberWord::Hundred | NumberWord::Scale(_)), NumberWord::And) => true,
+            _ => false,
+        };
+        if !follows {
+            break;
+        }
+
+        match kind {
+            NumberWord::Unit | NumberWord::Teen | NumberWord::Tens => current += value,
+            NumberWord::Hundred => current *= 100,
+            NumberWord::Scale(scale) => {
+                total += current * scale;
+                current = 0;
+                last_scale = scale;
+            }
+            NumberWord::And => {}
+        }
+        last = Some(kind);
+        // A trailing "and" belongs to the text after the number
+        if kind != NumberWord::And {
+            used = index + 1;
+        }
+    }
+
+    (used > 0).then_some((total + current, used))
+}
+
+fn capitalize(tokens: &mut [Token], mut sentence_start: bool) {
+    for token in tokens {
+        match token {
+            Token::Word(word) => {
+                let pronoun = word == "i" || word.starts_with("i'");
+                if sentence_start || pronoun {
+                    *word = capitalize_first(word);
+                }
+                // Punctuated by the recognizer itself
+                sentence_start = ends_sentence(word);
+            }
+            Token::Punctuation(mark) => sentence_start = ends_sentence(mark),
+        }
+    }
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.ends_with(['.', '?', '!'])
+}
+
+fn capitalize_first(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn is_word(token: &Token, spoken: &str) -> bool {
+    matches!(token, Token::Word(word) if word.eq_ignore_ascii_case(spoken))
+}
+
+fn position(words: &[&str], word: &str) -> Option<u64> {
+    words
+        .iter()
+        .position(|candidate| *candidate == word)
+        .map(|index| index as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(text: &str) -> Option<(u64, usize)> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        parse_number(&words)
+    }
+
+    #[test]
+    fn parses_spoken_numbers() {
+        assert_eq!(number("zero"), Some((0, 1)));
+        assert_eq!(number("seven"), Some((7, 1)));
+        assert_eq!(number("Thirteen"), Some((13, 1)));
+        assert_eq!(number("forty two"), Some((42, 2)));
+        assert_eq!(number("one hundred"), Some((100, 2)));
+        assert_eq!(number("one hundred and five"), Some((105, 4)));
+        assert_eq!(number("three hundred twenty one"), Some((321, 4)));
+        assert_eq!(number("two thousand and twenty four"), Some((2024, 5)));
+        assert_eq!(
+            number("one million two hundred thousand three"),
+            Some((1_200_003, 6))
+        );
+    }
+
+    #[test]
+    fn stops_at_words_that_dont_follow() {
+        // Not a number word
+        assert_eq!(number("forty two apples"), Some((42, 2)));
+        assert_eq!(number("apples"), None);
+        assert_eq!(number(""), None);
+        // Numbers said one after the other
+        assert_eq!(number("three four"), Some((3, 1)));
+        assert_eq!(number("twenty thirty"), Some((20, 1)));
+        assert_eq!(number("twelve five"), Some((12, 1)));
+        assert_eq!(number("forty eleven"), Some((40, 1)));
+        // A scale can't start a number or repeat a larger one
+        assert_eq!(number("hundred"), None);
+        assert_eq!(number("five thousand two million"), Some((5002, 3)));
+        assert_eq!(number("two hundred hundred"), Some((200, 2)));
+        // A trailing "and" is left for the text after the number
+        assert_eq!(number("five hundred and"), Some((500, 2)));
+        assert_eq!(number("and five"), None);
+    }
+
+    #[test]
+    fn formats_punctuation_numbers_and_capitals() {
+        let config = Settings {
+            format_numbers: true,
+            ..Settings::default()
+        };
+        let mut formatter = TextFormatter::new(&config);
+        assert_eq!(
+            formatter.format("i have twenty five apples comma three pears period"),
+            "I have 25 apples, three pears."
+        );
+        // Carries on from the last utterance
+        assert_eq!(
+            formatter.format("what about you question mark"),
+            " What about you?"
+        );
+        formatter.reset();
+        assert_eq!(formatter.format("hello"), "Hello");
+    }
+}
@@ -0,0 +1,681 @@
+use ractor::ActorRef;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::config::HotkeyBackend;
+use crate::keycodes::{function_key_code, us_key_for, NAMED_KEYS};
+use crate::types::{CoordinatorMsg, HotkeyAction};
+
+#[derive(Error, Debug)]
+pub enum HotkeyError {
+    #[error("Invalid hotkey '{spec}': {reason}")]
+    InvalidSpec { spec: String, reason: String },
+
+    #[error("Invalid key sequence '{spec}': {reason}")]
+    InvalidKeys { spec: String, reason: String },
+
+    #[error("No readable keyboard in /dev/input has that key (is the user in the 'input' group?)")]
+    NoDevices,
+
+    #[error("X11 hotkey grab failed: {0}")]
+    X11(String),
+
+    #[error("Global hotkeys aren't supported on this platform")]
+    Unsupported,
+
+    #[error("No hotkey backend available: {0}")]
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+/// A key combination such as `Ctrl+Alt+Space`.
+///
+/// Keys are stored as Linux input event codes, which X11 keycodes are
+/// derived from (they're offset by 8 under the evdev XKB driver).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: Modifiers,
+    pub key: u16,
+    key_name: &'static str,
+}
+
+// Look up a key by name, returning its canonical name and code
+fn parse_key(name: &str) -> Option<(&'static str, u16)> {
+    const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const DIGITS: &str = "0123456789";
+    const F_KEYS: [&str; 24] = [
+        "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "F13", "F14",
+        "F15", "F16", "F17", "F18", "F19", "F20", "F21", "F22", "F23", "F24",
+    ];
+
+    let lower = name.to_ascii_lowercase();
+
+    if let Some(&(canonical, _, code, _)) = NAMED_KEYS
+        .iter()
+        .find(|(canonical, alias, _, _)| canonical.eq_ignore_ascii_case(&lower) || *alias == lower)
+    {
+        return Some((canonical, code));
+    }
+
+    let mut chars = lower.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let names = if c.is_ascii_lowercase() {
+            LETTERS
+        } else if c.is_ascii_digit() {
+            DIGITS
+        } else {
+            return None;
+        };
+        let index = names.find(c.to_ascii_uppercase())?;
+        let (code, _) = us_key_for(c)?;
+        return Some((&names[index..index + 1], code));
+    }
+
+    let number: u16 = lower.strip_prefix('f')?.parse().ok()?;
+    let code = function_key_code(number)?;
+    Some((F_KEYS[number as usize - 1], code))
+}
+
+impl FromStr for Hotkey {
+    type Err = HotkeyError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| HotkeyError::InvalidSpec {
+            spec: spec.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        let Some((key, modifier_names)) = parts.split_last() else {
+            return Err(invalid("empty"));
+        };
+        if key.is_empty() {
+            return Err(invalid("missing key"));
+        }
+
+        let mut modifiers = Modifiers::default();
+        for name in modifier_names {
+            let flag = match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut modifiers.ctrl,
+                "alt" => &mut modifiers.alt,
+                "shift" => &mut modifiers.shift,
+                "super" | "meta" | "win" | "logo" => &mut modifiers.meta,
+                _ => return Err(invalid(&format!("unknown modifier '{}'", name))),
+            };
+            if *flag {
+                return Err(invalid(&format!("modifier '{}' given twice", name)));
+            }
+            *flag = true;
+        }
+
+        if [
+            "ctrl", "control", "alt", "shift", "super", "meta", "win", "logo",
+        ]
+        .contains(&key.to_ascii_lowercase().as_str())
+        {
+            return Err(invalid(
+                "the last key can't be a plain modifier, use e.g. LeftCtrl or RightAlt",
+            ));
+        }
+
+        let (key_name, key) =
+            parse_key(key).ok_or_else(|| invalid(&format!("unknown key '{}'", key)))?;
+
+        Ok(Hotkey {
+            modifiers,
+            key,
+            key_name,
+        })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "Ctrl"),
+            (self.modifiers.alt, "Alt"),
+            (self.modifiers.shift, "Shift"),
+            (self.modifiers.meta, "Super"),
+        ];
+        for (_, name) in modifiers.iter().filter(|(held, _)| *held) {
+            write!(f, "{}+", name)?;
+        }
+        f.write_str(self.key_name)
+    }
+}
+
+// Most times a braced step may repeat, to catch typos like {tab 33}
+const MAX_REPEAT: usize = 20;
+
+/// Keys for a command to press, such as `ctrl+shift+t`, `alt+tab` or
+/// `{enter}{tab}`.
+///
+/// Steps are separated by spaces or written in braces, and a braced step
+/// can repeat, e.g. `{backspace 3}`. The notation is kept as written, so
+/// saving the config doesn't rewrite it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeySequence {
+    spec: String,
+    keys: Vec<Hotkey>,
+}
+
+impl KeySequence {
+    pub fn keys(&self) -> &[Hotkey] {
+        &self.keys
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = HotkeyError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| HotkeyError::InvalidKeys {
+            spec: spec.to_string(),
+            reason,
+        };
+
+        let mut keys = Vec::new();
+        let mut rest = spec.trim();
+        while !rest.is_empty() {
+            let (step, after) = if let Some(braced) = rest.strip_prefix('{') {
+                let end = braced
+                    .find('}')
+                    .ok_or_else(|| invalid("missing '}'".to_string()))?;
+                (braced[..end].trim(), &braced[end + 1..])
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '{')
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            };
+
+            // A trailing number repeats the step, anything else is part of
+            // the combination (e.g. "ctrl + c")
+            let (combo, times) = match step.rsplit_once(char::is_whitespace) {
+                Some((combo, count)) if count.chars().all(|c| c.is_ascii_digit()) => {
+                    let times: usize = count
+                        .parse()
+                        .map_err(|_| invalid(format!("bad repeat count '{}'", count)))?;
+                    if !(1..=MAX_REPEAT).contains(&times) {
+                        return Err(invalid(format!("repeat count must be 1 to {}", MAX_REPEAT)));
+                    }
+                    (combo, times)
+                }
+                _ => (step, 1),
+            };
+            let hotkey = combo.parse::<Hotkey>().map_err(|e| match e {
+                HotkeyError::InvalidSpec { spec, reason } => {
+                    invalid(format!("{}: {}", spec, reason))
+                }
+                e => invalid(e.to_string()),
+            })?;
+            keys.extend(std::iter::repeat_n(hotkey, times));
+
+            rest = after.trim_start();
+        }
+
+        if keys.is_empty() {
+            return Err(invalid("no keys".to_string()));
+        }
+        Ok(KeySequence {
+            spec: spec.trim().to_string(),
+            keys,
+        })
+    }
+}
+
+impl TryFrom<String> for KeySequence {
+    type Error = HotkeyError;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl From<KeySequence> for String {
+    fn from(keys: KeySequence) -> Self {
+        keys.spec
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+/// Keeps the hotkey listener threads running. They exit once this is dropped.
+pub struct HotkeyListener {
+    backend: &'static str,
+    #[cfg(target_os = "linux")]
+    _wake: std::os::fd::OwnedFd, // Closing it wakes the threads
+}
+
+impl HotkeyListener {
+    pub fn backend(&self) -> &'static str {
+        self.backend
+    }
+}
+
+/// Listen for the hotkey globally, sending `HotkeyPressed` and
+/// `HotkeyReleased` with `action` to the coordinator.
+///
+/// With `HotkeyBackend::Auto`, evdev is tried first (works under X11,
+/// Wayland and on the console, but needs read access to /dev/input), then an
+/// X11 key grab.
+pub fn spawn_listener(
+    hotkey: &Hotkey,
+    backend: HotkeyBackend,
+    action: HotkeyAction,
+    coordinator: ActorRef<CoordinatorMsg>,
+) -> Result<HotkeyListener, HotkeyError> {
+    #[cfg(target_os = "linux")]
+    {
+        let (wake, wake_sender) = linux::wake_pipe()?;
+        let backend = match backend {
+            HotkeyBackend::Evdev => {
+                linux::spawn_evdev(hotkey, action, coordinator, &wake)?;
+                "evdev"
+            }
+            HotkeyBackend::X11 => {
+                linux::spawn_x11(hotkey, action, coordinator, &wake)?;
+                "X11"
+            }
+            HotkeyBackend::Auto => {
+                match linux::spawn_evdev(hotkey, action, coordinator.clone(), &wake) {
+                    Ok(()) => "evdev",
+                    Err(evdev_error) => {
+                        tracing::info!("evdev hotkey unavailable ({}), trying X11", evdev_error);
+                        linux::spawn_x11(hotkey, action, coordinator, &wake).map_err(
+                            |x11_error| {
+                                HotkeyError::Unavailable(format!("{}; {}", evdev_error, x11_error))
+                            },
+                        )?;
+                        "X11"
+                    }
+                }
+            }
+        };
+        Ok(HotkeyListener {
+            backend,
+            _wake: wake_sender,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (hotkey, backend, action, coordinator);
+        Err(HotkeyError::Unsupported)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use evdev::{EventSummary, KeyCode};
+    use ractor::ActorRef;
+    use rustix::event::{poll, PollFd, PollFlags};
+    use rustix::pipe::{pipe_with, PipeFlags};
+    use std::io::ErrorKind;
+    use std::os::fd::{AsFd, OwnedFd};
+    use std::sync::Arc;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt, GrabMode, ModMask};
+    use x11rb::protocol::Event;
+
+    use super::{Hotkey, HotkeyError, Modifiers};
+    use crate::types::{CoordinatorMsg, HotkeyAction};
+
+    // X11 keycodes are evdev codes shifted by 8
+    const X11_KEYCODE_OFFSET: u16 = 8;
+
+    // Key event values from the kernel
+    const KEY_RELEASED: i32 = 0;
+    const KEY_PRESSED: i32 = 1;
+
+    // A pipe whose read end the listener threads wait on alongside their
+    // input. Closing the write end makes it readable, which stops them.
+    pub(super) fn wake_pipe() -> Result<(Arc<OwnedFd>, OwnedFd), HotkeyError> {
+        let (wake, sender) = pipe_with(PipeFlags::CLOEXEC)
+            .map_err(|e| HotkeyError::Unavailable(format!("pipe: {}", e)))?;
+        Ok((Arc::new(wake), sender))
+    }
+
+    // Block until `input` is readable. False once it's time to stop.
+    pub(super) fn wait_for_input(input: &impl AsFd, wake: &OwnedFd) -> std::io::Result<bool> {
+        let mut fds = [
+            PollFd::new(input, PollFlags::IN),
+            PollFd::new(wake, PollFlags::IN),
+        ];
+        loop {
+            match poll(&mut fds, None) {
+                Ok(_) => return Ok(fds[1].revents().is_empty()),
+                Err(rustix::io::Errno::INTR) => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    // Reads every keyboard with the hotkey's key. Devices aren't grabbed, so
+    // the key combination still reaches the focused application.
+    pub(super) fn spawn_evdev(
+        hotkey: &Hotkey,
+        action: HotkeyAction,
+        coordinator: ActorRef<CoordinatorMsg>,
+        wake: &Arc<OwnedFd>,
+    ) -> Result<(), HotkeyError> {
+        let key = KeyCode::new(hotkey.key);
+
+        // Devices we can't open are skipped by enumerate()
+        let devices: Vec<_> = evdev::enumerate()
+            .filter(|(_, device)| {
+                device
+                    .supported_keys()
+                    .is_some_and(|keys| keys.contains(key))
+            })
+            .collect();
+        if devices.is_empty() {
+            return Err(HotkeyError::NoDevices);
+        }
+
+        for (path, mut device) in devices {
+            // Reads only happen once poll says there's something to read,
+            // but a spurious wakeup mustn't block
+            device
+                .set_nonblocking(true)
+                .map_err(|e| HotkeyError::Unavailable(format!("{:?}: {}", path, e)))?;
+            tracing::info!(
+                "Listening for hotkey {} on {} ({:?})",
+                hotkey,
+                device.name().unwrap_or("unnamed device"),
+                path
+            );
+            let hotkey = hotkey.clone();
+            let coordinator = coordinator.clone();
+            let wake = wake.clone();
+
+            std::thread::Builder::new()
+                .name("hotkey-evdev".to_string())
+                .spawn(move || {
+                    let mut held = Modifiers::default();
+                    let mut active = false;
+
+                    loop {
+                        match wait_for_input(&device, &wake) {
+                            Ok(true) => {}
+                            Ok(false) => return,
+                            Err(e) => {
+                                tracing::warn!("Stopped waiting for {:?}: {}", path, e);
+                                return;
+                            }
+                        }
+                        let events = match device.fetch_events() {
+                            Ok(events) => events,
+                            Err(e) if e.kind() == ErrorKind::WouldBlock => continue,
+                            Err(e) => {
+                                // Usually the device was unplugged
+                                tracing::warn!("Stopped reading {:?}: {}", path, e);
+                                return;
+                            }
+                        };
+
+                        for event in events {
+                            let EventSummary::Key(_, code, value) = event.destructure() else {
+                                continue;
+                            };
+
+                            if code == key {
+                                // Autorepeat (value 2) is ignored
+                                if value == KEY_PRESSED && !active && held == hotkey.modifiers {
+                                    active = true;
+                                    let _ = coordinator
+                                        .send_message(CoordinatorMsg::HotkeyPressed(action));
+                                } else if value == KEY_RELEASED && active {
+                                    active = false;
+                                    let _ = coordinator
+                                        .send_message(CoordinatorMsg::HotkeyReleased(action));
+                                }
+                                continue;
+                            }
+
+                            let flag = match code {
+                                KeyCode::KEY_LEFTCTRL | KeyCode::KEY_RIGHTCTRL => &mut held.ctrl,
+                                KeyCode::KEY_LEFTALT | KeyCode::KEY_RIGHTALT => &mut held.alt,
+                                KeyCode::KEY_LEFTSHIFT | KeyCode::KEY_RIGHTSHIFT => &mut held.shift,
+                                KeyCode::KEY_LEFTMETA | KeyCode::KEY_RIGHTMETA => &mut held.meta,
+                                _ => continue,
+                            };
+                            *flag = value != KEY_RELEASED;
+                        }
+                    }
+                })
+                .map_err(|e| HotkeyError::Unavailable(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    // Grabs the key on the root window. Only one client can grab a
+    // combination, so this fails if another application already has it.
+    pub(super) fn spawn_x11(
+        hotkey: &Hotkey,
+        action: HotkeyAction,
+        coordinator: ActorRef<CoordinatorMsg>,
+        wake: &Arc<OwnedFd>,
+    ) -> Result<(), HotkeyError> {
+        let x11_error = |e: &dyn std::fmt::Display| HotkeyError::X11(e.to_string());
+
+        let keycode = u8::try_from(hotkey.key + X11_KEYCODE_OFFSET)
+            .map_err(|_| HotkeyError::X11(format!("{} has no X11 keycode", hotkey)))?;
+
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| x11_error(&e))?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let mut modifiers = ModMask::from(0u16);
+        if hotkey.modifiers.ctrl {
+            modifiers |= ModMask::CONTROL;
+        }
+        if hotkey.modifiers.alt {
+            modifiers |= ModMask::M1;
+        }
+        if hotkey.modifiers.shift {
+            modifiers |= ModMask::SHIFT;
+        }
+        if hotkey.modifiers.meta {
+            modifiers |= ModMask::M4;
+        }
+
+        // Grabs match modifiers exactly, so also grab with Caps Lock and
+        // Num Lock on
+        let lock_masks = [
+            ModMask::from(0u16),
+            ModMask::LOCK,
+            ModMask::M2,
+            ModMask::LOCK | ModMask::M2,
+        ];
+        for lock in lock_masks {
+            conn.grab_key(
+                false,
+                root,
+                modifiers | lock,
+                keycode,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )
+            .map_err(|e| x11_error(&e))?
+            .check()
+            .map_err(|e| x11_error(&format!("{} (is it used by another application?)", e)))?;
+        }
+        conn.flush().map_err(|e| x11_error(&e))?;
+        tracing::info!("Grabbed hotkey {} on the X11 root window", hotkey);
+
+        let wake = wake.clone();
+        std::thread::Builder::new()
+            .name("hotkey-x11".to_string())
+            .spawn(move || {
+                let mut active = false;
+                let mut pending = None;
+
+                loop {
+                    // Events already read from the socket are handled before
+                    // waiting on it again
+                    let event = match pending.take() {
+                        Some(event) => event,
+                        None => match conn.poll_for_event() {
+                            Ok(Some(event)) => event,
+                            Ok(None) => match wait_for_input(conn.stream(), &wake) {
+                                Ok(true) => continue,
+                                Ok(false) => return,
+                                Err(e) => {
+                                    tracing::warn!("Stopped waiting for X11 events: {}", e);
+                                    return;
+                                }
+                            },
+                            Err(e) => {
+                                tracing::warn!("Lost the X11 connection: {}", e);
+                                return;
+                            }
+                        },
+                    };
+
+                    match event {
+                        Event::KeyPress(press) if press.detail == keycode && !active => {
+                            active = true;
+                            let _ = coordinator.send_message(CoordinatorMsg::HotkeyPressed(action));
+                        }
+                        Event::KeyRelease(release) if release.detail == keycode => {
+                            // Autorepeat shows up as a release immediately
+                            // followed by a press with the same timestamp
+                            if let Ok(Some(next)) = conn.poll_for_event() {
+                                if let Event::KeyPress(press) = &next {
+                                    if press.detail == keycode && press.time == release.time {
+                                        continue;
+                                    }
+                                }
+                                pending = Some(next);
+                            }
+                            if active {
+                                active = false;
+                                let _ = coordinator
+                                    .send_message(CoordinatorMsg::HotkeyReleased(action));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            })
+            .map_err(|e| x11_error(&e))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotkey(spec: &str) -> Hotkey {
+        spec.parse().unwrap()
+    }
+
+    fn keys(spec: &str) -> Vec<String> {
+        let sequence: KeySequence = spec.parse().unwrap();
+        sequence.keys().iter().map(Hotkey::to_string).collect()
+    }
+
+    #[test]
+    fn parses_hotkeys() {
+        let parsed = hotkey("ctrl+shift+t");
+        assert_eq!(
+            parsed.modifiers,
+            Modifiers {
+                ctrl: true,
+                shift: true,
+                ..Modifiers::default()
+            }
+        );
+        assert_eq!(parsed.key, 20);
+        assert_eq!(parsed.to_string(), "Ctrl+Shift+T");
+
+        assert_eq!(hotkey("Ctrl + Alt + Space").to_string(), "Ctrl+Alt+Space");
+        assert_eq!(hotkey("super+return").to_string(), "Super+Enter");
+        assert_eq!(hotkey("a").key, 30);
+        assert_eq!(hotkey("m").key, 50);
+        assert_eq!(hotkey("1").key, 2);
+        assert_eq!(hotkey("0").key, 11);
+        assert_eq!(hotkey("F1").key, 59);
+        assert_eq!(hotkey("f12").key, 88);
+        assert_eq!(hotkey("F24").key, 194);
+    }
+
+    #[test]
+    fn rejects_bad_hotkeys() {
+        for spec in [
+            "",
+            "ctrl+",
+            "hyper+a",
+            "ctrl+ctrl+a",
+            "ctrl+shift",
+            "f25",
+            "ab",
+        ] {
+            assert!(spec.parse::<Hotkey>().is_err(), "'{}' parsed", spec);
+        }
+    }
+
+    #[test]
+    fn parses_key_sequences() {
+        assert_eq!(keys("ctrl+shift+t"), ["Ctrl+Shift+T"]);
+        assert_eq!(keys("{enter}{tab}"), ["Enter", "Tab"]);
+        assert_eq!(keys("alt+tab ctrl+v"), ["Alt+Tab", "Ctrl+V"]);
+        assert_eq!(keys("{ctrl + c} {down 2}"), ["Ctrl+C", "Down", "Down"]);
+        assert_eq!(
+            keys("{backspace 3}x"),
+            ["Backspace", "Backspace", "Backspace", "X"]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_key_sequences() {
+        for spec in [
+            "", "  ", "{enter", "{tab 0}", "{tab 33}", "ctrl+", "{nokey}",
+        ] {
+            assert!(spec.parse::<KeySequence>().is_err(), "'{}' parsed", spec);
+        }
+    }
+
+    #[test]
+    fn key_sequences_keep_their_notation() {
+        let sequence: KeySequence = " {Enter}{tab} ".parse().unwrap();
+        assert_eq!(String::from(sequence), "{Enter}{tab}");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dropping_the_listener_wakes_its_threads() {
+        use rustix::pipe::{pipe_with, PipeFlags};
+        use std::thread;
+        use std::time::Duration;
+
+        let (input, input_sender) = pipe_with(PipeFlags::CLOEXEC).unwrap();
+        let (wake, wake_sender) = linux::wake_pipe().unwrap();
+        rustix::io::write(&input_sender, b"x").unwrap();
+        assert!(linux::wait_for_input(&input, &wake).unwrap());
+
+        let mut buf = [0; 1];
+        rustix::io::read(&input, &mut buf).unwrap();
+        let waiter = thread::spawn(move || linux::wait_for_input(&input, &wake).unwrap());
+        thread::sleep(Duration::from_millis(50));
+        drop(wake_sender);
+        assert!(!waiter.join().unwrap());
+    }
+}
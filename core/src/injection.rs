@@ -0,0 +1,923 @@
+use enigo::{Direction, Enigo, Key, Keyboard, Settings as EnigoSettings};
+use std::path::Path;
+use std::process::Command;
+use thiserror::Error;
+
+use crate::config::InjectionBackend;
+use crate::hotkey::Hotkey;
+use crate::keycodes::{function_key_number, us_char, us_key_for, NAMED_KEYS};
+
+#[derive(Error, Debug)]
+pub enum InjectionError {
+    #[error("{backend} isn't available: {reason}")]
+    Unavailable {
+        backend: &'static str,
+        reason: String,
+    },
+
+    #[error("{backend} can't type '{ch}'")]
+    UnsupportedChar { backend: &'static str, ch: char },
+
+    #[error("{backend} can't press {keys}")]
+    UnsupportedKeys { backend: &'static str, keys: String },
+
+    #[error("{backend} failed: {reason}")]
+    Failed {
+        backend: &'static str,
+        reason: String,
+    },
+}
+
+fn unavailable(backend: &'static str, reason: impl ToString) -> InjectionError {
+    InjectionError::Unavailable {
+        backend,
+        reason: reason.to_string(),
+    }
+}
+
+fn failed(backend: &'static str, reason: impl ToString) -> InjectionError {
+    InjectionError::Failed {
+        backend,
+        reason: reason.to_string(),
+    }
+}
+
+/// A way of getting text and key presses into the focused application.
+pub trait TextInjector: Send {
+    /// Human readable backend name, for status messages.
+    fn name(&self) -> &'static str;
+
+    fn type_text(&mut self, text: &str) -> Result<(), InjectionError>;
+
+    /// Press and release a key combination such as `Ctrl+V`.
+    fn press_keys(&mut self, keys: &Hotkey) -> Result<(), InjectionError>;
+
+    /// Delete `text`, which was just typed and is right before the cursor.
+    fn erase(&mut self, text: &str) -> Result<(), InjectionError> {
+        let backspace = "Backspace"
+            .parse::<Hotkey>()
+            .map_err(|e| failed(self.name(), e))?;
+        for _ in text.chars() {
+            self.press_keys(&backspace)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionType {
+    Wayland,
+    X11,
+    Other, // e.g. a Linux console, macOS or Windows
+}
+
+impl SessionType {
+    pub fn detect() -> Self {
+        match std::env::var("XDG_SESSION_TYPE").as_deref() {
+            Ok("wayland") => return SessionType::Wayland,
+            Ok("x11") => return SessionType::X11,
+            _ => {}
+        }
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            SessionType::Wayland
+        } else if std::env::var_os("DISPLAY").is_some() {
+            SessionType::X11
+        } else {
+            SessionType::Other
+        }
+    }
+}
+
+/// Create the configured backend, or pick one for the session with `Auto`.
+pub fn create_injector(backend: InjectionBackend) -> Result<Box<dyn TextInjector>, InjectionError> {
+    match backend {
+        InjectionBackend::Auto => detect_injector(),
+        InjectionBackend::Enigo => Ok(Box::new(EnigoInjector::new()?)),
+        InjectionBackend::Wtype => Ok(Box::new(CommandInjector::wtype()?)),
+        InjectionBackend::Ydotool => Ok(Box::new(CommandInjector::ydotool()?)),
+        #[cfg(target_os = "linux")]
+        InjectionBackend::Uinput => Ok(Box::new(linux::UinputKeyboard::new()?)),
+        #[cfg(target_os = "linux")]
+        InjectionBackend::VirtualKeyboard => Ok(Box::new(linux::VirtualKeyboard::new()?)),
+        #[cfg(target_os = "linux")]
+        InjectionBackend::InputMethod => Ok(Box::new(linux::InputMethod::new()?)),
+        #[cfg(not(target_os = "linux"))]
+        _ => Err(unavailable("this backend", "only supported on Linux")),
+    }
+}
+
+fn detect_injector() -> Result<Box<dyn TextInjector>, InjectionError> {
+    let session = SessionType::detect();
+    // wtype needs the same protocol as VirtualKeyboard, and the input method
+    // only reaches text fields, so both are only used when chosen explicitly
+    let candidates: &[InjectionBackend] = match session {
+        SessionType::Wayland => &[
+            InjectionBackend::VirtualKeyboard,
+            InjectionBackend::Ydotool,
+            InjectionBackend::Uinput,
+            InjectionBackend::Enigo,
+        ],
+        SessionType::X11 => &[
+            InjectionBackend::Enigo,
+            InjectionBackend::Ydotool,
+            InjectionBackend::Uinput,
+        ],
+        SessionType::Other => &[
+            InjectionBackend::Uinput,
+            InjectionBackend::Ydotool,
+            InjectionBackend::Enigo,
+        ],
+    };
+
+    let mut reasons = Vec::new();
+    for &candidate in candidates {
+        match create_injector(candidate) {
+            Ok(injector) => {
+                tracing::info!(
+                    "{:?} session, injecting text with {}",
+                    session,
+                    injector.name()
+                );
+                if session == SessionType::Wayland && candidate == InjectionBackend::Enigo {
+                    tracing::warn!("enigo only reaches XWayland windows in a Wayland session");
+                }
+                return Ok(injector);
+            }
+            Err(e) => {
+                tracing::debug!("Skipping text injection backend: {}", e);
+                reasons.push(e.to_string());
+            }
+        }
+    }
+    Err(unavailable("Automatic detection", reasons.join("; ")))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    Meta,
+}
+
+impl Modifier {
+    // Input event code of the left-hand key
+    fn code(self) -> u16 {
+        match self {
+            Modifier::Ctrl => 29,
+            Modifier::Alt => 56,
+            Modifier::Shift => 42,
+            Modifier::Meta => 125,
+        }
+    }
+}
+
+fn held_modifiers(keys: &Hotkey) -> Vec<Modifier> {
+    [
+        (keys.modifiers.ctrl, Modifier::Ctrl),
+        (keys.modifiers.alt, Modifier::Alt),
+        (keys.modifiers.shift, Modifier::Shift),
+        (keys.modifiers.meta, Modifier::Meta),
+    ]
+    .into_iter()
+    .filter(|(held, _)| *held)
+    .map(|(_, modifier)| modifier)
+    .collect()
+}
+
+// XKB keysym name for a key
+fn keysym_name(code: u16) -> Option<String> {
+    if let Some(&(_, _, _, Some(keysym))) = NAMED_KEYS.iter().find(|(_, _, key, _)| *key == code) {
+        return Some(keysym.to_string());
+    }
+    if let Some(number) = function_key_number(code) {
+        return Some(format!("F{}", number));
+    }
+    us_char(code).map(char_keysym)
+}
+
+// XKB keysym name that types a character
+fn char_keysym(c: char) -> String {
+    match c {
+        '\n' => "Return".to_string(),
+        '\t' => "Tab".to_string(),
+        ' ' => "space".to_string(),
+        c if c.is_ascii_alphanumeric() => c.to_string(),
+        c => format!("U{:04X}", c as u32),
+    }
+}
+
+/// enigo, which drives X11 (and XWayland) through XTest.
+pub struct EnigoInjector {
+    enigo: Enigo,
+}
+
+impl EnigoInjector {
+    const NAME: &'static str = "enigo";
+
+    pub fn new() -> Result<Self, InjectionError> {
+        let enigo = Enigo::new(&EnigoSettings::default())
+            .map_err(|e| unavailable(Self::NAME, format!("{:?}", e)))?;
+        Ok(Self { enigo })
+    }
+
+    fn key(code: u16) -> Option<Key> {
+        let key = match code {
+            1 => Key::Escape,
+            14 => Key::Backspace,
+            15 => Key::Tab,
+            28 => Key::Return,
+            29 => Key::LControl,
+            42 => Key::LShift,
+            54 => Key::RShift,
+            56 => Key::Alt,
+            57 => Key::Space,
+            58 => Key::CapsLock,
+            97 => Key::RControl,
+            102 => Key::Home,
+            103 => Key::UpArrow,
+            104 => Key::PageUp,
+            105 => Key::LeftArrow,
+            106 => Key::RightArrow,
+            107 => Key::End,
+            108 => Key::DownArrow,
+            109 => Key::PageDown,
+            111 => Key::Delete,
+            125 => Key::Meta,
+            #[cfg(not(target_os = "macos"))]
+            69 => Key::Numlock,
+            #[cfg(not(target_os = "macos"))]
+            99 => Key::Print,
+            #[cfg(not(target_os = "macos"))]
+            110 => Key::Insert,
+            #[cfg(not(target_os = "macos"))]
+            119 => Key::Pause,
+            _ => {
+                const F_KEYS: [Key; 20] = [
+                    Key::F1,
+                    Key::F2,
+                    Key::F3,
+                    Key::F4,
+                    Key::F5,
+                    Key::F6,
+                    Key::F7,
+                    Key::F8,
+                    Key::F9,
+                    Key::F10,
+                    Key::F11,
+                    Key::F12,
+                    Key::F13,
+                    Key::F14,
+                    Key::F15,
+                    Key::F16,
+                    Key::F17,
+                    Key::F18,
+                    Key::F19,
+                    Key::F20,
+                ];
+                return match function_key_number(code) {
+                    Some(number) => F_KEYS.get(number as usize - 1).copied(),
+                    None => us_char(code).map(Key::Unicode),
+                };
+            }
+        };
+        Some(key)
+    }
+}
+
+impl TextInjector for EnigoInjector {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), InjectionError> {
+        self.enigo
+            .text(text)
+            .map_err(|e| failed(Self::NAME, format!("{:?}", e)))
+    }
+
+    fn press_keys(&mut self, keys: &Hotkey) -> Result<(), InjectionError> {
+        let key = Self::key(keys.key).ok_or_else(|| InjectionError::UnsupportedKeys {
+            backend: Self::NAME,
+            keys: keys.to_string(),
+        })?;
+
+        let mut result = Ok(());
+        let mut pressed = Vec::new();
+        for modifier in held_modifiers(keys) {
+            let modifier = match modifier {
+                Modifier::Ctrl => Key::Control,
+                Modifier::Alt => Key::Alt,
+                Modifier::Shift => Key::Shift,
+                Modifier::Meta => Key::Meta,
+            };
+            result = self.enigo.key(modifier, Direction::Press);
+            if result.is_err() {
+                break;
+            }
+            pressed.push(modifier);
+        }
+        if result.is_ok() {
+            result = self.enigo.key(key, Direction::Click);
+        }
+
+        // Never leave a modifier held down
+        for &modifier in pressed.iter().rev() {
+            if let Err(e) = self.enigo.key(modifier, Direction::Release) {
+                tracing::warn!("Failed to release {:?}: {:?}", modifier, e);
+            }
+        }
+
+        result.map_err(|e| failed(Self::NAME, format!("{:?}", e)))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum InjectionCommand {
+    Wtype,
+    Ydotool,
+}
+
+/// Shells out to `wtype` or `ydotool`.
+pub struct CommandInjector {
+    command: InjectionCommand,
+}
+
+impl CommandInjector {
+    pub fn wtype() -> Result<Self, InjectionError> {
+        if !on_path("wtype") {
+            return Err(unavailable("wtype", "not found on PATH"));
+        }
+        Ok(Self {
+            command: InjectionCommand::Wtype,
+        })
+    }
+
+    pub fn ydotool() -> Result<Self, InjectionError> {
+        if !on_path("ydotool") {
+            return Err(unavailable("ydotool", "not found on PATH"));
+        }
+        // ydotool only talks to its daemon, which owns the uinput device
+        let socket = std::env::var_os("YDOTOOL_SOCKET")
+            .map(Into::into)
+            .or_else(|| {
+                std::env::var_os("XDG_RUNTIME_DIR")
+                    .map(|dir| Path::new(&dir).join(".ydotool_socket"))
+            })
+            .filter(|socket: &std::path::PathBuf| socket.exists())
+            .or_else(|| {
+                let legacy = Path::new("/tmp/.ydotool_socket");
+                legacy.exists().then(|| legacy.to_path_buf())
+            });
+        if socket.is_none() {
+            return Err(unavailable(
+                "ydotool",
+                "ydotoold doesn't seem to be running",
+            ));
+        }
+        Ok(Self {
+            command: InjectionCommand::Ydotool,
+        })
+    }
+
+    fn program(&self) -> &'static str {
+        match self.command {
+            InjectionCommand::Wtype => "wtype",
+            InjectionCommand::Ydotool => "ydotool",
+        }
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), InjectionError> {
+        let program = self.program();
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| failed(program, e))?;
+        if !output.status.success() {
+            return Err(failed(
+                program,
+                format!(
+                    "{} ({})",
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                    output.status
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl TextInjector for CommandInjector {
+    fn name(&self) -> &'static str {
+        self.program()
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), InjectionError> {
+        let args = match self.command {
+            InjectionCommand::Wtype => vec!["--".to_string(), text.to_string()],
+            // ydotool types with the US layout too
+            InjectionCommand::Ydotool => {
+                if let Some(ch) = text.chars().find(|&c| us_key_for(c).is_none()) {
+                    return Err(InjectionError::UnsupportedChar {
+                        backend: self.program(),
+                        ch,
+                    });
+                }
+                vec!["type".to_string(), "--".to_string(), text.to_string()]
+            }
+        };
+        self.run(&args)
+    }
+
+    fn press_keys(&mut self, keys: &Hotkey) -> Result<(), InjectionError> {
+        let modifiers = held_modifiers(keys);
+        let args = match self.command {
+            InjectionCommand::Wtype => {
+                let keysym =
+                    keysym_name(keys.key).ok_or_else(|| InjectionError::UnsupportedKeys {
+                        backend: self.program(),
+                        keys: keys.to_string(),
+                    })?;
+                let names: Vec<&str> = modifiers
+                    .iter()
+                    .map(|modifier| match modifier {
+                        Modifier::Ctrl => "ctrl",
+                        Modifier::Alt => "alt",
+                        Modifier::Shift => "shift",
+                        Modifier::Meta => "logo",
+                    })
+                    .collect();
+
+                let mut args = Vec::new();
+                for name in &names {
+                    args.extend(["-M".to_string(), name.to_string()]);
+                }
+                args.extend(["-k".to_string(), keysym]);
+                for name in names.iter().rev() {
+                    args.extend(["-m".to_string(), name.to_string()]);
+                }
+                args
+            }
+            // Raw input event codes, code:1 presses and code:0 releases
+            InjectionCommand::Ydotool => {
+                let codes: Vec<u16> = modifiers
+                    .iter()
+                    .map(|modifier| modifier.code())
+                    .chain([keys.key])
+                    .collect();
+                let mut args = vec!["key".to_string()];
+                args.extend(codes.iter().map(|code| format!("{}:1", code)));
+                args.extend(codes.iter().rev().map(|code| format!("{}:0", code)));
+                args
+            }
+        };
+        self.run(&args)
+    }
+
+    // One process for all the backspaces rather than one each
+    fn erase(&mut self, text: &str) -> Result<(), InjectionError> {
+        let count = text.chars().count();
+        if count == 0 {
+            return Ok(());
+        }
+        let args: Vec<String> = match self.command {
+            InjectionCommand::Wtype => ["-k", "BackSpace"]
+                .iter()
+                .cycle()
+                .take(count * 2)
+                .map(|arg| arg.to_string())
+                .collect(),
+            InjectionCommand::Ydotool => std::iter::once("key")
+                .chain(["14:1", "14:0"].iter().cycle().take(count * 2).copied())
+                .map(str::to_string)
+                .collect(),
+        };
+        self.run(&args)
+    }
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use evdev::uinput::VirtualDevice;
+    use evdev::{AttributeSet, EventType, InputEvent, KeyCode};
+    use rustix::fs::{memfd_create, MemfdFlags};
+    use std::fs::File;
+    use std::io::Write;
+    use std::os::fd::AsFd;
+    use std::time::{Duration, Instant};
+    use wayland_client::globals::{registry_queue_init, GlobalList, GlobalListContents};
+    use wayland_client::protocol::wl_registry::{self, WlRegistry};
+    use wayland_client::protocol::wl_seat::WlSeat;
+    use wayland_client::{delegate_noop, Connection, Dispatch, EventQueue, QueueHandle};
+    use wayland_protocols_misc::zwp_input_method_v2::client::zwp_input_method_manager_v2::ZwpInputMethodManagerV2;
+    use wayland_protocols_misc::zwp_input_method_v2::client::zwp_input_method_v2::{
+        self, ZwpInputMethodV2,
+    };
+    use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1;
+    use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1;
+
+    use super::{
+        char_keysym, failed, held_modifiers, keysym_name, unavailable, InjectionError, Modifier,
+        TextInjector,
+    };
+    use crate::hotkey::Hotkey;
+    use crate::keycodes::us_key_for;
+
+    // The compositor needs a moment to pick up a new uinput device, events
+    // sent before that are lost
+    const UINPUT_SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+    // Some applications drop keys that arrive too quickly
+    const KEY_DELAY: Duration = Duration::from_millis(2);
+
+    /// A virtual keyboard device created through /dev/uinput.
+    pub struct UinputKeyboard {
+        device: VirtualDevice,
+    }
+
+    impl UinputKeyboard {
+        const NAME: &'static str = "uinput";
+
+        pub fn new() -> Result<Self, InjectionError> {
+            let mut keys = AttributeSet::<KeyCode>::new();
+            for code in 1..=255 {
+                keys.insert(KeyCode::new(code));
+            }
+
+            let device = VirtualDevice::builder()
+                .and_then(|builder| builder.name("WhisperKey virtual keyboard").with_keys(&keys))
+                .and_then(|builder| builder.build())
+                .map_err(|e| {
+                    unavailable(Self::NAME, format!("{} (is /dev/uinput writable?)", e))
+                })?;
+            std::thread::sleep(UINPUT_SETTLE_DELAY);
+
+            Ok(Self { device })
+        }
+
+        fn emit(&mut self, events: &[(u16, i32)]) -> Result<(), InjectionError> {
+            let events: Vec<InputEvent> = events
+                .iter()
+                .map(|&(code, value)| InputEvent::new(EventType::KEY.0, code, value))
+                .collect();
+            // emit() adds the SYN_REPORT
+            self.device
+                .emit(&events)
+                .map_err(|e| failed(Self::NAME, e))?;
+            std::thread::sleep(KEY_DELAY);
+            Ok(())
+        }
+    }
+
+    impl TextInjector for UinputKeyboard {
+        fn name(&self) -> &'static str {
+            Self::NAME
+        }
+
+        fn type_text(&mut self, text: &str) -> Result<(), InjectionError> {
+            // Check everything first rather than typing half of it
+            let keys = text
+                .chars()
+                .map(|ch| {
+                    us_key_for(ch).ok_or(InjectionError::UnsupportedChar {
+                        backend: Self::NAME,
+                        ch,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let shift = Modifier::Shift.code();
+            for (code, shifted) in keys {
+                if shifted {
+                    self.emit(&[(shift, 1), (code, 1), (code, 0), (shift, 0)])?;
+                } else {
+                    self.emit(&[(code, 1), (code, 0)])?;
+                }
+            }
+            Ok(())
+        }
+
+        fn press_keys(&mut self, keys: &Hotkey) -> Result<(), InjectionError> {
+            let codes: Vec<u16> = held_modifiers(keys)
+                .iter()
+                .map(|modifier| modifier.code())
+                .chain([keys.key])
+                .collect();
+            let mut events: Vec<(u16, i32)> = codes.iter().map(|&code| (code, 1)).collect();
+            events.extend(codes.iter().rev().map(|&code| (code, 0)));
+            self.emit(&events)
+        }
+    }
+
+    // Tracks the input method's activation, applied on each done event
+    #[derive(Default)]
+    struct WaylandState {
+        pending_active: bool,
+        active: bool,
+        serial: u32, // Done events so far, commits must echo it
+        unavailable: bool,
+    }
+
+    impl Dispatch<WlRegistry, GlobalListContents> for WaylandState {
+        fn event(
+            _state: &mut Self,
+            _registry: &WlRegistry,
+            _event: wl_registry::Event,
+            _data: &GlobalListContents,
+            _conn: &Connection,
+            _qh: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<ZwpInputMethodV2, ()> for WaylandState {
+        fn event(
+            state: &mut Self,
+            _input_method: &ZwpInputMethodV2,
+            event: zwp_input_method_v2::Event,
+            _data: &(),
+            _conn: &Connection,
+            _qh: &QueueHandle<Self>,
+        ) {
+            match event {
+                zwp_input_method_v2::Event::Activate => state.pending_active = true,
+                zwp_input_method_v2::Event::Deactivate => state.pending_active = false,
+                zwp_input_method_v2::Event::Done => {
+                    state.active = state.pending_active;
+                    state.serial += 1;
+                }
+                zwp_input_method_v2::Event::Unavailable => state.unavailable = true,
+                _ => {}
+            }
+        }
+    }
+
+    delegate_noop!(WaylandState: ignore WlSeat);
+    delegate_noop!(WaylandState: ZwpVirtualKeyboardManagerV1);
+    delegate_noop!(WaylandState: ZwpVirtualKeyboardV1);
+    delegate_noop!(WaylandState: ZwpInputMethodManagerV2);
+
+    fn connect(
+        backend: &'static str,
+    ) -> Result<(GlobalList, EventQueue<WaylandState>, WlSeat), InjectionError> {
+        let conn = Connection::connect_to_env().map_err(|e| unavailable(backend, e))?;
+        let (globals, queue) =
+            registry_queue_init::<WaylandState>(&conn).map_err(|e| unavailable(backend, e))?;
+        let seat = globals
+            .bind::<WlSeat, _, _>(&queue.handle(), 1..=1, ())
+            .map_err(|e| unavailable(backend, format!("no seat: {}", e)))?;
+        Ok((globals, queue, seat))
+    }
+
+    // wl_keyboard.keymap_format.xkb_v1
+    const KEYMAP_FORMAT_XKB_V1: u32 = 1;
+
+    /// Wayland's zwp_virtual_keyboard_v1, supported by wlroots compositors
+    /// and KDE but not GNOME.
+    ///
+    /// Like wtype, it uploads a keymap with one key per character to type, so
+    /// it types any text regardless of the user's layout.
+    pub struct VirtualKeyboard {
+        queue: EventQueue<WaylandState>,
+        state: WaylandState,
+        keyboard: ZwpVirtualKeyboardV1,
+        start: Instant, // Key event timestamps are relative to this
+    }
+
+    impl VirtualKeyboard {
+        const NAME: &'static str = "Wayland virtual keyboard";
+
+        pub fn new() -> Result<Self, InjectionError> {
+            let (globals, mut queue, seat) = connect(Self::NAME)?;
+            let qh = queue.handle();
+            let manager = globals
+                .bind::<ZwpVirtualKeyboardManagerV1, _, _>(&qh, 1..=1, ())
+                .map_err(|_| {
+                    unavailable(
+                        Self::NAME,
+                        "the compositor doesn't support zwp_virtual_keyboard_v1",
+                    )
+                })?;
+            let keyboard = manager.create_virtual_keyboard(&seat, &qh, ());
+
+            // Compositors can refuse with a protocol error
+            let mut state = WaylandState::default();
+            queue
+                .roundtrip(&mut state)
+                .map_err(|e| unavailable(Self::NAME, e))?;
+
+            Ok(Self {
+                queue,
+                state,
+                keyboard,
+                start: Instant::now(),
+            })
+        }
+
+        fn load_keymap(&mut self, keysyms: &[String]) -> Result<(), InjectionError> {
+            let mut keymap = String::from("xkb_keymap {\nxkb_keycodes \"(unnamed)\" {\n");
+            keymap += &format!("minimum = 8;\nmaximum = {};\n", keysyms.len() + 9);
+            for index in 0..keysyms.len() {
+                keymap += &format!("<K{}> = {};\n", index + 1, index + 9);
+            }
+            keymap += "};\n";
+            keymap += "xkb_types \"(unnamed)\" { include \"complete\" };\n";
+            keymap += "xkb_compatibility \"(unnamed)\" { include \"complete\" };\n";
+            keymap += "xkb_symbols \"(unnamed)\" {\n";
+            for (index, keysym) in keysyms.iter().enumerate() {
+                keymap += &format!("key <K{}> {{[{}]}};\n", index + 1, keysym);
+            }
+            keymap += "};\n};\n";
+
+            let file = keymap_file(&keymap).map_err(|e| failed(Self::NAME, e))?;
+            // The size includes the NUL terminator
+            self.keyboard
+                .keymap(KEYMAP_FORMAT_XKB_V1, file.as_fd(), keymap.len() as u32 + 1);
+            Ok(())
+        }
+
+        // Keymap index to key code, which the compositor offsets by 8
+        fn tap(&mut self, index: usize) -> Result<(), InjectionError> {
+            let key = index as u32 + 1;
+            let time = self.start.elapsed().as_millis() as u32;
+            self.keyboard.key(time, key, 1);
+            self.keyboard.key(time, key, 0);
+            self.queue.flush().map_err(|e| failed(Self::NAME, e))?;
+            std::thread::sleep(KEY_DELAY);
+            Ok(())
+        }
+
+        fn sync(&mut self) -> Result<(), InjectionError> {
+            self.queue
+                .roundtrip(&mut self.state)
+                .map(|_| ())
+                .map_err(|e| failed(Self::NAME, e))
+        }
+    }
+
+    impl TextInjector for VirtualKeyboard {
+        fn name(&self) -> &'static str {
+            Self::NAME
+        }
+
+        fn type_text(&mut self, text: &str) -> Result<(), InjectionError> {
+            let mut keysyms: Vec<String> = Vec::new();
+            let mut indexes = Vec::new();
+            for ch in text.chars() {
+                let keysym = char_keysym(ch);
+                let index = match keysyms.iter().position(|known| *known == keysym) {
+                    Some(index) => index,
+                    None => {
+                        keysyms.push(keysym);
+                        keysyms.len() - 1
+                    }
+                };
+                indexes.push(index);
+            }
+            if keysyms.is_empty() {
+                return Ok(());
+            }
+
+            self.load_keymap(&keysyms)?;
+            for index in indexes {
+                self.tap(index)?;
+            }
+            self.sync()
+        }
+
+        fn press_keys(&mut self, keys: &Hotkey) -> Result<(), InjectionError> {
+            let keysym = keysym_name(keys.key).ok_or_else(|| InjectionError::UnsupportedKeys {
+                backend: Self::NAME,
+                keys: keys.to_string(),
+            })?;
+
+            // Modifier masks as in the standard XKB modifier map
+            let mask = held_modifiers(keys)
+                .iter()
+                .map(|modifier| match modifier {
+                    Modifier::Shift => 1,
+                    Modifier::Ctrl => 4,
+                    Modifier::Alt => 8,
+                    Modifier::Meta => 64,
+                })
+                .fold(0, |mask, bit| mask | bit);
+
+            self.load_keymap(&[keysym])?;
+            self.keyboard.modifiers(mask, 0, 0, 0);
+            let result = self.tap(0);
+            self.keyboard.modifiers(0, 0, 0, 0);
+            result?;
+            self.sync()
+        }
+    }
+
+    // The compositor maps the keymap from a file descriptor. An anonymous
+    // memory file never appears on disk, so nothing else can get at it.
+    fn keymap_file(keymap: &str) -> std::io::Result<File> {
+        let mut file = File::from(memfd_create("whisperkey-keymap", MemfdFlags::CLOEXEC)?);
+        file.write_all(keymap.as_bytes())?;
+        file.write_all(&[0])?;
+        file.flush()?;
+        Ok(file)
+    }
+
+    /// Wayland's zwp_input_method_v2, which commits text straight into the
+    /// focused text field. Only one input method can run per seat, so this
+    /// doesn't work alongside IBus or fcitx, and it can't press keys.
+    pub struct InputMethod {
+        queue: EventQueue<WaylandState>,
+        state: WaylandState,
+        input_method: ZwpInputMethodV2,
+    }
+
+    impl InputMethod {
+        const NAME: &'static str = "Wayland input method";
+
+        pub fn new() -> Result<Self, InjectionError> {
+            let (globals, mut queue, seat) = connect(Self::NAME)?;
+            let qh = queue.handle();
+            let manager = globals
+                .bind::<ZwpInputMethodManagerV2, _, _>(&qh, 1..=1, ())
+                .map_err(|_| {
+                    unavailable(
+                        Self::NAME,
+                        "the compositor doesn't support zwp_input_method_v2",
+                    )
+                })?;
+            let input_method = manager.get_input_method(&seat, &qh, ());
+
+            let mut state = WaylandState::default();
+            queue
+                .roundtrip(&mut state)
+                .map_err(|e| unavailable(Self::NAME, e))?;
+            if state.unavailable {
+                return Err(unavailable(
+                    Self::NAME,
+                    "another input method is already running",
+                ));
+            }
+
+            Ok(Self {
+                queue,
+                state,
+                input_method,
+            })
+        }
+
+        // Catch up on focus changes since the last commit
+        fn check_focus(&mut self) -> Result<(), InjectionError> {
+            self.queue
+                .roundtrip(&mut self.state)
+                .map_err(|e| failed(Self::NAME, e))?;
+            if self.state.unavailable {
+                return Err(failed(Self::NAME, "replaced by another input method"));
+            }
+            if !self.state.active {
+                return Err(failed(Self::NAME, "no text field is focused"));
+            }
+            Ok(())
+        }
+
+        fn commit(&mut self) -> Result<(), InjectionError> {
+            self.input_method.commit(self.state.serial);
+            self.queue
+                .roundtrip(&mut self.state)
+                .map(|_| ())
+                .map_err(|e| failed(Self::NAME, e))
+        }
+    }
+
+    impl TextInjector for InputMethod {
+        fn name(&self) -> &'static str {
+            Self::NAME
+        }
+
+        fn type_text(&mut self, text: &str) -> Result<(), InjectionError> {
+            self.check_focus()?;
+            self.input_method.commit_string(text.to_string());
+            self.commit()
+        }
+
+        // Backspace isn't available to an input method, but deleting text
+        // around the cursor is
+        fn erase(&mut self, text: &str) -> Result<(), InjectionError> {
+            self.check_focus()?;
+            let before = u32::try_from(text.len())
+                .map_err(|_| failed(Self::NAME, "too much text to delete"))?;
+            self.input_method.delete_surrounding_text(before, 0);
+            self.commit()
+        }
+
+        fn press_keys(&mut self, keys: &Hotkey) -> Result<(), InjectionError> {
+            Err(InjectionError::UnsupportedKeys {
+                backend: Self::NAME,
+                keys: keys.to_string(),
+            })
+        }
+    }
+}
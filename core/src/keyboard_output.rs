@@ -1,11 +1,33 @@
-use enigo::{Enigo, Keyboard, Settings};
+use arboard::Clipboard;
 use ractor::{Actor, ActorProcessingErr, ActorRef};
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
 use std::time::Duration;
 use thiserror::Error;
+use tokio::sync::oneshot;
 
-use crate::config::Settings as AppSettings;
-use crate::types::{CoordinatorMsg, KeyboardOutputMsg};
+use crate::config::{InjectionBackend, OutputMethod, PasteShortcut, Settings as AppSettings};
+use crate::hotkey::{Hotkey, Modifiers};
+use crate::injection::{create_injector, InjectionError, TextInjector};
+use crate::types::{CoordinatorMsg, InjectionOutcome, KeyboardOutputMsg};
+
+// In Auto mode, text at least this long is pasted rather than typed
+const AUTO_PASTE_MIN_CHARS: usize = 32;
+
+// Applications read the clipboard asynchronously after the paste shortcut, so
+// restoring it right away can paste the old contents instead
+const CLIPBOARD_RESTORE_DELAY: Duration = Duration::from_millis(300);
+
+// How many typed texts "scratch that" can remove one after the other
+const MAX_HISTORY: usize = 20;
+
+// Linux input event code for Enter
+const KEY_ENTER: u16 = 28;
+
+// The injection thread checks for a cancel between pieces of this many
+// characters, however long the chunk
+const CANCEL_CHECK_CHARS: usize = 16;
 
 // Error types for keyboard output
 #[derive(Error, Debug)]
@@ -15,15 +37,214 @@ pub enum KeyboardOutputError {
 
     #[error("Failed to type text: {0}")]
     TypeError(String),
+
+    #[error("Failed to paste text: {0}")]
+    PasteError(String),
 }
 
 pub struct KeyboardOutputActor {}
 
+// Output waiting in the queue or partly typed
+struct TypingItem {
+    id: u64,
+    text: String,
+    position: usize, // Byte offset of the first character not typed yet
+    typed: usize,    // Characters typed so far
+    total: usize,
+    paste: bool,
+    erase: Erase,      // Deleted before anything else
+    keys: Vec<Hotkey>, // Pressed before the text
+    kept: String,      // Typed live from partial results and kept, part of the same utterance
+    cancelled: bool,   // Dropped once the injection thread is done with its current step
+}
+
+impl TypingItem {
+    fn new(text: String) -> Self {
+        Self {
+            id: 0,
+            position: 0,
+            typed: 0,
+            total: text.chars().count(),
+            text,
+            paste: false,
+            erase: Erase::Nothing,
+            keys: Vec::new(),
+            kept: String::new(),
+            cancelled: false,
+        }
+    }
+}
+
+#[derive(Default)]
+enum Erase {
+    #[default]
+    Nothing,
+    Live(String), // Typed from partial results of the same utterance
+    LastTyped,    // Scratch that
+}
+
+// Text typed from partial results of the utterance in progress
+#[derive(Default)]
+struct LiveText {
+    typed: String, // On screen, as far as we know
+    status: LiveStatus,
+    final_text: Option<String>, // Arrived while a correction was still being typed
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum LiveStatus {
+    #[default]
+    Following,
+    Paused,    // A correction went past the cap, or typing failed
+    Cancelled, // The rest of the utterance isn't typed either
+}
+
+// A step of output handed to the injection thread
+#[derive(Debug)]
+enum Injection {
+    Erase(String),
+    PressKeys(Vec<Hotkey>),
+    Type(String),
+}
+
+struct InjectionJob {
+    generation: u64,
+    injection: Injection,
+}
+
+// What the step on the injection thread is for, so its outcome can be applied.
+// Steps of the front item keep it there until they're done.
+enum Step {
+    Erase { scratched: Option<String> }, // Ahead of the front item
+    Keys { line_breaks: Option<usize> }, // Count given when they're all Enter
+    Paste,                               // The front item's text
+    Chunk { chars: usize },              // Of the front item's text
+    LiveErase { keep: usize, append: String }, // Correcting a partial result
+    LiveType(String),                    // Rest of a partial result
+}
+
+// Backends block while injecting (uinput waits for the device to settle, wtype
+// and ydotool run a process per call), so they run on their own thread and
+// report back with InjectionOutcomes
+struct InjectionThread {
+    jobs: mpsc::Sender<InjectionJob>,
+    generation: Arc<AtomicU64>, // The actor's, so a cancel stops the step in progress
+}
+
+impl InjectionThread {
+    // Start the thread, which sends the backend's name or why it couldn't be
+    // created to `ready`
+    fn spawn(
+        actor: ActorRef<KeyboardOutputMsg>,
+        backend: InjectionBackend,
+        ready: oneshot::Sender<Result<&'static str, InjectionError>>,
+    ) -> std::io::Result<Self> {
+        let (jobs, receiver) = mpsc::channel::<InjectionJob>();
+        let generation = Arc::new(AtomicU64::new(0));
+        let current = generation.clone();
+
+        std::thread::Builder::new()
+            .name("keyboard-output".to_string())
+            .spawn(move || {
+                let mut injector = match open_injector(backend) {
+                    Ok(injector) => {
+                        let _ = ready.send(Ok(injector.name()));
+                        injector
+                    }
+                    Err(e) => {
+                        let _ = ready.send(Err(e));
+                        return;
+                    }
+                };
+
+                // Ends when the actor stops and drops the sender
+                for job in receiver {
+                    let outcome = inject(injector.as_mut(), &job, &current);
+                    if actor
+                        .send_message(KeyboardOutputMsg::Injected(outcome))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self { jobs, generation })
+    }
+}
+
+// The configured backend, or a detected one if it isn't available
+fn open_injector(backend: InjectionBackend) -> Result<Box<dyn TextInjector>, InjectionError> {
+    create_injector(backend).or_else(|e| {
+        if backend == InjectionBackend::Auto {
+            return Err(e);
+        }
+        tracing::warn!("{}, detecting a backend instead", e);
+        create_injector(InjectionBackend::Auto)
+    })
+}
+
+// Run one step, stopping early if the actor's generation moves on
+fn inject(
+    injector: &mut dyn TextInjector,
+    job: &InjectionJob,
+    generation: &AtomicU64,
+) -> InjectionOutcome {
+    let cancelled = || generation.load(Ordering::Acquire) != job.generation;
+    let mut done = 0;
+    match &job.injection {
+        Injection::Erase(text) => {
+            if cancelled() {
+                return InjectionOutcome::Cancelled { done };
+            }
+            if let Err(error) = injector.erase(text) {
+                return InjectionOutcome::Failed { done, error };
+            }
+        }
+        Injection::PressKeys(keys) => {
+            for keys in keys {
+                if cancelled() {
+                    return InjectionOutcome::Cancelled { done };
+                }
+                if let Err(error) = injector.press_keys(keys) {
+                    return InjectionOutcome::Failed { done, error };
+                }
+                done += 1;
+            }
+        }
+        Injection::Type(text) => {
+            let mut rest = text.as_str();
+            while !rest.is_empty() {
+                if cancelled() {
+                    return InjectionOutcome::Cancelled { done };
+                }
+                let (piece, after) = split_at_char(rest, CANCEL_CHECK_CHARS);
+                if let Err(error) = injector.type_text(piece) {
+                    return InjectionOutcome::Failed { done, error };
+                }
+                done += piece.chars().count();
+                rest = after;
+            }
+        }
+    }
+    InjectionOutcome::Done
+}
+
 pub struct KeyboardOutputState {
-    enigo: Enigo,
+    injection: InjectionThread,
+    pending: Option<Step>, // On the injection thread, the next step waits for it
+    clipboard: Option<Clipboard>, // None when the output method is Type or it's unavailable
     coordinator: ActorRef<CoordinatorMsg>,
     config: Arc<AppSettings>,
     enabled: bool,
+    queue: VecDeque<TypingItem>,
+    next_item: u64,
+    scheduled: bool, // A TypeNext is on its way
+    generation: u64, // Bumped on cancel, so scheduled TypeNexts are ignored
+    paste_count: u64,
+    clipboard_backup: Option<String>, // Clipboard text from before our pastes
+    live: Option<LiveText>,
+    history: VecDeque<String>, // What recent items typed, newest last
 }
 
 #[ractor::async_trait]
@@ -34,39 +255,73 @@ impl Actor for KeyboardOutputActor {
 
     async fn pre_start(
         &self,
-        _myself: ActorRef<Self::Msg>,
+        myself: ActorRef<Self::Msg>,
         (coordinator, config): Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
         tracing::info!("KeyboardOutputActor started");
 
-        // Initialize Enigo keyboard controller
-        // Create default settings for Enigo
-        let enigo_settings = Settings::default();
-        let enigo = match Enigo::new(&enigo_settings) {
-            Ok(e) => e,
+        // Initialize the text injection backend on its thread, without
+        // blocking this one while it does
+        let (ready_sender, ready) = oneshot::channel();
+        let started = match InjectionThread::spawn(myself, config.injection_backend, ready_sender) {
+            Ok(injection) => match ready.await {
+                Ok(Ok(name)) => Ok((injection, name)),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("the injection thread exited".to_string()),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        let (injection, injector_name) = match started {
+            Ok(started) => started,
             Err(e) => {
-                tracing::error!("Failed to initialize keyboard controller: {:?}", e);
-                return Err(ActorProcessingErr::from(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to initialize keyboard controller: {:?}", e),
-                )));
+                tracing::error!("Failed to initialize keyboard controller: {}", e);
+                return Err(ActorProcessingErr::from(std::io::Error::other(format!(
+                    "Failed to initialize keyboard controller: {}",
+                    e
+                ))));
+            }
+        };
+        tracing::info!("Keyboard output using {}", injector_name);
+
+        // The clipboard is only needed for pasting. On X11 it has to stay
+        // alive, since whoever set the text serves it to the pasting app.
+        let clipboard = if config.output_method == OutputMethod::Type {
+            None
+        } else {
+            match Clipboard::new() {
+                Ok(clipboard) => Some(clipboard),
+                Err(e) => {
+                    tracing::warn!("Clipboard unavailable, typing instead of pasting: {}", e);
+                    None
+                }
             }
         };
 
         // Create state with default values
         let state = KeyboardOutputState {
-            enigo,
+            injection,
+            pending: None,
+            clipboard,
             coordinator,
             config,
             enabled: false, // Disabled by default for safety
+            queue: VecDeque::new(),
+            next_item: 0,
+            scheduled: false,
+            generation: 0,
+            paste_count: 0,
+            clipboard_backup: None,
+            live: None,
+            history: VecDeque::new(),
         };
 
         // Send status update to coordinator
         state
             .coordinator
-            .send_message(CoordinatorMsg::UpdateStatus(
-                "Keyboard output initialized (disabled by default)".to_string(),
-            ))
+            .send_message(CoordinatorMsg::UpdateStatus(format!(
+                "Keyboard output initialized with {} (disabled by default)",
+                injector_name
+            )))
             .ok();
 
         Ok(state)
@@ -74,48 +329,86 @@ impl Actor for KeyboardOutputActor {
 
     async fn handle(
         &self,
-        _myself: ActorRef<Self::Msg>,
+        myself: ActorRef<Self::Msg>,
         message: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
         match message {
             KeyboardOutputMsg::TypeText(text) => {
+                tracing::info!("Queueing text: {}", text);
+                queue_item(&myself, state, TypingItem::new(text));
+            }
+            KeyboardOutputMsg::PressKeys(keys) => {
+                tracing::info!("Queueing keys: {:?}", keys);
+                let item = TypingItem {
+                    keys,
+                    ..TypingItem::new(String::new())
+                };
+                queue_item(&myself, state, item);
+            }
+            KeyboardOutputMsg::ScratchThat => {
+                tracing::info!("Queueing scratch that");
+                let item = TypingItem {
+                    erase: Erase::LastTyped,
+                    ..TypingItem::new(String::new())
+                };
+                queue_item(&myself, state, item);
+            }
+            KeyboardOutputMsg::LivePartial(text) => {
                 if state.enabled {
-                    tracing::info!("Typing text: {}", text);
-
-                    // Small delay to let user prepare
-                    if state.config.keyboard_output_delay_ms > 0 {
-                        // Use std::thread::sleep instead of tokio::time::sleep
-                        std::thread::sleep(Duration::from_millis(
-                            state.config.keyboard_output_delay_ms as u64,
-                        ));
-                    }
-
-                    // Type the text
-                    if let Err(e) = state.enigo.text(&text) {
-                        tracing::error!("Failed to type text: {:?}", e);
-                    }
-
-                    // Notify coordinator of success
-                    state
-                        .coordinator
-                        .send_message(CoordinatorMsg::UpdateStatus(format!(
-                            "Typed text: {}",
-                            text
-                        )))
-                        .ok();
-                } else {
-                    tracing::info!("Keyboard output is disabled, not typing: {}", text);
+                    type_partial(state, &text);
+                }
+            }
+            KeyboardOutputMsg::LiveFinal(text) => {
+                // What the partials left on screen isn't known until the
+                // correction being typed is done
+                if let (Some(Step::LiveErase { .. } | Step::LiveType(_)), Some(live)) =
+                    (&state.pending, &mut state.live)
+                {
+                    live.final_text = Some(text);
+                    return Ok(());
+                }
+                live_final(&myself, state, text);
+            }
+            KeyboardOutputMsg::TypeNext { generation } => {
+                // Scheduled before a cancel
+                if generation != state.generation {
+                    return Ok(());
+                }
+                state.scheduled = false;
+                next_step(&myself, state);
+            }
+            KeyboardOutputMsg::Injected(outcome) => {
+                if let Some(step) = state.pending.take() {
+                    step_done(&myself, state, step, outcome);
+                }
+            }
+            KeyboardOutputMsg::Cancel => {
+                if cancel_queue(state) > 0 {
                     state
                         .coordinator
-                        .send_message(CoordinatorMsg::UpdateStatus(
-                            "Keyboard output is disabled (enable in settings)".to_string(),
-                        ))
+                        .send_message(CoordinatorMsg::UpdateStatus("Typing cancelled".to_string()))
                         .ok();
                 }
             }
+            KeyboardOutputMsg::RestoreClipboard { paste } => {
+                // A later paste restores it instead
+                if paste != state.paste_count {
+                    return Ok(());
+                }
+                if let (Some(clipboard), Some(previous)) =
+                    (&mut state.clipboard, state.clipboard_backup.take())
+                {
+                    if let Err(e) = clipboard.set_text(previous) {
+                        tracing::warn!("Failed to restore the clipboard: {}", e);
+                    }
+                }
+            }
             KeyboardOutputMsg::Enable(enable) => {
                 state.enabled = enable;
+                if !enable {
+                    cancel_queue(state);
+                }
                 let status = if enable {
                     "Keyboard output enabled"
                 } else {
@@ -129,6 +422,7 @@ impl Actor for KeyboardOutputActor {
             }
             KeyboardOutputMsg::Shutdown => {
                 tracing::info!("KeyboardOutputActor shutting down");
+                cancel_queue(state);
             }
         }
         Ok(())
@@ -143,3 +437,644 @@ impl Actor for KeyboardOutputActor {
         Ok(())
     }
 }
+
+fn should_paste(method: OutputMethod, text: &str) -> bool {
+    match method {
+        OutputMethod::Type => false,
+        OutputMethod::Paste => true,
+        // Typing non-ASCII text depends on the layout and input method
+        OutputMethod::Auto => text.chars().count() >= AUTO_PASTE_MIN_CHARS || !text.is_ascii(),
+    }
+}
+
+fn queue_item(
+    myself: &ActorRef<KeyboardOutputMsg>,
+    state: &mut KeyboardOutputState,
+    mut item: TypingItem,
+) {
+    item.id = state.next_item;
+    state.next_item += 1;
+
+    if !state.enabled {
+        tracing::info!("Keyboard output is disabled, dropping item {}", item.id);
+        send_status(
+            state,
+            "Keyboard output is disabled (enable in settings)".to_string(),
+        );
+        // Let the coordinator leave the typing state
+        state
+            .coordinator
+            .send_message(CoordinatorMsg::TypingFinished {
+                item: item.id,
+                completed: false,
+            })
+            .ok();
+        return;
+    }
+
+    // Pasting nothing would still replace the clipboard
+    item.paste = !item.text.is_empty() && should_paste(state.config.output_method, &item.text);
+    state.queue.push_back(item);
+
+    // Small delay to let user prepare, unless already typing
+    if !state.scheduled {
+        let delay = Duration::from_millis(state.config.keyboard_output_delay_ms as u64);
+        schedule_next(myself, state, delay);
+    }
+}
+
+// Type the stable part of a partial result, correcting what earlier partials
+// typed if the recognizer changed its mind
+fn type_partial(state: &mut KeyboardOutputState, text: &str) {
+    // Earlier text or a correction is still being typed, later partials or
+    // the final result catch up
+    if !state.queue.is_empty() || state.pending.is_some() {
+        return;
+    }
+    let live = state.live.get_or_insert_with(LiveText::default);
+    if live.status != LiveStatus::Following {
+        return;
+    }
+
+    let target = stable_prefix(text);
+    let keep = if text.starts_with(live.typed.as_str()) {
+        // Nothing to correct, even if the last word isn't stable yet
+        live.typed.len()
+    } else {
+        common_prefix_len(&live.typed, target)
+    };
+
+    let erase = &live.typed[keep..];
+    let max_rewrite = state.config.live_typing_max_rewrite_chars as usize;
+    if erase.chars().count() > max_rewrite {
+        tracing::debug!(
+            "Partial result rewrites more than {} characters, waiting for the final result",
+            max_rewrite
+        );
+        live.status = LiveStatus::Paused;
+        return;
+    }
+
+    let append = target.get(keep..).unwrap_or_default().to_string();
+    if !erase.is_empty() {
+        let erase = erase.to_string();
+        start_step(
+            state,
+            Step::LiveErase { keep, append },
+            Injection::Erase(erase),
+        );
+    } else if !append.is_empty() {
+        start_step(
+            state,
+            Step::LiveType(append.clone()),
+            Injection::Type(append),
+        );
+    }
+}
+
+// Queue the final result of an utterance, replacing what its partials typed
+fn live_final(myself: &ActorRef<KeyboardOutputMsg>, state: &mut KeyboardOutputState, text: String) {
+    let live = state.live.take().unwrap_or_default();
+    if live.status == LiveStatus::Cancelled {
+        tracing::info!("Typing was cancelled, not typing: {}", text);
+        push_history(&mut state.history, live.typed);
+        let id = state.next_item;
+        state.next_item += 1;
+        state
+            .coordinator
+            .send_message(CoordinatorMsg::TypingFinished {
+                item: id,
+                completed: false,
+            })
+            .ok();
+        return;
+    }
+
+    // Only the part that differs from the partials is retyped
+    tracing::info!("Queueing text: {}", text);
+    let keep = common_prefix_len(&live.typed, &text);
+    let item = TypingItem {
+        erase: Erase::Live(live.typed[keep..].to_string()),
+        kept: live.typed[..keep].to_string(),
+        ..TypingItem::new(text[keep..].to_string())
+    };
+    queue_item(myself, state, item);
+}
+
+// Everything before the last word, which may still change as more audio
+// comes in
+fn stable_prefix(text: &str) -> &str {
+    let text = text.trim_end();
+    match text.char_indices().rev().find(|(_, ch)| ch.is_whitespace()) {
+        Some((index, ch)) => &text[..index + ch.len_utf8()],
+        None => "",
+    }
+}
+
+// Length in bytes of the longest common prefix
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(a.len().min(b.len()), |((index, _), _)| index)
+}
+
+// The first `chars` characters and the rest
+fn split_at_char(text: &str, chars: usize) -> (&str, &str) {
+    let end = text
+        .char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(index, _)| index);
+    text.split_at(end)
+}
+
+fn start_step(state: &mut KeyboardOutputState, step: Step, injection: Injection) {
+    let job = InjectionJob {
+        generation: state.generation,
+        injection,
+    };
+    if state.injection.jobs.send(job).is_err() {
+        // The thread only exits with the actor, or if a backend panicked
+        tracing::error!("The injection thread is gone, nothing can be typed");
+        return;
+    }
+    state.pending = Some(step);
+}
+
+// Hand the next step of the item at the front of the queue to the injection
+// thread: deleting text, then pressing keys, then pasting or typing a chunk
+fn next_step(myself: &ActorRef<KeyboardOutputMsg>, state: &mut KeyboardOutputState) {
+    // Its outcome moves things along
+    if state.pending.is_some() {
+        return;
+    }
+    let Some(item) = state.queue.front_mut() else {
+        return;
+    };
+
+    let erase = match std::mem::take(&mut item.erase) {
+        Erase::Nothing => None,
+        Erase::Live(text) => Some((text, false)),
+        Erase::LastTyped => {
+            let last = state.history.pop_back();
+            if last.is_none() {
+                send_status(state, "Nothing to scratch".to_string());
+            }
+            last.map(|text| (text, true))
+        }
+    };
+    if let Some((text, scratch)) = erase.filter(|(text, _)| !text.is_empty()) {
+        // The history entry goes back if it isn't erased
+        let scratched = scratch.then(|| text.clone());
+        start_step(state, Step::Erase { scratched }, Injection::Erase(text));
+        return;
+    }
+
+    let Some(item) = state.queue.front_mut() else {
+        return;
+    };
+    let keys = std::mem::take(&mut item.keys);
+    if !keys.is_empty() {
+        // Enter leaves a line break that backspace removes. Other keys do
+        // things we can't take back by deleting, like Ctrl+Z or switching
+        // windows.
+        let line_breaks = keys
+            .iter()
+            .all(|keys| keys.key == KEY_ENTER && keys.modifiers == Modifiers::default())
+            .then_some(keys.len());
+        if line_breaks.is_none() {
+            state.history.clear();
+        }
+        start_step(
+            state,
+            Step::Keys { line_breaks },
+            Injection::PressKeys(keys),
+        );
+        return;
+    }
+
+    if item.text.is_empty() {
+        finish_item(state, true);
+        schedule_more(myself, state);
+        return;
+    }
+
+    // Pasting is a single step
+    if item.paste {
+        let text = item.text.clone();
+        match paste_text(state, &text) {
+            Ok(keys) => {
+                start_step(state, Step::Paste, Injection::PressKeys(vec![keys]));
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("{}, typing instead", e);
+                send_status(state, format!("{}, typing instead", e));
+                if let Some(item) = state.queue.front_mut() {
+                    item.paste = false;
+                }
+            }
+        }
+    }
+
+    let chunk_chars = match state.config.typing_chunk_chars {
+        0 => usize::MAX,
+        chars => chars as usize,
+    };
+    let Some(item) = state.queue.front() else {
+        return;
+    };
+    let (chunk, _) = split_at_char(&item.text[item.position..], chunk_chars);
+    let step = Step::Chunk {
+        chars: chunk.chars().count(),
+    };
+    let chunk = chunk.to_string();
+    start_step(state, step, Injection::Type(chunk));
+}
+
+// Apply what the injection thread did and move on
+fn step_done(
+    myself: &ActorRef<KeyboardOutputMsg>,
+    state: &mut KeyboardOutputState,
+    step: Step,
+    outcome: InjectionOutcome,
+) {
+    // How far the step got, if it stopped early
+    let (stopped_at, error) = match outcome {
+        InjectionOutcome::Done => (None, None),
+        InjectionOutcome::Cancelled { done } => (Some(done), None),
+        InjectionOutcome::Failed { done, error } => (Some(done), Some(error)),
+    };
+
+    match step {
+        Step::LiveErase { keep, append } => {
+            let Some(live) = &mut state.live else {
+                return;
+            };
+            if let Some(e) = error {
+                tracing::warn!("Failed to correct live text: {}", e);
+                live.status = LiveStatus::Paused;
+            }
+            if stopped_at.is_none() {
+                live.typed.truncate(keep);
+                // Unless the final result or a cancel came in meanwhile
+                if !append.is_empty()
+                    && live.status == LiveStatus::Following
+                    && live.final_text.is_none()
+                {
+                    start_step(
+                        state,
+                        Step::LiveType(append.clone()),
+                        Injection::Type(append),
+                    );
+                    return;
+                }
+            }
+        }
+        Step::LiveType(append) => {
+            let Some(live) = &mut state.live else {
+                return;
+            };
+            let (typed, _) = split_at_char(&append, stopped_at.unwrap_or(usize::MAX));
+            live.typed.push_str(typed);
+            if let Some(e) = error {
+                tracing::warn!("Failed to type live text: {}", e);
+                live.status = LiveStatus::Paused;
+            }
+        }
+        Step::Erase { scratched } => {
+            // Still on screen, so "scratch that" can have another go
+            if let Some(text) = scratched.filter(|_| stopped_at.is_some()) {
+                push_history(&mut state.history, text);
+            }
+            if let Some(e) = error {
+                fail_item(state, e);
+            }
+        }
+        Step::Keys { line_breaks } => {
+            // Including those pressed before a cancel or failure
+            if let Some(count) = line_breaks {
+                push_history(&mut state.history, "\n".repeat(stopped_at.unwrap_or(count)));
+            }
+            if let Some(e) = error {
+                fail_item(state, e);
+            }
+        }
+        Step::Paste => {
+            restore_clipboard_later(myself, state);
+            if let Some(e) = error {
+                tracing::warn!("Failed to paste text: {}, typing instead", e);
+                send_status(
+                    state,
+                    format!("Failed to paste text: {}, typing instead", e),
+                );
+                if let Some(item) = state.queue.front_mut() {
+                    item.paste = false;
+                }
+            } else if stopped_at.is_none() {
+                if let Some(item) = state.queue.front_mut() {
+                    item.position = item.text.len();
+                    item.typed = item.total;
+                    let status = format!("Pasted text: {}", item.text);
+                    send_status(state, status);
+                }
+                finish_item(state, true);
+            }
+        }
+        Step::Chunk { chars } => {
+            if let Some(item) = state.queue.front_mut() {
+                let (typed, _) =
+                    split_at_char(&item.text[item.position..], stopped_at.unwrap_or(chars));
+                item.typed += typed.chars().count();
+                item.position += typed.len();
+                state
+                    .coordinator
+                    .send_message(CoordinatorMsg::TypingProgress {
+                        item: item.id,
+                        typed: item.typed,
+                        total: item.total,
+                    })
+                    .ok();
+            }
+            if let Some(e) = error {
+                tracing::error!("Failed to type text: {}", e);
+                send_status(state, format!("Failed to type text: {}", e));
+                finish_item(state, false);
+            } else if let Some(item) = state
+                .queue
+                .front()
+                .filter(|item| item.position == item.text.len())
+            {
+                let status = format!("Typed text: {}", item.text);
+                send_status(state, status);
+                finish_item(state, true);
+            }
+        }
+    }
+
+    // A cancel left the item in place until its step was done
+    if state.queue.front().is_some_and(|item| item.cancelled) {
+        finish_item(state, false);
+    }
+
+    // The final result waited for the correction to be typed
+    if let Some(text) = state.live.as_mut().and_then(|live| live.final_text.take()) {
+        live_final(myself, state, text);
+    }
+    schedule_more(myself, state);
+}
+
+fn fail_item(state: &mut KeyboardOutputState, e: InjectionError) {
+    tracing::error!("Keyboard output failed: {}", e);
+    send_status(state, format!("Keyboard output failed: {}", e));
+    finish_item(state, false);
+}
+
+// Pause between steps, so a cancel gets in
+fn schedule_more(myself: &ActorRef<KeyboardOutputMsg>, state: &mut KeyboardOutputState) {
+    if !state.queue.is_empty() && !state.scheduled {
+        let delay = Duration::from_millis(state.config.typing_chunk_delay_ms as u64);
+        schedule_next(myself, state, delay);
+    }
+}
+
+fn schedule_next(
+    myself: &ActorRef<KeyboardOutputMsg>,
+    state: &mut KeyboardOutputState,
+    delay: Duration,
+) {
+    let generation = state.generation;
+    state.scheduled = true;
+    if delay.is_zero() {
+        myself
+            .send_message(KeyboardOutputMsg::TypeNext { generation })
+            .ok();
+    } else {
+        myself.send_after(delay, move || KeyboardOutputMsg::TypeNext { generation });
+    }
+}
+
+// Take the front item off the queue and tell the coordinator it's done
+fn finish_item(state: &mut KeyboardOutputState, completed: bool) {
+    if let Some(item) = state.queue.pop_front() {
+        end_item(state, item, completed);
+    }
+}
+
+fn end_item(state: &mut KeyboardOutputState, item: TypingItem, completed: bool) {
+    if item.cancelled {
+        tracing::info!(
+            "Cancelled typing after {}/{} characters: {}",
+            item.typed,
+            item.total,
+            item.text
+        );
+    }
+    record_typed(&mut state.history, &item);
+    state
+        .coordinator
+        .send_message(CoordinatorMsg::TypingFinished {
+            item: item.id,
+            completed,
+        })
+        .ok();
+}
+
+// Drop everything queued, returning how many items that was. The item the
+// injection thread is busy with goes once it stops.
+fn cancel_queue(state: &mut KeyboardOutputState) -> usize {
+    state.generation += 1;
+    state
+        .injection
+        .generation
+        .store(state.generation, Ordering::Release);
+    state.scheduled = false;
+
+    if let Some(live) = &mut state.live {
+        live.status = LiveStatus::Cancelled;
+    }
+
+    let busy = matches!(
+        state.pending,
+        Some(Step::Erase { .. } | Step::Keys { .. } | Step::Paste | Step::Chunk { .. })
+    );
+    let cancelled = state.queue.iter().filter(|item| !item.cancelled).count();
+    for item in &mut state.queue {
+        item.cancelled = true;
+    }
+    let dropped: Vec<TypingItem> = state.queue.drain(usize::from(busy)..).collect();
+    for item in dropped {
+        end_item(state, item, false);
+    }
+    cancelled
+}
+
+// Remember what an item left on screen, for "scratch that"
+fn record_typed(history: &mut VecDeque<String>, item: &TypingItem) {
+    push_history(
+        history,
+        format!("{}{}", item.kept, &item.text[..item.position]),
+    );
+}
+
+fn push_history(history: &mut VecDeque<String>, text: String) {
+    if text.is_empty() {
+        return;
+    }
+    if history.len() == MAX_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(text);
+}
+
+fn send_status(state: &KeyboardOutputState, status: String) {
+    state
+        .coordinator
+        .send_message(CoordinatorMsg::UpdateStatus(status))
+        .ok();
+}
+
+// Put the text on the clipboard, returning the keys that paste it. The
+// previous text is restored once the application had time to read ours.
+fn paste_text(state: &mut KeyboardOutputState, text: &str) -> Result<Hotkey, KeyboardOutputError> {
+    let keys = paste_keys(state.config.paste_shortcut)?;
+    let Some(clipboard) = &mut state.clipboard else {
+        return Err(KeyboardOutputError::PasteError(
+            "clipboard unavailable".to_string(),
+        ));
+    };
+
+    // Only text can be restored, anything else (e.g. an image) is lost.
+    // Back-to-back pastes keep what was there before the first one.
+    if state.config.restore_clipboard && state.clipboard_backup.is_none() {
+        state.clipboard_backup = clipboard.get_text().ok();
+    }
+
+    clipboard
+        .set_text(text)
+        .map_err(|e| KeyboardOutputError::PasteError(e.to_string()))?;
+    Ok(keys)
+}
+
+// Once the paste keys were pressed, or failed to be
+fn restore_clipboard_later(myself: &ActorRef<KeyboardOutputMsg>, state: &mut KeyboardOutputState) {
+    state.paste_count += 1;
+    if state.clipboard_backup.is_some() {
+        let paste = state.paste_count;
+        myself.send_after(CLIPBOARD_RESTORE_DELAY, move || {
+            KeyboardOutputMsg::RestoreClipboard { paste }
+        });
+    }
+}
+
+fn paste_keys(shortcut: PasteShortcut) -> Result<Hotkey, KeyboardOutputError> {
+    let spec = match shortcut {
+        PasteShortcut::CtrlV => "Ctrl+V",
+        PasteShortcut::CtrlShiftV => "Ctrl+Shift+V",
+        PasteShortcut::ShiftInsert => "Shift+Insert",
+    };
+    spec.parse::<Hotkey>()
+        .map_err(|e| KeyboardOutputError::PasteError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Records what it typed, and cancels after `cancel_after` calls
+    struct Recorder<'a> {
+        typed: Vec<String>,
+        generation: &'a AtomicU64,
+        cancel_after: usize,
+    }
+
+    impl TextInjector for Recorder<'_> {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn type_text(&mut self, text: &str) -> Result<(), InjectionError> {
+            self.typed.push(text.to_string());
+            if self.typed.len() == self.cancel_after {
+                self.generation.fetch_add(1, Ordering::AcqRel);
+            }
+            Ok(())
+        }
+
+        fn press_keys(&mut self, keys: &Hotkey) -> Result<(), InjectionError> {
+            self.type_text(&keys.to_string())
+        }
+    }
+
+    fn job(injection: Injection) -> InjectionJob {
+        InjectionJob {
+            generation: 0,
+            injection,
+        }
+    }
+
+    #[test]
+    fn typing_is_split_into_pieces() {
+        let generation = AtomicU64::new(0);
+        let mut recorder = Recorder {
+            typed: Vec::new(),
+            generation: &generation,
+            cancel_after: usize::MAX,
+        };
+        let text = "é".repeat(CANCEL_CHECK_CHARS + 3);
+        let outcome = inject(
+            &mut recorder,
+            &job(Injection::Type(text.clone())),
+            &generation,
+        );
+        assert!(matches!(outcome, InjectionOutcome::Done));
+        assert_eq!(recorder.typed.concat(), text);
+        assert_eq!(recorder.typed.len(), 2);
+    }
+
+    #[test]
+    fn a_cancel_stops_typing_between_pieces() {
+        let generation = AtomicU64::new(0);
+        let mut recorder = Recorder {
+            typed: Vec::new(),
+            generation: &generation,
+            cancel_after: 1,
+        };
+        let text = "a".repeat(CANCEL_CHECK_CHARS * 3);
+        let outcome = inject(&mut recorder, &job(Injection::Type(text)), &generation);
+        assert!(matches!(
+            outcome,
+            InjectionOutcome::Cancelled { done } if done == CANCEL_CHECK_CHARS
+        ));
+        assert_eq!(recorder.typed.len(), 1);
+    }
+
+    #[test]
+    fn a_cancel_stops_key_presses() {
+        let generation = AtomicU64::new(0);
+        let mut recorder = Recorder {
+            typed: Vec::new(),
+            generation: &generation,
+            cancel_after: 2,
+        };
+        let keys = vec!["Enter".parse::<Hotkey>().unwrap(); 4];
+        let outcome = inject(&mut recorder, &job(Injection::PressKeys(keys)), &generation);
+        assert!(matches!(outcome, InjectionOutcome::Cancelled { done: 2 }));
+
+        // Jobs from before the cancel don't start
+        let outcome = inject(
+            &mut recorder,
+            &job(Injection::Erase("abc".to_string())),
+            &generation,
+        );
+        assert!(matches!(outcome, InjectionOutcome::Cancelled { done: 0 }));
+        assert_eq!(recorder.typed.len(), 2);
+    }
+
+    #[test]
+    fn splitting_counts_characters() {
+        assert_eq!(split_at_char("héllo", 2), ("hé", "llo"));
+        assert_eq!(split_at_char("hé", 5), ("hé", ""));
+        assert_eq!(split_at_char("", 0), ("", ""));
+    }
+}
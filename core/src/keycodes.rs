@@ -0,0 +1,179 @@
+// Linux input event codes (linux/input-event-codes.h) for the keys hotkeys
+// can name and the injection backends can press. X11 keycodes are these
+// offset by 8.
+
+// Keys by name: (canonical name, alias, code, XKB keysym). Keys that type a
+// character have no keysym here, theirs comes from the character.
+pub(crate) const NAMED_KEYS: &[(&str, &str, u16, Option<&str>)] = &[
+    ("Esc", "escape", 1, Some("Escape")),
+    ("Minus", "-", 12, None),
+    ("Equal", "=", 13, None),
+    ("Backspace", "backspace", 14, Some("BackSpace")),
+    ("Tab", "tab", 15, Some("Tab")),
+    ("Enter", "return", 28, Some("Return")),
+    ("LeftCtrl", "lctrl", 29, Some("Control_L")),
+    ("Semicolon", ";", 39, None),
+    ("Apostrophe", "'", 40, None),
+    ("Grave", "`", 41, None),
+    ("LeftShift", "lshift", 42, Some("Shift_L")),
+    ("Backslash", "\\", 43, None),
+    ("Comma", ",", 51, None),
+    ("Dot", ".", 52, None),
+    ("Slash", "/", 53, None),
+    ("RightShift", "rshift", 54, Some("Shift_R")),
+    ("LeftAlt", "lalt", 56, Some("Alt_L")),
+    ("Space", "space", 57, None),
+    ("CapsLock", "caps", 58, Some("Caps_Lock")),
+    ("NumLock", "numlock", 69, Some("Num_Lock")),
+    ("ScrollLock", "scroll", 70, Some("Scroll_Lock")),
+    ("RightCtrl", "rctrl", 97, Some("Control_R")),
+    ("Print", "printscreen", 99, Some("Print")),
+    ("RightAlt", "altgr", 100, Some("Alt_R")),
+    ("Home", "home", 102, Some("Home")),
+    ("Up", "up", 103, Some("Up")),
+    ("PageUp", "pgup", 104, Some("Prior")),
+    ("Left", "left", 105, Some("Left")),
+    ("Right", "right", 106, Some("Right")),
+    ("End", "end", 107, Some("End")),
+    ("Down", "down", 108, Some("Down")),
+    ("PageDown", "pgdn", 109, Some("Next")),
+    ("Insert", "ins", 110, Some("Insert")),
+    ("Delete", "del", 111, Some("Delete")),
+    ("Pause", "break", 119, Some("Pause")),
+    ("LeftMeta", "lsuper", 125, Some("Super_L")),
+    ("RightMeta", "rsuper", 126, Some("Super_R")),
+    ("Menu", "compose", 127, Some("Menu")),
+];
+
+// US QWERTY layout: input event code, unshifted and shifted character.
+// Backends that send key codes rely on the user's layout matching it.
+const US_LAYOUT: &[(u16, char, char)] = &[
+    (2, '1', '!'),
+    (3, '2', '@'),
+    (4, '3', '#'),
+    (5, '4', '$'),
+    (6, '5', '%'),
+    (7, '6', '^'),
+    (8, '7', '&'),
+    (9, '8', '*'),
+    (10, '9', '('),
+    (11, '0', ')'),
+    (12, '-', '_'),
+    (13, '=', '+'),
+    (15, '\t', '\t'),
+    (16, 'q', 'Q'),
+    (17, 'w', 'W'),
+    (18, 'e', 'E'),
+    (19, 'r', 'R'),
+    (20, 't', 'T'),
+    (21, 'y', 'Y'),
+    (22, 'u', 'U'),
+    (23, 'i', 'I'),
+    (24, 'o', 'O'),
+    (25, 'p', 'P'),
+    (26, '[', '{'),
+    (27, ']', '}'),
+    (28, '\n', '\n'),
+    (30, 'a', 'A'),
+    (31, 's', 'S'),
+    (32, 'd', 'D'),
+    (33, 'f', 'F'),
+    (34, 'g', 'G'),
+    (35, 'h', 'H'),
+    (36, 'j', 'J'),
+    (37, 'k', 'K'),
+    (38, 'l', 'L'),
+    (39, ';', ':'),
+    (40, '\'', '"'),
+    (41, '`', '~'),
+    (43, '\\', '|'),
+    (44, 'z', 'Z'),
+    (45, 'x', 'X'),
+    (46, 'c', 'C'),
+    (47, 'v', 'V'),
+    (48, 'b', 'B'),
+    (49, 'n', 'N'),
+    (50, 'm', 'M'),
+    (51, ',', '<'),
+    (52, '.', '>'),
+    (53, '/', '?'),
+    (57, ' ', ' '),
+];
+
+// The key and whether Shift is needed to type a character
+pub(crate) fn us_key_for(c: char) -> Option<(u16, bool)> {
+    US_LAYOUT
+        .iter()
+        .find_map(|&(code, plain, _)| (plain == c).then_some((code, false)))
+        .or_else(|| {
+            US_LAYOUT
+                .iter()
+                .find_map(|&(code, _, shifted)| (shifted == c).then_some((code, true)))
+        })
+}
+
+// The unshifted character a key types
+pub(crate) fn us_char(code: u16) -> Option<char> {
+    US_LAYOUT
+        .iter()
+        .find(|(key, _, _)| *key == code)
+        .map(|&(_, plain, _)| plain)
+}
+
+// Function key numbers and codes: F1-F10 are 59-68, F11-F12 are 87-88 and
+// F13-F24 are 183-194
+const FUNCTION_KEYS: &[(u16, u16, u16)] = &[(1, 10, 59), (11, 12, 87), (13, 24, 183)];
+
+pub(crate) fn function_key_code(number: u16) -> Option<u16> {
+    FUNCTION_KEYS
+        .iter()
+        .find(|(first, last, _)| (*first..=*last).contains(&number))
+        .map(|(first, _, code)| code + number - first)
+}
+
+pub(crate) fn function_key_number(code: u16) -> Option<u16> {
+    FUNCTION_KEYS.iter().find_map(|&(first, last, first_code)| {
+        let number = code.checked_sub(first_code)? + first;
+        (number <= last).then_some(number)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_keys_map_both_ways() {
+        for (number, code) in [(1, 59), (10, 68), (11, 87), (12, 88), (13, 183), (24, 194)] {
+            assert_eq!(function_key_code(number), Some(code));
+            assert_eq!(function_key_number(code), Some(number));
+        }
+        assert_eq!(function_key_code(0), None);
+        assert_eq!(function_key_code(25), None);
+        assert_eq!(function_key_number(58), None);
+        assert_eq!(function_key_number(69), None);
+        assert_eq!(function_key_number(195), None);
+    }
+
+    #[test]
+    fn us_layout_lookups() {
+        assert_eq!(us_key_for('z'), Some((44, false)));
+        assert_eq!(us_key_for('Z'), Some((44, true)));
+        assert_eq!(us_key_for('?'), Some((53, true)));
+        assert_eq!(us_key_for('é'), None);
+        assert_eq!(us_char(47), Some('v'));
+        assert_eq!(us_char(1), None);
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        for (index, (name, _, code, _)) in NAMED_KEYS.iter().enumerate() {
+            assert!(
+                NAMED_KEYS[index + 1..].iter().all(|key| key.2 != *code),
+                "{} shares code {}",
+                name,
+                code
+            );
+        }
+    }
+}
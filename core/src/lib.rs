@@ -3,15 +3,29 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 pub mod audio_capture;
+pub mod audio_devices;
 pub mod audio_processor;
 pub mod command;
 pub mod config;
 pub mod coordinator;
+pub mod denoise;
+pub mod formatting;
+pub mod hotkey;
+pub mod injection;
 pub mod keyboard_output;
+mod keycodes;
+pub mod resampler;
+pub mod segmenter;
 pub mod transcriber;
 pub mod types;
+pub mod vad;
+pub mod wakeword;
 
-pub use config::{load_config, save_config, Settings, VadMode};
+pub use audio_devices::{list_hosts, list_input_devices, InputDeviceInfo};
+pub use config::{
+    load_config, save_config, ActivationMode, EditAction, HotkeyBackend, HotkeyMode,
+    InjectionBackend, OutputMethod, PasteShortcut, Settings, TranscriptionBackend, VadMode,
+};
 pub use coordinator::Coordinator;
 pub use types::{AppOutput, AudioCaptureMsg, AudioChunk, CoordinatorMsg};
 
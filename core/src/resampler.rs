@@ -0,0 +1,144 @@
+use rubato::{FftFixedIn, Resampler};
+use thiserror::Error;
+
+use crate::types::AudioChunk;
+
+#[derive(Error, Debug)]
+pub enum ResampleError {
+    #[error("Failed to create resampler: {0}")]
+    InitError(String),
+    #[error("Failed to resample audio: {0}")]
+    ProcessError(String),
+    #[error("Invalid audio format: {0}")]
+    FormatError(String),
+}
+
+/// Converts captured audio (any rate, any channel count, interleaved) into
+/// mono audio at the pipeline's sample rate.
+///
+/// The resampler works on fixed-size blocks, so input that doesn't fill a
+/// whole block is kept until the next chunk arrives.
+pub struct AudioConverter {
+    target_rate: u32,
+    input_rate: u32,
+    input_channels: u16,
+    resampler: Option<FftFixedIn<f32>>,
+    // Mono samples waiting for a full resampler block
+    pending: Vec<f32>,
+}
+
+impl AudioConverter {
+    pub fn new(target_rate: u32) -> Self {
+        Self {
+            target_rate,
+            input_rate: target_rate,
+            input_channels: 1,
+            resampler: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Convert a chunk to mono at the target rate. The returned chunk may be
+    /// empty if not enough input has been buffered yet.
+    pub fn process(&mut self, chunk: AudioChunk) -> Result<AudioChunk, ResampleError> {
+        if chunk.channels == 0 || chunk.sample_rate == 0 {
+            return Err(ResampleError::FormatError(format!(
+                "{} channels at {} Hz",
+                chunk.channels, chunk.sample_rate
+            )));
+        }
+
+        // Rebuild the resampler if the input format changed (e.g. a different device)
+        if chunk.sample_rate != self.input_rate || chunk.channels != self.input_channels {
+            self.configure(chunk.sample_rate, chunk.channels)?;
+        }
+
+        let mono = downmix(&chunk.samples, chunk.channels);
+
+        let Some(resampler) = &mut self.resampler else {
+            // Already at the target rate, nothing to resample
+            return Ok(AudioChunk::mono(mono, self.target_rate));
+        };
+
+        self.pending.extend_from_slice(&mono);
+        let output = resample_blocks(resampler, &mut self.pending)?;
+
+        Ok(AudioChunk::mono(output, self.target_rate))
+    }
+
+    /// Drop any buffered input and reset the resampler's internal state.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        if let Some(resampler) = &mut self.resampler {
+            resampler.reset();
+        }
+    }
+
+    fn configure(&mut self, input_rate: u32, input_channels: u16) -> Result<(), ResampleError> {
+        tracing::info!(
+            "Configuring audio converter: {} Hz, {} channel(s) -> {} Hz mono",
+            input_rate,
+            input_channels,
+            self.target_rate
+        );
+
+        self.resampler = if input_rate == self.target_rate {
+            None
+        } else {
+            // Process in 10 ms blocks to keep latency low
+            let block_size = (input_rate as usize / 100).max(1);
+            let resampler = FftFixedIn::<f32>::new(
+                input_rate as usize,
+                self.target_rate as usize,
+                block_size,
+                1,
+                1,
+            )
+            .map_err(|e| ResampleError::InitError(e.to_string()))?;
+            Some(resampler)
+        };
+
+        self.input_rate = input_rate;
+        self.input_channels = input_channels;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Average interleaved channels down to a single mono channel.
+pub fn downmix(samples: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+
+    let channels = channels as usize;
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Feed every complete block in `buffer` through a mono resampler, leaving any
+/// remainder in the buffer for the next call.
+pub(crate) fn resample_blocks(
+    resampler: &mut FftFixedIn<f32>,
+    buffer: &mut Vec<f32>,
+) -> Result<Vec<f32>, ResampleError> {
+    let mut output = Vec::new();
+    loop {
+        let needed = resampler.input_frames_next();
+        if buffer.len() < needed {
+            break;
+        }
+
+        let block: Vec<f32> = buffer.drain(..needed).collect();
+        let resampled = resampler
+            .process(&[block], None)
+            .map_err(|e| ResampleError::ProcessError(e.to_string()))?;
+
+        if let Some(channel) = resampled.into_iter().next() {
+            output.extend(channel);
+        }
+    }
+    Ok(output)
+}
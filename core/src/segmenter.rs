@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use crate::types::Utterance;
+use crate::vad::{VadEvent, VadFrame};
+
+/// Output of [`UtteranceSegmenter`].
+#[derive(Debug, Clone)]
+pub enum SegmentEvent {
+    /// A new utterance began at the given stream offset.
+    Started { start: Duration },
+    /// Speech audio belonging to the current utterance, for recognizers that
+    /// decode while the user is still talking.
+    Audio(Vec<f32>),
+    /// The current utterance is complete.
+    Ended(Utterance),
+}
+
+/// Groups VAD frames into utterances.
+///
+/// Non-speech frames are dropped, so only speech (plus the VAD's pre-roll and
+/// hangover) is passed on. Utterances longer than `max_utterance_ms` are split
+/// so batch recognizers never get more audio than they can handle.
+pub struct UtteranceSegmenter {
+    sample_rate: u32,
+    max_samples: usize,
+    // Samples seen since the stream started, used for utterance timestamps
+    position: u64,
+    current: Option<(u64, Vec<f32>)>,
+}
+
+impl UtteranceSegmenter {
+    pub fn new(sample_rate: u32, max_utterance_ms: u32) -> Self {
+        Self {
+            sample_rate,
+            max_samples: (sample_rate as u64 * max_utterance_ms as u64 / 1000).max(1) as usize,
+            position: 0,
+            current: None,
+        }
+    }
+
+    pub fn in_utterance(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Feed one classified frame, returning any events it produced.
+    pub fn push(&mut self, frame: VadFrame) -> Vec<SegmentEvent> {
+        let mut events = Vec::new();
+        let frame_len = frame.samples.len() as u64;
+
+        match frame.event {
+            Some(VadEvent::SpeechStart { pre_roll }) => {
+                // The pre-roll precedes this frame in the stream
+                let start = self.position.saturating_sub(pre_roll.len() as u64);
+                self.current = Some((start, Vec::new()));
+                self.append(pre_roll, &mut events);
+            }
+            Some(VadEvent::SpeechEnd) => {
+                events.extend(self.finish());
+            }
+            None => {}
+        }
+
+        if frame.in_speech {
+            self.append(frame.samples, &mut events);
+        }
+        self.position += frame_len;
+
+        // Split overly long utterances, continuing with a fresh one
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|(_, samples)| samples.len() >= self.max_samples)
+        {
+            events.extend(self.finish());
+            self.current = Some((self.position, Vec::new()));
+        }
+
+        events
+    }
+
+    /// End the current utterance, if any (e.g. because capture stopped).
+    pub fn finish(&mut self) -> Option<SegmentEvent> {
+        let (start, samples) = self.current.take()?;
+        if samples.is_empty() {
+            return None;
+        }
+
+        let end = start + samples.len() as u64;
+        Some(SegmentEvent::Ended(Utterance {
+            start: offset(self.sample_rate, start),
+            end: offset(self.sample_rate, end),
+            samples,
+        }))
+    }
+
+    /// Forget the current utterance and restart the stream clock.
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.position = 0;
+    }
+
+    // Add speech to the current utterance, announcing it with the first samples
+    fn append(&mut self, samples: Vec<f32>, events: &mut Vec<SegmentEvent>) {
+        if samples.is_empty() {
+            return;
+        }
+        let Some((start, buffer)) = &mut self.current else {
+            return;
+        };
+
+        if buffer.is_empty() {
+            events.push(SegmentEvent::Started {
+                start: offset(self.sample_rate, *start),
+            });
+        }
+        buffer.extend_from_slice(&samples);
+        events.push(SegmentEvent::Audio(samples));
+    }
+}
+
+// Convert a sample position into a time offset
+fn offset(sample_rate: u32, samples: u64) -> Duration {
+    Duration::from_micros(samples * 1_000_000 / sample_rate as u64)
+}
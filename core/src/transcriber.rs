@@ -1,15 +1,20 @@
 use ractor::{Actor, ActorProcessingErr, ActorRef};
-use std::io::{BufRead, BufReader, Write};
+use std::env;
+use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+use crate::config::{Settings, TranscriptionBackend};
 use crate::types::{
-    AudioChunk, CoordinatorMsg, FinalTranscription, IpcAudioChunk, IpcTranscriptionResult,
+    AudioChunk, CoordinatorMsg, FinalTranscription, IpcAudioChunk, IpcRequest, IpcResponse,
     TranscriberMsg,
 };
+use whisperkey_protocol::{IpcHello, IpcReader, IpcWriter, ProtocolError, PROTOCOL_VERSION};
 
 #[derive(Error, Debug)]
 pub enum TranscriberError {
@@ -21,22 +26,46 @@ pub enum TranscriberError {
     ProcessExitedError,
     #[error("No model path provided for transcriber")]
     NoModelPathError,
+    #[error("Transcriber uses protocol version {found}, expected {expected}")]
+    IncompatibleVersion { expected: u8, found: u8 },
+    #[error("Transcriber handshake failed: {0}")]
+    HandshakeError(String),
+    #[error("Transcriber didn't report ready within {0:?}")]
+    HandshakeTimeout(Duration),
+}
+
+// Environment variable that overrides the transcriber executable
+const TRANSCRIBER_PATH_ENV: &str = "WHISPERKEY_TRANSCRIBER";
+
+// Backoff between restarts of a crashed transcriber
+const RESTART_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(10);
+
+// A process that stayed up this long gets a fresh restart budget
+const STABLE_RUN_TIME: Duration = Duration::from_secs(60);
+
+// Work items for the stdin thread
+enum StdinCommand {
+    Audio(AudioChunk),
+    Finalize,
 }
 
 pub struct TranscriberActor {
     // Empty struct as all state is in TranscriberState
 }
 
-pub struct TranscriberState {
-    // The subprocess
-    process: Option<Child>,
-
-    // Communications thread handles
-    stdin_thread: Option<JoinHandle<()>>,
-    stdout_thread: Option<JoinHandle<()>>,
+// A running transcriber process and the threads talking to it
+struct TranscriberProcess {
+    child: Child,
+    _stdin_thread: JoinHandle<()>,
+    _stdout_thread: JoinHandle<()>,
+    chunk_sender: Arc<Mutex<Sender<StdinCommand>>>,
+    started_at: Instant,
+}
 
-    // Channel for sending audio chunks to the stdin thread
-    chunk_sender: Option<Arc<Mutex<std::sync::mpsc::Sender<AudioChunk>>>>,
+pub struct TranscriberState {
+    // The subprocess, None while restarting or after giving up
+    process: Option<TranscriberProcess>,
 
     // For sending transcription results back to coordinator
     coordinator: ActorRef<CoordinatorMsg>,
@@ -44,223 +73,88 @@ pub struct TranscriberState {
     // Whether we're shutting down
     is_shutting_down: bool,
 
+    // What the current process reported in its handshake, once it's ready
+    capabilities: Option<IpcHello>,
+
+    // Supervision: messages from earlier processes carry an older generation
+    // and are ignored
+    generation: u64,
+    restart_attempts: u32, // Consecutive restarts without a stable run
+    total_restarts: u32,
+    dropped_chunks: u64, // Audio dropped since the transcriber was last ready
+
     // Configuration
     sample_rate: u32,
     model_path: Option<PathBuf>,
+    config: Arc<Settings>,
 }
 
 #[ractor::async_trait]
 impl Actor for TranscriberActor {
     type Msg = TranscriberMsg;
     type State = TranscriberState;
-    type Arguments = (ActorRef<CoordinatorMsg>, u32, Option<PathBuf>); // Coordinator ref, sample rate, and model path
+    type Arguments = (
+        ActorRef<CoordinatorMsg>,
+        u32,
+        Option<PathBuf>,
+        Arc<Settings>,
+    ); // Coordinator ref, sample rate, model path and backend settings
 
     async fn pre_start(
         &self,
         myself: ActorRef<Self::Msg>,
-        (coordinator, sample_rate, model_path): Self::Arguments,
+        (coordinator, sample_rate, model_path, config): Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
         tracing::info!(
-            "TranscriberActor starting with sample rate: {}",
-            sample_rate
+            "TranscriberActor starting with sample rate: {}, backend: {:?}",
+            sample_rate,
+            config.transcription_backend
         );
 
         if let Some(path) = &model_path {
-            tracing::info!("Using Vosk model at: {:?}", path);
+            tracing::info!("Using model at: {:?}", path);
+        } else if config.transcription_backend == TranscriptionBackend::Whisper {
+            tracing::info!(
+                "Using Whisper model '{}' from the models directory",
+                config.whisper_model
+            );
         } else {
             tracing::warn!("No model path provided, transcriber may fail to start!");
             // We'll continue and let the transcriber process handle the error
         }
 
-        // Create a channel for sending audio chunks to the stdin thread
-        let (chunk_sender, chunk_receiver) = std::sync::mpsc::channel::<AudioChunk>();
-        let chunk_sender = Arc::new(Mutex::new(chunk_sender));
-
-        // Start the transcriber process with proper arguments
-        let mut command = Command::new("cargo");
-        command.args(["run", "--package", "transcriber"]);
-
-        // Add model path if specified
-        if let Some(path) = &model_path {
-            command.args(["--", "--model-path", path.to_str().unwrap_or("")]);
-        }
-
-        // Add sample rate
-        command.args(["--sample-rate", &sample_rate.to_string()]);
-
-        // Configure stdio
-        command
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::inherit()); // Forward stderr to parent for easy debugging
-
-        tracing::debug!("Executing command: {:?}", command);
-
-        let mut process = command.spawn().map_err(|e| {
-            ActorProcessingErr::from(TranscriberError::ProcessStartError(e.to_string()))
-        })?;
-
-        // Get handles to stdin/stdout
-        let stdin = process.stdin.take().ok_or_else(|| {
-            ActorProcessingErr::from(TranscriberError::ProcessStartError(
-                "Failed to open stdin".to_string(),
-            ))
-        })?;
-
-        let stdout = process.stdout.take().ok_or_else(|| {
-            ActorProcessingErr::from(TranscriberError::ProcessStartError(
-                "Failed to open stdout".to_string(),
-            ))
-        })?;
-
-        let coordinator_clone = coordinator.clone();
-        let sample_rate_copy = sample_rate;
-
-        // Start thread for sending audio chunks to transcriber's stdin
-        let stdin_thread = thread::spawn(move || {
-            let mut stdin = stdin;
-            let mut last_error = None;
-
-            // Process audio chunks from the channel
-            for chunk in chunk_receiver {
-                // Convert to IPC message
-                let ipc_chunk = IpcAudioChunk {
-                    samples: chunk.0,
-                    sample_rate: sample_rate_copy,
-                };
-
-                // Serialize to JSON
-                match serde_json::to_string(&ipc_chunk) {
-                    Ok(json) => {
-                        // Send to transcriber's stdin
-                        if let Err(e) = writeln!(stdin, "{}", json) {
-                            tracing::error!("Failed to write to transcriber stdin: {}", e);
-                            last_error = Some(e);
-                            break;
-                        }
-
-                        // Flush to ensure it gets processed
-                        if let Err(e) = stdin.flush() {
-                            tracing::error!("Failed to flush transcriber stdin: {}", e);
-                            last_error = Some(e);
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to serialize audio chunk: {}", e);
-                        last_error = Some(e.into());
-                    }
-                }
-            }
-
-            if let Some(e) = last_error {
-                tracing::error!("Stdin thread exiting due to error: {}", e);
-                // Send error back to coordinator
-                let _ = coordinator_clone.send_message(CoordinatorMsg::UpdateStatus(format!(
-                    "Transcriber communication error: {}",
-                    e
-                )));
-            } else {
-                tracing::info!("Stdin thread exiting normally");
-            }
-        });
-
-        // Start thread for reading transcription results from stdout
-        let coordinator_for_stdout = coordinator.clone();
-        let stdout_thread = thread::spawn(move || {
-            let stdout_reader = BufReader::new(stdout);
-
-            // Read lines from transcriber's stdout
-            for line in stdout_reader.lines() {
-                match line {
-                    Ok(line) => {
-                        // Try to deserialize as a transcription result
-                        match serde_json::from_str::<IpcTranscriptionResult>(&line) {
-                            Ok(result) => {
-                                if result.is_final {
-                                    tracing::info!("Received transcription: {}", result.text);
-
-                                    // Forward to coordinator
-                                    let _ = coordinator_for_stdout.send_message(
-                                        CoordinatorMsg::TranscriptionResult(FinalTranscription(
-                                            result.text.clone(),
-                                        )),
-                                    );
-
-                                    // Also send status update
-                                    let confidence_str = result
-                                        .confidence
-                                        .map(|c| format!(" (confidence: {:.1}%)", c * 100.0))
-                                        .unwrap_or_default();
-
-                                    let _ = coordinator_for_stdout.send_message(
-                                        CoordinatorMsg::UpdateStatus(format!(
-                                            "Transcribed: {}{}",
-                                            result.text, confidence_str
-                                        )),
-                                    );
-                                } else {
-                                    // Partial result - just update status
-                                    let _ = coordinator_for_stdout.send_message(
-                                        CoordinatorMsg::UpdateStatus(format!(
-                                            "Partial: {}",
-                                            result.text
-                                        )),
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                tracing::error!(
-                                    "Failed to deserialize transcription result: {}",
-                                    e
-                                );
-                                tracing::error!("Raw line: {}", line);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to read from transcriber stdout: {}", e);
-
-                        // Send error back to coordinator
-                        let _ = coordinator_for_stdout.send_message(CoordinatorMsg::UpdateStatus(
-                            format!("Transcriber stdout read error: {}", e),
-                        ));
-
-                        break;
-                    }
-                }
-            }
-
-            tracing::info!("Stdout thread exiting");
-
-            // Tell coordinator the transcriber has stopped
-            let _ = coordinator_for_stdout.send_message(CoordinatorMsg::UpdateStatus(
-                "Transcriber process stopped".to_string(),
-            ));
-        });
-
-        tracing::info!("TranscriberActor started, process and threads running");
-
-        // Tell coordinator the transcriber is ready
-        coordinator.send_message(CoordinatorMsg::UpdateStatus(
-            "Transcriber ready".to_string(),
-        ))?;
-
-        Ok(TranscriberState {
-            process: Some(process),
-            stdin_thread: Some(stdin_thread),
-            stdout_thread: Some(stdout_thread),
-            chunk_sender: Some(chunk_sender),
+        let mut state = TranscriberState {
+            process: None,
             coordinator,
             is_shutting_down: false,
+            capabilities: None,
+            generation: 0,
+            restart_attempts: 0,
+            total_restarts: 0,
+            dropped_chunks: 0,
             sample_rate,
             model_path,
-        })
+            config,
+        };
+
+        // Failing to launch at all (e.g. no executable) isn't worth retrying
+        self.start_process(&myself, &mut state)?;
+
+        tracing::info!("TranscriberActor started, waiting for the transcriber to load its model");
+
+        // The transcriber is only ready once it sends its hello
+        state
+            .coordinator
+            .send_message(CoordinatorMsg::UpdateStatus(
+                "Loading transcription model...".to_string(),
+            ))?;
+
+        Ok(state)
     }
 
     async fn handle(
         &self,
-        _myself: ActorRef<Self::Msg>,
+        myself: ActorRef<Self::Msg>,
         message: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
@@ -271,55 +165,121 @@ impl Actor for TranscriberActor {
                     return Ok(());
                 }
 
-                // Check if we have a valid sender
-                if let Some(sender) = &state.chunk_sender {
-                    // Try to send the chunk to the stdin thread
-                    match sender.lock() {
-                        Ok(sender) => {
-                            if let Err(e) = sender.send(chunk) {
-                                tracing::error!(
-                                    "Failed to send audio chunk to stdin thread: {}",
-                                    e
-                                );
-                                state
-                                    .coordinator
-                                    .send_message(CoordinatorMsg::UpdateStatus(format!(
-                                        "Transcriber error: {}",
-                                        e
-                                    )))?;
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Failed to lock chunk sender: {}", e);
-                            state
-                                .coordinator
-                                .send_message(CoordinatorMsg::UpdateStatus(format!(
-                                    "Transcriber error: Failed to lock chunk sender: {}",
-                                    e
-                                )))?;
-                        }
+                self.send_to_stdin(state, StdinCommand::Audio(chunk))?;
+            }
+            TranscriberMsg::FinalizeUtterance => {
+                if state.is_shutting_down {
+                    return Ok(());
+                }
+
+                self.send_to_stdin(state, StdinCommand::Finalize)?;
+            }
+            TranscriberMsg::Ready { generation, hello } => {
+                if generation != state.generation {
+                    return Ok(());
+                }
+
+                if hello.protocol_version != PROTOCOL_VERSION {
+                    let error = TranscriberError::IncompatibleVersion {
+                        expected: PROTOCOL_VERSION,
+                        found: hello.protocol_version,
+                    };
+                    self.fail_startup(&myself, state, error)?;
+                    return Ok(());
+                }
+
+                tracing::info!("Transcriber ready: {:?}", hello);
+                if state.dropped_chunks > 0 {
+                    tracing::warn!(
+                        "Dropped {} audio chunks while the transcriber was unavailable",
+                        state.dropped_chunks
+                    );
+                    state.dropped_chunks = 0;
+                }
+
+                let model = hello
+                    .model
+                    .as_deref()
+                    .map(|model| format!(", model {}", model))
+                    .unwrap_or_default();
+                state
+                    .coordinator
+                    .send_message(CoordinatorMsg::UpdateStatus(format!(
+                        "Transcriber ready ({}{})",
+                        hello.backend, model
+                    )))?;
+                state
+                    .coordinator
+                    .send_message(CoordinatorMsg::TranscriberReady(hello.clone()))?;
+                state.capabilities = Some(hello);
+            }
+            TranscriberMsg::HandshakeFailed { generation, reason } => {
+                // The process speaks something we don't understand, restarting
+                // it won't help
+                if generation == state.generation && state.capabilities.is_none() {
+                    self.fail_startup(&myself, state, TranscriberError::HandshakeError(reason))?;
+                }
+            }
+            TranscriberMsg::HandshakeTimeout { generation } => {
+                if generation == state.generation
+                    && state.capabilities.is_none()
+                    && !state.is_shutting_down
+                {
+                    let error = TranscriberError::HandshakeTimeout(Duration::from_millis(
+                        state.config.transcriber_startup_timeout_ms as u64,
+                    ));
+                    tracing::error!("{}", error);
+
+                    // Killing it closes stdout, which reports the exit and
+                    // goes through the normal restart path
+                    if let Some(process) = &mut state.process {
+                        let _ = process.child.kill();
                     }
-                } else {
-                    tracing::error!("No chunk sender available");
+                }
+            }
+            TranscriberMsg::ProcessExited { generation } => {
+                if generation != state.generation || state.is_shutting_down {
+                    return Ok(());
+                }
+
+                // A process that stayed up for a while has proven itself, so
+                // this crash starts a fresh restart budget
+                if state
+                    .process
+                    .as_ref()
+                    .is_some_and(|process| process.started_at.elapsed() >= STABLE_RUN_TIME)
+                {
+                    state.restart_attempts = 0;
+                }
+
+                let exit_status = self.stop_process(state);
+                tracing::error!("Transcriber process exited: {}", exit_status);
+                self.schedule_restart(&myself, state, exit_status)?;
+            }
+            TranscriberMsg::Restart { generation } => {
+                if generation != state.generation || state.is_shutting_down {
+                    return Ok(());
+                }
+
+                tracing::info!(
+                    "Restarting transcriber (attempt {} of {})",
+                    state.restart_attempts,
+                    state.config.transcriber_max_restarts
+                );
+                if let Err(e) = self.start_process(&myself, state) {
+                    tracing::error!("{}", e);
+                    self.schedule_restart(&myself, state, e.to_string())?;
                 }
             }
             TranscriberMsg::Shutdown => {
                 tracing::info!("Shutting down transcriber...");
                 state.is_shutting_down = true;
 
-                // Drop the chunk sender to stop the stdin thread
-                state.chunk_sender = None;
-
-                // Try to kill the process
-                if let Some(mut process) = state.process.take() {
-                    match process.kill() {
-                        Ok(_) => tracing::info!("Killed transcriber process"),
-                        Err(e) => tracing::error!("Failed to kill transcriber process: {}", e),
-                    }
+                if state.process.is_some() {
+                    let exit_status = self.stop_process(state);
+                    tracing::info!("Stopped transcriber process: {}", exit_status);
                 }
 
-                // Don't wait for the threads - they'll exit when the process ends
-
                 state
                     .coordinator
                     .send_message(CoordinatorMsg::UpdateStatus(
@@ -339,22 +299,417 @@ impl Actor for TranscriberActor {
         // Make sure we shutdown properly
         state.is_shutting_down = true;
 
-        // Drop the chunk sender to stop the stdin thread
-        state.chunk_sender = None;
-
         // Kill the process if it's still running
-        if let Some(mut process) = state.process.take() {
-            match process.kill() {
-                Ok(_) => tracing::info!("Killed transcriber process during shutdown"),
-                Err(e) => {
-                    tracing::error!("Failed to kill transcriber process during shutdown: {}", e)
+        if state.process.is_some() {
+            let exit_status = self.stop_process(state);
+            tracing::info!(
+                "Stopped transcriber process during shutdown: {}",
+                exit_status
+            );
+        }
+
+        tracing::info!("TranscriberActor stopped");
+        Ok(())
+    }
+}
+
+impl TranscriberActor {
+    // Launch a new transcriber process under a fresh generation and start
+    // waiting for its hello
+    fn start_process(
+        &self,
+        myself: &ActorRef<TranscriberMsg>,
+        state: &mut TranscriberState,
+    ) -> Result<(), TranscriberError> {
+        state.generation += 1;
+        state.capabilities = None;
+
+        let process = spawn_transcriber(
+            myself,
+            &state.coordinator,
+            state.generation,
+            state.sample_rate,
+            state.model_path.as_ref(),
+            &state.config,
+        )?;
+        state.process = Some(process);
+
+        let generation = state.generation;
+        let startup_timeout =
+            Duration::from_millis(state.config.transcriber_startup_timeout_ms as u64);
+        myself.send_after(startup_timeout, move || TranscriberMsg::HandshakeTimeout {
+            generation,
+        });
+        Ok(())
+    }
+
+    // Stop the current process and its threads, returning how it exited
+    fn stop_process(&self, state: &mut TranscriberState) -> String {
+        state.capabilities = None;
+
+        let Some(mut process) = state.process.take() else {
+            return "not running".to_string();
+        };
+
+        // Dropping the chunk sender (with the rest of `process`) stops the
+        // stdin thread; the stdout thread exits once the process is gone
+        let status = match process.child.try_wait() {
+            Ok(Some(status)) => Ok(status),
+            Ok(None) => {
+                if let Err(e) = process.child.kill() {
+                    tracing::error!("Failed to kill transcriber process: {}", e);
                 }
+                process.child.wait()
             }
+            Err(e) => Err(e),
+        };
+
+        match status {
+            Ok(status) => status.to_string(),
+            Err(e) => format!("unknown ({})", e),
         }
+    }
 
-        // Don't wait for threads - they'll exit when the process ends
+    // Restart a crashed transcriber after a backoff, unless the restart budget
+    // is used up
+    fn schedule_restart(
+        &self,
+        myself: &ActorRef<TranscriberMsg>,
+        state: &mut TranscriberState,
+        last_exit: String,
+    ) -> Result<(), ActorProcessingErr> {
+        // Invalidate anything still in flight from the dead process
+        state.generation += 1;
+
+        let max_restarts = state.config.transcriber_max_restarts;
+        if state.restart_attempts >= max_restarts {
+            tracing::error!(
+                "Transcriber failed {} times in a row, giving up",
+                state.restart_attempts + 1
+            );
+            state
+                .coordinator
+                .send_message(CoordinatorMsg::TranscriberFailed {
+                    restarts: state.total_restarts,
+                    last_exit,
+                })?;
+            return Ok(());
+        }
 
-        tracing::info!("TranscriberActor stopped");
+        let delay = restart_delay(state.restart_attempts);
+        state.restart_attempts += 1;
+        state.total_restarts += 1;
+
+        state
+            .coordinator
+            .send_message(CoordinatorMsg::TranscriberRestarting {
+                attempt: state.restart_attempts,
+                max_attempts: max_restarts,
+                total_restarts: state.total_restarts,
+                last_exit,
+                delay,
+            })?;
+
+        let generation = state.generation;
+        myself.send_after(delay, move || TranscriberMsg::Restart { generation });
         Ok(())
     }
+
+    // The transcriber can never become usable: report why and stop the actor
+    fn fail_startup(
+        &self,
+        myself: &ActorRef<TranscriberMsg>,
+        state: &mut TranscriberState,
+        error: TranscriberError,
+    ) -> Result<(), ActorProcessingErr> {
+        tracing::error!("{}", error);
+        state.is_shutting_down = true;
+        state
+            .coordinator
+            .send_message(CoordinatorMsg::UpdateStatus(format!(
+                "Transcriber failed to start: {}",
+                error
+            )))?;
+
+        // post_stop kills the process
+        myself.stop(Some(error.to_string()));
+        Ok(())
+    }
+
+    // Hand a command to the stdin thread, reporting failures to the coordinator.
+    //
+    // Audio is only passed on while the transcriber is ready. While it's
+    // loading or restarting, audio (and utterance ends) are dropped rather than
+    // buffered: a restarted model starts from a clean slate, and replaying
+    // speech from before a crash would type text the user has moved on from.
+    fn send_to_stdin(
+        &self,
+        state: &mut TranscriberState,
+        command: StdinCommand,
+    ) -> Result<(), ActorProcessingErr> {
+        let process = match &state.process {
+            Some(process) if state.capabilities.is_some() => process,
+            _ => {
+                if state.dropped_chunks == 0 {
+                    tracing::warn!("Transcriber not ready, dropping audio until it is");
+                }
+                state.dropped_chunks += 1;
+                return Ok(());
+            }
+        };
+
+        // Try to send the command to the stdin thread
+        match process.chunk_sender.lock() {
+            Ok(sender) => {
+                if let Err(e) = sender.send(command) {
+                    tracing::error!("Failed to send to stdin thread: {}", e);
+                    state
+                        .coordinator
+                        .send_message(CoordinatorMsg::UpdateStatus(format!(
+                            "Transcriber error: {}",
+                            e
+                        )))?;
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to lock chunk sender: {}", e);
+                state
+                    .coordinator
+                    .send_message(CoordinatorMsg::UpdateStatus(format!(
+                        "Transcriber error: Failed to lock chunk sender: {}",
+                        e
+                    )))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Start the transcriber process and the threads that feed it audio and read
+// its results
+fn spawn_transcriber(
+    myself: &ActorRef<TranscriberMsg>,
+    coordinator: &ActorRef<CoordinatorMsg>,
+    generation: u64,
+    sample_rate: u32,
+    model_path: Option<&PathBuf>,
+    config: &Settings,
+) -> Result<TranscriberProcess, TranscriberError> {
+    // Create a channel for sending audio chunks to the stdin thread
+    let (chunk_sender, chunk_receiver) = std::sync::mpsc::channel::<StdinCommand>();
+    let chunk_sender = Arc::new(Mutex::new(chunk_sender));
+
+    // Start the transcriber process with proper arguments
+    let executable = find_transcriber(config)?;
+    tracing::info!("Using transcriber executable: {:?}", executable);
+    let mut command = Command::new(&executable);
+
+    // Add model path if specified
+    if let Some(path) = model_path {
+        command.arg("--model-path").arg(path);
+    }
+
+    // Add sample rate
+    command.args(["--sample-rate", &sample_rate.to_string()]);
+
+    // Wire format for stdin/stdout
+    command.args(["--ipc-format", config.ipc_format.as_arg()]);
+
+    // Select the recognition backend
+    command.args(["--backend", config.transcription_backend.as_arg()]);
+    match config.transcription_backend {
+        TranscriptionBackend::Whisper => {
+            command.args([
+                "--whisper-model",
+                &config.whisper_model,
+                "--threads",
+                &config.whisper_threads.to_string(),
+                "--language",
+                &config.language,
+            ]);
+        }
+        TranscriptionBackend::Stub => {
+            if let Some(script) = &config.stub_script {
+                command.arg("--script").arg(script);
+            }
+        }
+        TranscriptionBackend::Vosk => {}
+    }
+
+    // Configure stdio
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit()); // Forward stderr to parent for easy debugging
+
+    tracing::debug!("Executing command: {:?}", command);
+
+    let mut child = command.spawn().map_err(|e| {
+        TranscriberError::ProcessStartError(format!("{}: {}", executable.display(), e))
+    })?;
+
+    // Get handles to stdin/stdout
+    let stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| TranscriberError::ProcessStartError("Failed to open stdin".to_string()))?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| TranscriberError::ProcessStartError("Failed to open stdout".to_string()))?;
+
+    // Start thread for sending audio chunks to transcriber's stdin
+    let ipc_format = config.ipc_format;
+    let stdin_thread = thread::spawn(move || {
+        let mut writer = IpcWriter::new(BufWriter::new(stdin), ipc_format);
+
+        // Process audio chunks from the channel
+        for command in chunk_receiver {
+            // Convert to IPC message
+            let request = match command {
+                StdinCommand::Audio(chunk) => IpcRequest::Audio(IpcAudioChunk {
+                    samples: chunk.samples,
+                    sample_rate: chunk.sample_rate,
+                }),
+                StdinCommand::Finalize => IpcRequest::Finalize,
+            };
+
+            // Send to transcriber's stdin. A failure means the process died,
+            // which the stdout thread reports when it sees EOF.
+            if let Err(e) = writer.write_request(&request) {
+                tracing::error!("Failed to write to transcriber stdin: {}", e);
+                return;
+            }
+        }
+
+        tracing::info!("Stdin thread exiting normally");
+    });
+
+    // Start thread for reading transcription results from stdout
+    let coordinator_for_stdout = coordinator.clone();
+    let transcriber = myself.clone();
+    let stdout_thread = thread::spawn(move || {
+        let mut reader = IpcReader::new(BufReader::new(stdout), ipc_format);
+        let mut handshake_done = false;
+
+        // Read messages from transcriber's stdout
+        loop {
+            match reader.read_response() {
+                Ok(Some(IpcResponse::Hello(hello))) => {
+                    handshake_done = true;
+                    let _ = transcriber.send_message(TranscriberMsg::Ready { generation, hello });
+                }
+                Ok(Some(IpcResponse::Result(_))) if !handshake_done => {
+                    let _ = transcriber.send_message(TranscriberMsg::HandshakeFailed {
+                        generation,
+                        reason: "received a result before the hello".to_string(),
+                    });
+                    break;
+                }
+                Ok(Some(IpcResponse::Result(result))) => {
+                    if result.is_final {
+                        tracing::info!("Received transcription: {}", result.text);
+
+                        // Forward to coordinator
+                        let _ = coordinator_for_stdout.send_message(
+                            CoordinatorMsg::TranscriptionResult(FinalTranscription(
+                                result.text.clone(),
+                            )),
+                        );
+
+                        // Also send status update
+                        let confidence_str = result
+                            .confidence
+                            .map(|c| format!(" (confidence: {:.1}%)", c * 100.0))
+                            .unwrap_or_default();
+
+                        let _ = coordinator_for_stdout.send_message(CoordinatorMsg::UpdateStatus(
+                            format!("Transcribed: {}{}", result.text, confidence_str),
+                        ));
+                    } else {
+                        // Partial result, shown and maybe typed live
+                        let _ = coordinator_for_stdout
+                            .send_message(CoordinatorMsg::PartialTranscription(result.text));
+                    }
+                }
+                Ok(None) => break, // Transcriber closed stdout
+                Err(ProtocolError::UnsupportedVersion(found)) => {
+                    let error = TranscriberError::IncompatibleVersion {
+                        expected: PROTOCOL_VERSION,
+                        found,
+                    };
+                    let _ = transcriber.send_message(TranscriberMsg::HandshakeFailed {
+                        generation,
+                        reason: error.to_string(),
+                    });
+                    break;
+                }
+                Err(e) if e.is_recoverable() => {
+                    tracing::error!("Failed to deserialize transcription result: {}", e);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to read from transcriber stdout: {}", e);
+                    break;
+                }
+            }
+        }
+
+        tracing::info!("Stdout thread exiting");
+
+        // Whatever the reason, this process is no longer usable
+        let _ = transcriber.send_message(TranscriberMsg::ProcessExited { generation });
+    });
+
+    Ok(TranscriberProcess {
+        child,
+        _stdin_thread: stdin_thread,
+        _stdout_thread: stdout_thread,
+        chunk_sender,
+        started_at: Instant::now(),
+    })
+}
+
+fn restart_delay(attempt: u32) -> Duration {
+    RESTART_INITIAL_DELAY
+        .saturating_mul(1u32 << attempt.min(16))
+        .min(RESTART_MAX_DELAY)
+}
+
+// Locate the transcriber executable, trying in order: the path from the
+// config, the WHISPERKEY_TRANSCRIBER environment variable, the directory of
+// the running executable, and finally PATH
+fn find_transcriber(config: &Settings) -> Result<PathBuf, TranscriberError> {
+    let file_name = format!("transcriber{}", env::consts::EXE_SUFFIX);
+    let mut candidates = Vec::new();
+
+    if let Some(path) = &config.transcriber_path {
+        candidates.push(PathBuf::from(path));
+    }
+    if let Some(path) = env::var_os(TRANSCRIBER_PATH_ENV) {
+        candidates.push(PathBuf::from(path));
+    }
+    if let Some(dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+    {
+        candidates.push(dir.join(&file_name));
+    }
+    if let Some(paths) = env::var_os("PATH") {
+        candidates.extend(env::split_paths(&paths).map(|dir| dir.join(&file_name)));
+    }
+
+    if let Some(found) = candidates.iter().find(|path| path.is_file()) {
+        return Ok(found.clone());
+    }
+
+    let tried = candidates
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(TranscriberError::ProcessStartError(format!(
+        "transcriber executable not found (tried: {}). Set transcriber_path in the config or {}",
+        tried, TRANSCRIBER_PATH_ENV
+    )))
 }
@@ -1,31 +1,109 @@
-use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
 
-// Represents a chunk of raw audio data (e.g., f32 samples)
+use crate::hotkey::Hotkey;
+use crate::injection::InjectionError;
+
+// Represents a chunk of raw audio data (interleaved f32 samples)
 #[derive(Debug, Clone)] // Clone might be useful, Debug for logging
-pub struct AudioChunk(pub Vec<f32>);
+pub struct AudioChunk {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl AudioChunk {
+    pub fn new(samples: Vec<f32>, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            samples,
+            sample_rate,
+            channels,
+        }
+    }
+
+    pub fn mono(samples: Vec<f32>, sample_rate: u32) -> Self {
+        Self::new(samples, sample_rate, 1)
+    }
+
+    // Number of frames (samples per channel) in this chunk
+    pub fn frames(&self) -> usize {
+        if self.channels == 0 {
+            0
+        } else {
+            self.samples.len() / self.channels as usize
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
 
 // Commands for the AudioCaptureActor
 #[derive(Debug)]
 pub enum AudioCaptureMsg {
     Start,
     Stop,
+    StreamFailed { generation: u64, error: String }, // From the cpal error callback
+    HealthCheck { generation: u64 },                 // Periodic stall detection
+    Reconnect { generation: u64 },                   // Retry opening the input device
+}
+
+// A segment of speech found by the VAD, with offsets from the start of capture
+#[derive(Debug, Clone)]
+pub struct Utterance {
+    pub start: Duration,
+    pub end: Duration,
+    pub samples: Vec<f32>, // Mono samples at the pipeline sample rate
+}
+
+impl Utterance {
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
 }
 
 // Commands for the AudioProcessorActor
 #[derive(Debug)]
 pub enum AudioProcessorMsg {
     ProcessChunk(AudioChunk),
+    Flush,             // End any utterance in progress (e.g. capture stopped)
+    ArmWakeWord(bool), // Route speech to the wake word spotter instead of the transcriber
     Shutdown,
 }
 
 // Commands for the KeyboardOutputActor
 #[derive(Debug)]
 pub enum KeyboardOutputMsg {
-    TypeText(String),
+    TypeText(String),                // Queued behind anything still being typed
+    LivePartial(String),             // Partial result of the utterance in progress
+    LiveFinal(String),               // Final result, replacing whatever partials were typed
+    PressKeys(Vec<Hotkey>),          // Key combinations pressed one after the other, in queue order
+    ScratchThat,                     // Delete the text typed last, in queue order
+    Cancel,                          // Drop the text being typed and everything queued
+    TypeNext { generation: u64 },    // Type the next chunk, unless cancelled since it was scheduled
+    RestoreClipboard { paste: u64 }, // Put the clipboard back, unless something was pasted since
+    Injected(InjectionOutcome),      // From the injection thread, for the step it was given
     Enable(bool),
     Shutdown,
 }
 
+// How a step of keyboard output ended on the injection thread. `done` counts
+// the characters typed or key combinations pressed before it stopped.
+#[derive(Debug)]
+pub enum InjectionOutcome {
+    Done,
+    Cancelled { done: usize },
+    Failed { done: usize, error: InjectionError },
+}
+
+/// Which global hotkey was pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    Dictation, // Start/stop listening, per hotkey_mode
+    Abort,     // Cancel typing
+}
+
 // Messages related to the AppCoordinator
 #[derive(Debug)]
 pub enum CoordinatorMsg {
@@ -35,8 +113,122 @@ pub enum CoordinatorMsg {
     AudioChunk(AudioChunk), // Message for coordinator to handle chunks
     UpdateStatus(String),   // For internal status updates
     TranscriptionResult(FinalTranscription), // From transcriber
+    PartialTranscription(String), // Hypothesis so far for the utterance in progress
     SilenceDetected(bool),  // Silence state change from VAD
     ToggleKeyboardOutput(bool), // Enable/disable keyboard output
+    InputDeviceOpened(String), // Name of the device audio capture opened
+    AudioDeviceLost(String), // Capture stream died, reconnecting
+    UtteranceStarted(Duration), // VAD found the start of speech
+    UtteranceEnded(Utterance), // VAD found the end of speech
+    TranscriberReady(IpcHello), // Transcriber loaded its model
+    TranscriberRestarting {
+        attempt: u32, // Consecutive restart attempt, 1-based
+        max_attempts: u32,
+        total_restarts: u32,
+        last_exit: String, // Exit status of the process that died
+        delay: Duration,
+    },
+    TranscriberFailed {
+        restarts: u32,
+        last_exit: String,
+    }, // Restart budget used up
+    SilenceTimeout, // Quiet for auto_stop_silence_ms since the last speech
+    SessionTimeout {
+        session: u64,
+    }, // A session hit max_session_secs
+    WaitForWakeWord, // Start capture in WakeWord mode, unless the spotter failed to load
+    WakeWordDetected(String), // The spotter heard this wake phrase
+    WakeWordUnavailable(String), // The spotter failed to load
+    HotkeyPressed(HotkeyAction), // From the global hotkey listeners
+    HotkeyReleased(HotkeyAction),
+    CancelTyping, // From the UI or the abort hotkey
+    TypingProgress {
+        item: u64,
+        typed: usize, // Characters typed so far
+        total: usize,
+    },
+    TypingFinished {
+        item: u64,
+        completed: bool, // False if cancelled or typing failed
+    }, // Keyboard output is done with a TypeText
+    ProcessingTimeout {
+        utterance: u64,
+    }, // No transcription arrived for an utterance
+}
+
+/// What the coordinator is doing, as far as the user is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListeningState {
+    Idle,               // Capture stopped, nothing pending
+    Listening,          // Capturing audio
+    Processing,         // An utterance ended, waiting for its transcription
+    Typing,             // Keyboard output is typing a result
+    WaitingForWakeWord, // Capturing, but only the wake word spotter hears it
+}
+
+impl ListeningState {
+    /// Whether the coordinator may move from this state to `next`.
+    pub fn can_transition_to(self, next: ListeningState) -> bool {
+        use ListeningState::*;
+        matches!(
+            (self, next),
+            (Idle, Listening)
+                | (Idle, WaitingForWakeWord)
+                | (WaitingForWakeWord, Idle)
+                | (WaitingForWakeWord, Listening)
+                // A late result for the utterance before the session ended
+                | (WaitingForWakeWord, Typing)
+                | (Listening, WaitingForWakeWord)
+                | (Processing, WaitingForWakeWord)
+                | (Typing, WaitingForWakeWord)
+                // A result for the last utterance can arrive after capture stopped
+                | (Idle, Typing)
+                | (Listening, Idle)
+                | (Listening, Processing)
+                // Streaming backends finish utterances on their own
+                | (Listening, Typing)
+                | (Processing, Idle)
+                | (Processing, Listening)
+                | (Processing, Typing)
+                | (Typing, Idle)
+                | (Typing, Listening)
+                | (Typing, Processing)
+        )
+    }
+}
+
+impl fmt::Display for ListeningState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ListeningState::Idle => "Idle",
+            ListeningState::Listening => "Listening",
+            ListeningState::Processing => "Processing",
+            ListeningState::Typing => "Typing",
+            ListeningState::WaitingForWakeWord => "Waiting for wake word",
+        };
+        f.write_str(name)
+    }
+}
+
+// Core actors supervised by the coordinator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    AudioCapture,
+    AudioProcessor,
+    Transcriber,
+    KeyboardOutput,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Component::AudioCapture => "Audio capture",
+            Component::AudioProcessor => "Audio processor",
+            Component::Transcriber => "Transcriber",
+            Component::KeyboardOutput => "Keyboard output",
+        };
+        f.write_str(name)
+    }
 }
 
 // For UI updates
@@ -44,6 +236,11 @@ pub enum CoordinatorMsg {
 pub enum AppOutput {
     UpdateStatus(String),
     UpdateTranscription(String),
+    InputDeviceChanged(String),
+    TranscriberReady(IpcHello),
+    ComponentFailed { component: Component, error: String },
+    ComponentRecovered(Component),
+    StateChanged(ListeningState),
 }
 
 // Placeholder for transcription results, will be refined later
@@ -54,22 +251,18 @@ pub struct FinalTranscription(pub String);
 #[derive(Debug)]
 pub enum TranscriberMsg {
     ProcessAudioChunk(AudioChunk),
+    FinalizeUtterance, // End of speech, emit the final result now
     Shutdown,
+    // From the process's stdout thread and timers, tagged with the generation
+    // of the process they belong to
+    Ready { generation: u64, hello: IpcHello }, // Handshake received
+    HandshakeFailed { generation: u64, reason: String },
+    HandshakeTimeout { generation: u64 },
+    ProcessExited { generation: u64 }, // stdout closed, the process is gone
+    Restart { generation: u64 },       // Backoff elapsed, start a new process
 }
 
-// IPC messages (serialized to/from JSON)
-
-// Audio chunk sent to transcriber process via IPC
-#[derive(Debug, Serialize, Deserialize)]
-pub struct IpcAudioChunk {
-    pub samples: Vec<f32>,
-    pub sample_rate: u32,
-}
-
-// Transcription result received from transcriber process via IPC
-#[derive(Debug, Serialize, Deserialize)]
-pub struct IpcTranscriptionResult {
-    pub text: String,
-    pub is_final: bool,
-    pub confidence: Option<f32>,
-}
+// IPC messages, shared with the transcriber process
+pub use whisperkey_protocol::{
+    IpcAudioChunk, IpcFormat, IpcHello, IpcRequest, IpcResponse, IpcTranscriptionResult,
+};
@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+use crate::config::Settings;
+
+// RNNoise voice probability above which a frame counts as speech
+const RNNOISE_VOICE_THRESHOLD: f32 = 0.5;
+
+// webrtc_vad::Vad wraps a raw pointer to the C detector, so it isn't Send.
+// The detector has no thread affinity, it just must not be used from two
+// threads at once, which the owning actor already guarantees.
+struct SendVad(webrtc_vad::Vad);
+
+unsafe impl Send for SendVad {}
+
+enum Classifier {
+    WebRtc(SendVad),
+    // Used when the sample rate isn't one webrtc's VAD supports
+    Energy(f32),
+}
+
+/// Smoothed speech state changes reported by [`VoiceActivityDetector`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum VadEvent {
+    /// Speech started. `pre_roll` holds the audio that preceded the frame
+    /// that triggered the start, so the onset isn't clipped.
+    SpeechStart { pre_roll: Vec<f32> },
+    /// Speech ended after the end hangover expired.
+    SpeechEnd,
+}
+
+/// One classified VAD frame.
+#[derive(Debug, Clone)]
+pub struct VadFrame {
+    pub samples: Vec<f32>,
+    /// Raw classification of this frame.
+    pub is_voice: bool,
+    /// Smoothed state after applying the start/end hangover.
+    pub in_speech: bool,
+    pub event: Option<VadEvent>,
+}
+
+/// Frame-by-frame voice activity detection with hangover and pre-roll.
+///
+/// Every sample is classified: input is split into fixed 10/20/30 ms frames
+/// and anything left over is carried into the next call.
+pub struct VoiceActivityDetector {
+    classifier: Classifier,
+    frame_len: usize,
+    frame_ms: u32,
+    // Samples waiting for a full frame
+    pending: Vec<f32>,
+    // Consecutive frames needed to enter / leave speech
+    start_frames: usize,
+    end_frames: usize,
+    voiced_run: usize,
+    silent_run: usize,
+    in_speech: bool,
+    // Recent non-speech audio, prepended when speech starts
+    pre_roll: VecDeque<f32>,
+    pre_roll_len: usize,
+    // RNNoise voice probabilities, one per 10 ms, waiting for their frame
+    voice_probabilities: VecDeque<f32>,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(sample_rate: u32, config: &Settings) -> Self {
+        // webrtc's VAD only accepts 10, 20 or 30 ms frames
+        let frame_ms = match config.vad_frame_ms {
+            10 | 20 | 30 => config.vad_frame_ms,
+            other => {
+                tracing::warn!("Unsupported VAD frame length {} ms, using 30 ms", other);
+                30
+            }
+        };
+        let frame_len = (sample_rate * frame_ms / 1000) as usize;
+
+        let classifier = match webrtc_sample_rate(sample_rate) {
+            Some(rate) => Classifier::WebRtc(SendVad(webrtc_vad::Vad::new_with_rate_and_mode(
+                rate,
+                config.vad_mode.into(),
+            ))),
+            None => {
+                tracing::warn!(
+                    "webrtc VAD doesn't support {} Hz, falling back to energy detection",
+                    sample_rate
+                );
+                Classifier::Energy(config.vad_energy_threshold)
+            }
+        };
+
+        let frames_for = |ms: u32| ms.div_ceil(frame_ms).max(1) as usize;
+
+        Self {
+            classifier,
+            frame_len,
+            frame_ms,
+            pending: Vec::new(),
+            start_frames: frames_for(config.vad_speech_start_ms),
+            end_frames: frames_for(config.vad_speech_end_ms),
+            voiced_run: 0,
+            silent_run: 0,
+            in_speech: false,
+            pre_roll: VecDeque::new(),
+            pre_roll_len: (sample_rate * config.vad_pre_roll_ms / 1000) as usize,
+            voice_probabilities: VecDeque::new(),
+        }
+    }
+
+    pub fn frame_ms(&self) -> u32 {
+        self.frame_ms
+    }
+
+    pub fn in_speech(&self) -> bool {
+        self.in_speech
+    }
+
+    /// Classify mono samples at the pipeline's sample rate.
+    ///
+    /// `voice_probabilities` are the RNNoise voice probabilities for every
+    /// 10 ms of denoised audio, empty when denoising is off. Each frame is
+    /// matched with the ones covering it, give or take the denoiser's delay.
+    pub fn process(&mut self, samples: &[f32], voice_probabilities: &[f32]) -> Vec<VadFrame> {
+        self.pending.extend_from_slice(samples);
+        self.voice_probabilities.extend(voice_probabilities);
+
+        let mut frames = Vec::with_capacity(self.pending.len() / self.frame_len.max(1));
+        while self.frame_len > 0 && self.pending.len() >= self.frame_len {
+            let frame: Vec<f32> = self.pending.drain(..self.frame_len).collect();
+            let voice_probability = self.frame_voice_probability();
+            let is_voice = self.classify(&frame, voice_probability);
+            frames.push(self.update(frame, is_voice));
+        }
+        frames
+    }
+
+    /// Forget buffered audio and return to the non-speech state.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.pre_roll.clear();
+        self.voice_probabilities.clear();
+        self.voiced_run = 0;
+        self.silent_run = 0;
+        self.in_speech = false;
+    }
+
+    // The highest RNNoise probability among the 10 ms that make up the next
+    // frame, if there are any
+    fn frame_voice_probability(&mut self) -> Option<f32> {
+        let count = ((self.frame_ms / 10) as usize).min(self.voice_probabilities.len());
+        self.voice_probabilities.drain(..count).reduce(f32::max)
+    }
+
+    fn classify(&mut self, frame: &[f32], voice_probability: Option<f32>) -> bool {
+        match &mut self.classifier {
+            Classifier::WebRtc(vad) => {
+                let i16_frame: Vec<i16> = frame.iter().map(|&s| (s * 32767.0) as i16).collect();
+                match vad.0.is_voice_segment(&i16_frame) {
+                    // RNNoise tells speech apart from noise that webrtc
+                    // takes for voice, like typing or a fan
+                    Ok(has_voice) => {
+                        has_voice && voice_probability.unwrap_or(1.0) >= RNNOISE_VOICE_THRESHOLD
+                    }
+                    Err(_) => {
+                        tracing::error!("VAD error: invalid frame of {} samples", frame.len());
+                        false
+                    }
+                }
+            }
+            Classifier::Energy(threshold) => {
+                let energy =
+                    frame.iter().map(|&sample| sample * sample).sum::<f32>() / frame.len() as f32;
+
+                // RNNoise's own voice probability is a better signal than
+                // raw energy when denoising is on
+                energy > *threshold
+                    || voice_probability.is_some_and(|p| p >= RNNOISE_VOICE_THRESHOLD)
+            }
+        }
+    }
+
+    fn update(&mut self, samples: Vec<f32>, is_voice: bool) -> VadFrame {
+        let mut event = None;
+
+        if is_voice {
+            self.voiced_run += 1;
+            self.silent_run = 0;
+        } else {
+            self.silent_run += 1;
+            self.voiced_run = 0;
+        }
+
+        if !self.in_speech && self.voiced_run >= self.start_frames {
+            self.in_speech = true;
+            event = Some(VadEvent::SpeechStart {
+                pre_roll: self.pre_roll.drain(..).collect(),
+            });
+        } else if self.in_speech && self.silent_run >= self.end_frames {
+            self.in_speech = false;
+            event = Some(VadEvent::SpeechEnd);
+        }
+
+        // Keep the most recent non-speech audio around for the next start
+        if !self.in_speech {
+            self.pre_roll.extend(samples.iter().copied());
+            let excess = self.pre_roll.len().saturating_sub(self.pre_roll_len);
+            self.pre_roll.drain(..excess);
+        }
+
+        VadFrame {
+            samples,
+            is_voice,
+            in_speech: self.in_speech,
+            event,
+        }
+    }
+}
+
+fn webrtc_sample_rate(sample_rate: u32) -> Option<webrtc_vad::SampleRate> {
+    match sample_rate {
+        8000 => Some(webrtc_vad::SampleRate::Rate8kHz),
+        16000 => Some(webrtc_vad::SampleRate::Rate16kHz),
+        32000 => Some(webrtc_vad::SampleRate::Rate32kHz),
+        48000 => Some(webrtc_vad::SampleRate::Rate48kHz),
+        _ => None,
+    }
+}
@@ -0,0 +1,186 @@
+use thiserror::Error;
+#[cfg(feature = "wake-word")]
+use vosk::{CompleteResult, DecodingState, Model, Recognizer};
+
+use crate::config::Settings;
+
+// Vosk's catch-all for speech that isn't in the grammar
+#[cfg(feature = "wake-word")]
+const UNKNOWN_WORD: &str = "[unk]";
+
+#[derive(Error, Debug)]
+pub enum WakeWordError {
+    #[error("No wake phrases configured")]
+    NoPhrases,
+
+    #[error("No Vosk model for wake word detection (set wake_word_model_path or model_path)")]
+    NoModel,
+
+    #[error("Failed to load wake word model from {0}")]
+    ModelLoad(String),
+
+    #[error("Failed to create wake word recognizer: {0}")]
+    Recognizer(String),
+
+    #[error("Wake word detection isn't built in (build with the wake-word feature)")]
+    NotBuilt,
+}
+
+/// Keyword spotter for the configured wake phrases.
+///
+/// A Vosk recognizer restricted to a grammar of just the wake phrases, so
+/// it's cheap to run and can't produce anything else. Everything else the
+/// user says decodes as `[unk]`.
+#[cfg(feature = "wake-word")]
+pub struct WakeWordDetector {
+    // The recognizer holds its own reference to the model
+    _model: Model,
+    recognizer: Recognizer,
+    phrases: Vec<String>,
+    min_confidence: f32,
+}
+
+#[cfg(feature = "wake-word")]
+impl WakeWordDetector {
+    pub fn new(config: &Settings, sample_rate: u32) -> Result<Self, WakeWordError> {
+        let phrases: Vec<String> = config
+            .wake_phrases
+            .iter()
+            .map(|phrase| normalize(phrase))
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        if phrases.is_empty() {
+            return Err(WakeWordError::NoPhrases);
+        }
+
+        let model_path = config
+            .wake_word_model_path
+            .as_ref()
+            .or(config.model_path.as_ref())
+            .ok_or(WakeWordError::NoModel)?;
+        let model =
+            Model::new(model_path).ok_or_else(|| WakeWordError::ModelLoad(model_path.clone()))?;
+
+        let mut grammar: Vec<&str> = phrases.iter().map(String::as_str).collect();
+        grammar.push(UNKNOWN_WORD);
+        let mut recognizer = Recognizer::new_with_grammar(&model, sample_rate as f32, &grammar)
+            .ok_or_else(|| {
+                WakeWordError::Recognizer(format!(
+                    "invalid grammar or sample rate ({} Hz)",
+                    sample_rate
+                ))
+            })?;
+        // Word confidences are what sensitivity is applied to
+        recognizer.set_words(true);
+
+        tracing::info!(
+            "Wake word detector loaded from {} for {:?}",
+            model_path,
+            phrases
+        );
+
+        Ok(Self {
+            _model: model,
+            recognizer,
+            phrases,
+            // Higher sensitivity accepts less certain matches
+            min_confidence: 1.0 - config.wake_word_sensitivity.clamp(0.0, 1.0),
+        })
+    }
+
+    /// Feed speech audio, returning the wake phrase if Vosk decided the
+    /// phrase is complete.
+    pub fn process(&mut self, samples: &[f32]) -> Option<String> {
+        let i16_samples: Vec<i16> = samples.iter().map(|&s| (s * 32767.0) as i16).collect();
+
+        match self.recognizer.accept_waveform(&i16_samples) {
+            Ok(DecodingState::Finalized) => self.finish(),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Wake word recognizer rejected audio: {}", e);
+                None
+            }
+        }
+    }
+
+    /// End of speech, returning the wake phrase if one was said.
+    pub fn finish(&mut self) -> Option<String> {
+        let result = self.recognizer.final_result();
+        let CompleteResult::Single(result) = result else {
+            return None;
+        };
+
+        let text = normalize(result.text);
+        let phrase = self
+            .phrases
+            .iter()
+            .find(|phrase| text.contains(phrase.as_str()))?;
+
+        // Judge the match on the words of the phrase itself
+        let confidences: Vec<f32> = result
+            .result
+            .iter()
+            .filter(|word| word.word != UNKNOWN_WORD)
+            .map(|word| word.conf)
+            .collect();
+        let confidence = if confidences.is_empty() {
+            0.0
+        } else {
+            confidences.iter().sum::<f32>() / confidences.len() as f32
+        };
+
+        if confidence < self.min_confidence {
+            tracing::debug!(
+                "Ignoring wake phrase '{}' with confidence {:.2} (need {:.2})",
+                phrase,
+                confidence,
+                self.min_confidence
+            );
+            return None;
+        }
+
+        tracing::info!(
+            "Wake phrase '{}' detected (confidence {:.2})",
+            phrase,
+            confidence
+        );
+        Some(phrase.clone())
+    }
+
+    pub fn reset(&mut self) {
+        self.recognizer.reset();
+    }
+}
+
+/// Stand-in without the `wake-word` feature. It never loads, so WakeWord
+/// mode falls back to manual activation.
+#[cfg(not(feature = "wake-word"))]
+pub enum WakeWordDetector {}
+
+#[cfg(not(feature = "wake-word"))]
+impl WakeWordDetector {
+    pub fn new(_config: &Settings, _sample_rate: u32) -> Result<Self, WakeWordError> {
+        Err(WakeWordError::NotBuilt)
+    }
+
+    pub fn process(&mut self, _samples: &[f32]) -> Option<String> {
+        match *self {}
+    }
+
+    pub fn finish(&mut self) -> Option<String> {
+        match *self {}
+    }
+
+    pub fn reset(&mut self) {
+        match *self {}
+    }
+}
+
+// Vosk models use lowercase words separated by single spaces
+#[cfg(feature = "wake-word")]
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,698 @@
+//! Messages exchanged between the core and the transcriber process, and their
+//! wire formats.
+//!
+//! The default format is a length-prefixed binary framing carrying raw PCM.
+//! Every frame looks like this (all integers little-endian):
+//!
+//! ```text
+//! u32  frame length (header + payload, not counting this field)
+//! u8   protocol version
+//! u8   message type
+//! u8   sample format (audio only, 0 otherwise)
+//! u8   flags (results only, 0 otherwise)
+//! u32  sample rate (audio only, 0 otherwise)
+//! u32  sequence number
+//! ...  payload
+//! ```
+//!
+//! JSON Lines is kept as a debug format that's easy to read and to pipe into
+//! the transcriber by hand.
+//!
+//! The transcriber's first message is always a hello, sent once its model has
+//! loaded. The core treats the transcriber as ready only after receiving it.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Version of the binary framing. Bumped on any incompatible change.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+// Bytes in a frame header, not counting the length prefix
+const HEADER_LEN: usize = 12;
+
+// Refuse frames larger than this rather than allocate whatever a corrupted
+// length prefix asks for
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+// Message types
+const MSG_AUDIO: u8 = 1;
+const MSG_FINALIZE: u8 = 2;
+const MSG_RESULT: u8 = 16;
+const MSG_HELLO: u8 = 17;
+
+// Result flags
+const FLAG_FINAL: u8 = 1;
+const FLAG_HAS_CONFIDENCE: u8 = 2;
+
+#[derive(Error, Debug)]
+pub enum ProtocolError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid JSON message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported protocol version {0} (expected {PROTOCOL_VERSION})")]
+    UnsupportedVersion(u8),
+    #[error("Unknown message type {0}")]
+    UnknownMessageType(u8),
+    #[error("Unknown sample format {0}")]
+    UnknownSampleFormat(u8),
+    #[error("Malformed frame: {0}")]
+    Malformed(String),
+}
+
+impl ProtocolError {
+    /// Whether the stream can still be read after this error. A bad JSON line
+    /// only loses that line, while a bad binary frame loses the framing.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, ProtocolError::Json(_))
+    }
+}
+
+/// Wire format, chosen when the transcriber is started.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpcFormat {
+    #[default]
+    Binary,
+    Json, // JSON Lines, for debugging
+}
+
+impl IpcFormat {
+    // Value for the transcriber's --ipc-format flag
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            IpcFormat::Binary => "binary",
+            IpcFormat::Json => "json",
+        }
+    }
+}
+
+impl FromStr for IpcFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(IpcFormat::Binary),
+            "json" => Ok(IpcFormat::Json),
+            other => Err(format!("unknown IPC format '{}' (binary or json)", other)),
+        }
+    }
+}
+
+/// PCM encoding of audio in binary frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    I16,
+    F32,
+}
+
+impl SampleFormat {
+    fn code(self) -> u8 {
+        match self {
+            SampleFormat::I16 => 1,
+            SampleFormat::F32 => 2,
+        }
+    }
+
+    fn from_code(code: u8) -> Result<Self, ProtocolError> {
+        match code {
+            1 => Ok(SampleFormat::I16),
+            2 => Ok(SampleFormat::F32),
+            other => Err(ProtocolError::UnknownSampleFormat(other)),
+        }
+    }
+}
+
+// Requests sent to the transcriber process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcRequest {
+    Audio(IpcAudioChunk),
+    Finalize,
+}
+
+// Mono audio sent to the transcriber process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcAudioChunk {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+// Messages sent back by the transcriber process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcResponse {
+    Hello(IpcHello),
+    Result(IpcTranscriptionResult),
+}
+
+// Sent by the transcriber once its model has loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcHello {
+    pub protocol_version: u8,
+    pub backend: String,
+    pub model: Option<String>, // Model file or directory name
+    pub sample_rates: Vec<u32>,
+    pub languages: Vec<String>, // Empty when the backend can't tell
+    pub partial_results: bool,
+}
+
+// Transcription result received from the transcriber process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcTranscriptionResult {
+    pub text: String,
+    pub is_final: bool,
+    pub confidence: Option<f32>,
+}
+
+/// Writes messages in either wire format.
+pub struct IpcWriter<W: Write> {
+    inner: W,
+    format: IpcFormat,
+    sample_format: SampleFormat,
+    sequence: u32,
+}
+
+impl<W: Write> IpcWriter<W> {
+    pub fn new(inner: W, format: IpcFormat) -> Self {
+        Self {
+            inner,
+            format,
+            sample_format: SampleFormat::I16,
+            sequence: 0,
+        }
+    }
+
+    /// PCM encoding used for audio in binary frames (16-bit by default).
+    pub fn with_sample_format(mut self, sample_format: SampleFormat) -> Self {
+        self.sample_format = sample_format;
+        self
+    }
+
+    pub fn write_request(&mut self, request: &IpcRequest) -> Result<(), ProtocolError> {
+        if self.format == IpcFormat::Json {
+            return self.write_json(request);
+        }
+
+        match request {
+            IpcRequest::Audio(chunk) => {
+                let payload = encode_samples(&chunk.samples, self.sample_format);
+                let header = Header {
+                    message_type: MSG_AUDIO,
+                    sample_format: self.sample_format.code(),
+                    flags: 0,
+                    sample_rate: chunk.sample_rate,
+                };
+                self.write_frame(header, &payload)
+            }
+            IpcRequest::Finalize => self.write_frame(Header::new(MSG_FINALIZE), &[]),
+        }
+    }
+
+    pub fn write_response(&mut self, response: &IpcResponse) -> Result<(), ProtocolError> {
+        if self.format == IpcFormat::Json {
+            return self.write_json(response);
+        }
+
+        match response {
+            // Sent once, so the hello's payload is simply JSON
+            IpcResponse::Hello(hello) => {
+                let payload = serde_json::to_vec(hello)?;
+                self.write_frame(Header::new(MSG_HELLO), &payload)
+            }
+            IpcResponse::Result(result) => {
+                let mut flags = 0;
+                if result.is_final {
+                    flags |= FLAG_FINAL;
+                }
+                if result.confidence.is_some() {
+                    flags |= FLAG_HAS_CONFIDENCE;
+                }
+
+                let mut payload = Vec::with_capacity(4 + result.text.len());
+                payload.extend_from_slice(&result.confidence.unwrap_or(0.0).to_le_bytes());
+                payload.extend_from_slice(result.text.as_bytes());
+
+                let header = Header {
+                    flags,
+                    ..Header::new(MSG_RESULT)
+                };
+                self.write_frame(header, &payload)
+            }
+        }
+    }
+
+    fn write_json(&mut self, message: &impl Serialize) -> Result<(), ProtocolError> {
+        let json = serde_json::to_string(message)?;
+        writeln!(self.inner, "{}", json)?;
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    fn write_frame(&mut self, header: Header, payload: &[u8]) -> Result<(), ProtocolError> {
+        let frame_len = HEADER_LEN + payload.len();
+        if frame_len > MAX_FRAME_LEN {
+            return Err(ProtocolError::Malformed(format!(
+                "frame of {} bytes exceeds the {} byte limit",
+                frame_len, MAX_FRAME_LEN
+            )));
+        }
+
+        let mut frame = Vec::with_capacity(4 + frame_len);
+        frame.extend_from_slice(&(frame_len as u32).to_le_bytes());
+        frame.push(PROTOCOL_VERSION);
+        frame.push(header.message_type);
+        frame.push(header.sample_format);
+        frame.push(header.flags);
+        frame.extend_from_slice(&header.sample_rate.to_le_bytes());
+        frame.extend_from_slice(&self.sequence.to_le_bytes());
+        frame.extend_from_slice(payload);
+
+        self.sequence = self.sequence.wrapping_add(1);
+
+        self.inner.write_all(&frame)?;
+        self.inner.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads messages in either wire format.
+pub struct IpcReader<R: BufRead> {
+    inner: R,
+    format: IpcFormat,
+    // Sequence number expected in the next binary frame
+    next_sequence: Option<u32>,
+}
+
+impl<R: BufRead> IpcReader<R> {
+    pub fn new(inner: R, format: IpcFormat) -> Self {
+        Self {
+            inner,
+            format,
+            next_sequence: None,
+        }
+    }
+
+    /// Read the next request, or `None` at the end of the stream.
+    pub fn read_request(&mut self) -> Result<Option<IpcRequest>, ProtocolError> {
+        if self.format == IpcFormat::Json {
+            return self.read_json();
+        }
+
+        let Some((header, payload)) = self.read_frame()? else {
+            return Ok(None);
+        };
+
+        match header.message_type {
+            MSG_AUDIO => {
+                let sample_format = SampleFormat::from_code(header.sample_format)?;
+                Ok(Some(IpcRequest::Audio(IpcAudioChunk {
+                    samples: decode_samples(&payload, sample_format)?,
+                    sample_rate: header.sample_rate,
+                })))
+            }
+            MSG_FINALIZE => Ok(Some(IpcRequest::Finalize)),
+            other => Err(ProtocolError::UnknownMessageType(other)),
+        }
+    }
+
+    /// Read the next response, or `None` at the end of the stream.
+    pub fn read_response(&mut self) -> Result<Option<IpcResponse>, ProtocolError> {
+        if self.format == IpcFormat::Json {
+            return self.read_json();
+        }
+
+        let Some((header, payload)) = self.read_frame()? else {
+            return Ok(None);
+        };
+
+        match header.message_type {
+            MSG_HELLO => Ok(Some(IpcResponse::Hello(serde_json::from_slice(&payload)?))),
+            MSG_RESULT => {
+                if payload.len() < 4 {
+                    return Err(ProtocolError::Malformed(
+                        "result frame is missing its confidence".to_string(),
+                    ));
+                }
+                let confidence =
+                    f32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+                let text = String::from_utf8(payload[4..].to_vec())
+                    .map_err(|e| ProtocolError::Malformed(format!("result text: {}", e)))?;
+
+                Ok(Some(IpcResponse::Result(IpcTranscriptionResult {
+                    text,
+                    is_final: header.flags & FLAG_FINAL != 0,
+                    confidence: (header.flags & FLAG_HAS_CONFIDENCE != 0).then_some(confidence),
+                })))
+            }
+            other => Err(ProtocolError::UnknownMessageType(other)),
+        }
+    }
+
+    fn read_json<T: serde::de::DeserializeOwned>(&mut self) -> Result<Option<T>, ProtocolError> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.inner.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            // Skip blank lines, handy when typing requests by hand
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+        Ok(Some(serde_json::from_str(&line)?))
+    }
+
+    fn read_frame(&mut self) -> Result<Option<(Header, Vec<u8>)>, ProtocolError> {
+        // The stream may only end between frames
+        if self.inner.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let truncated = |e: io::Error| match e.kind() {
+            io::ErrorKind::UnexpectedEof => {
+                ProtocolError::Malformed("stream ended in the middle of a frame".to_string())
+            }
+            _ => e.into(),
+        };
+
+        let mut len_bytes = [0u8; 4];
+        self.inner.read_exact(&mut len_bytes).map_err(truncated)?;
+
+        let frame_len = u32::from_le_bytes(len_bytes) as usize;
+        if !(HEADER_LEN..=MAX_FRAME_LEN).contains(&frame_len) {
+            return Err(ProtocolError::Malformed(format!(
+                "invalid frame length {}",
+                frame_len
+            )));
+        }
+
+        let mut frame = vec![0u8; frame_len];
+        self.inner.read_exact(&mut frame).map_err(truncated)?;
+
+        let version = frame[0];
+        if version != PROTOCOL_VERSION {
+            return Err(ProtocolError::UnsupportedVersion(version));
+        }
+
+        let header = Header {
+            message_type: frame[1],
+            sample_format: frame[2],
+            flags: frame[3],
+            sample_rate: u32::from_le_bytes([frame[4], frame[5], frame[6], frame[7]]),
+        };
+        let sequence = u32::from_le_bytes([frame[8], frame[9], frame[10], frame[11]]);
+
+        // A gap means frames were lost or the peer restarted its counter
+        if let Some(expected) = self.next_sequence {
+            if sequence != expected {
+                tracing::warn!(
+                    "IPC sequence gap: expected frame {}, got {}",
+                    expected,
+                    sequence
+                );
+            }
+        }
+        self.next_sequence = Some(sequence.wrapping_add(1));
+
+        frame.drain(..HEADER_LEN);
+        Ok(Some((header, frame)))
+    }
+}
+
+struct Header {
+    message_type: u8,
+    sample_format: u8,
+    flags: u8,
+    sample_rate: u32,
+}
+
+impl Header {
+    fn new(message_type: u8) -> Self {
+        Self {
+            message_type,
+            sample_format: 0,
+            flags: 0,
+            sample_rate: 0,
+        }
+    }
+}
+
+fn encode_samples(samples: &[f32], format: SampleFormat) -> Vec<u8> {
+    match format {
+        SampleFormat::I16 => samples
+            .iter()
+            .flat_map(|&s| ((s.clamp(-1.0, 1.0) * 32767.0) as i16).to_le_bytes())
+            .collect(),
+        SampleFormat::F32 => samples.iter().flat_map(|&s| s.to_le_bytes()).collect(),
+    }
+}
+
+fn decode_samples(payload: &[u8], format: SampleFormat) -> Result<Vec<f32>, ProtocolError> {
+    let width = match format {
+        SampleFormat::I16 => 2,
+        SampleFormat::F32 => 4,
+    };
+    if payload.len() % width != 0 {
+        return Err(ProtocolError::Malformed(format!(
+            "audio payload of {} bytes isn't a whole number of {:?} samples",
+            payload.len(),
+            format
+        )));
+    }
+
+    Ok(match format {
+        SampleFormat::I16 => payload
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+            .collect(),
+        SampleFormat::F32 => payload
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames_of(write: impl FnOnce(&mut IpcWriter<&mut Vec<u8>>)) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write(&mut IpcWriter::new(&mut bytes, IpcFormat::Binary));
+        bytes
+    }
+
+    fn audio(samples: Vec<f32>) -> IpcRequest {
+        IpcRequest::Audio(IpcAudioChunk {
+            samples,
+            sample_rate: 16000,
+        })
+    }
+
+    fn read_samples(bytes: &[u8]) -> Vec<f32> {
+        let mut reader = IpcReader::new(bytes, IpcFormat::Binary);
+        match reader.read_request().unwrap() {
+            Some(IpcRequest::Audio(chunk)) => {
+                assert_eq!(chunk.sample_rate, 16000);
+                chunk.samples
+            }
+            other => panic!("expected audio, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn header_layout() {
+        let bytes = frames_of(|writer| {
+            writer.write_request(&IpcRequest::Finalize).unwrap();
+            writer.write_request(&IpcRequest::Finalize).unwrap();
+        });
+        let frame_len = (HEADER_LEN as u32).to_le_bytes();
+        let mut expected = Vec::new();
+        for sequence in [0u32, 1] {
+            expected.extend_from_slice(&frame_len);
+            expected.extend_from_slice(&[PROTOCOL_VERSION, MSG_FINALIZE, 0, 0, 0, 0, 0, 0]);
+            expected.extend_from_slice(&sequence.to_le_bytes());
+        }
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn i16_samples_round_trip() {
+        let samples = vec![0.0, 0.5, -0.5, 1.0, -1.0, 0.25];
+        let bytes = frames_of(|writer| writer.write_request(&audio(samples.clone())).unwrap());
+        assert_eq!(bytes.len(), 4 + HEADER_LEN + samples.len() * 2);
+        assert_eq!(bytes[6], SampleFormat::I16.code());
+
+        for (read, sent) in read_samples(&bytes).iter().zip(&samples) {
+            assert!((read - sent).abs() < 1.0 / 16384.0, "{} != {}", read, sent);
+        }
+    }
+
+    #[test]
+    fn i16_samples_are_clamped() {
+        let bytes = frames_of(|writer| writer.write_request(&audio(vec![2.0, -2.0])).unwrap());
+        let samples = read_samples(&bytes);
+        assert!(samples[0] <= 1.0 && samples[0] > 0.999);
+        assert!(samples[1] >= -1.0 && samples[1] < -0.999);
+    }
+
+    #[test]
+    fn f32_samples_round_trip() {
+        let samples = vec![0.0, 0.123456, -0.987654, 1.5];
+        let mut bytes = Vec::new();
+        IpcWriter::new(&mut bytes, IpcFormat::Binary)
+            .with_sample_format(SampleFormat::F32)
+            .write_request(&audio(samples.clone()))
+            .unwrap();
+        assert_eq!(bytes[6], SampleFormat::F32.code());
+        assert_eq!(read_samples(&bytes), samples);
+    }
+
+    #[test]
+    fn responses_round_trip() {
+        let hello = IpcHello {
+            protocol_version: PROTOCOL_VERSION,
+            backend: "stub".to_string(),
+            model: None,
+            sample_rates: vec![16000],
+            languages: Vec::new(),
+            partial_results: true,
+        };
+        let bytes = frames_of(|writer| {
+            writer.write_response(&IpcResponse::Hello(hello)).unwrap();
+            for (text, is_final, confidence) in [("hello", false, None), ("héllo", true, Some(0.5))]
+            {
+                let result = IpcTranscriptionResult {
+                    text: text.to_string(),
+                    is_final,
+                    confidence,
+                };
+                writer.write_response(&IpcResponse::Result(result)).unwrap();
+            }
+        });
+
+        let mut reader = IpcReader::new(bytes.as_slice(), IpcFormat::Binary);
+        match reader.read_response().unwrap() {
+            Some(IpcResponse::Hello(hello)) => {
+                assert_eq!(hello.backend, "stub");
+                assert!(hello.partial_results);
+            }
+            other => panic!("expected a hello, got {:?}", other),
+        }
+        match reader.read_response().unwrap() {
+            Some(IpcResponse::Result(result)) => {
+                assert_eq!(result.text, "hello");
+                assert!(!result.is_final);
+                assert_eq!(result.confidence, None);
+            }
+            other => panic!("expected a result, got {:?}", other),
+        }
+        match reader.read_response().unwrap() {
+            Some(IpcResponse::Result(result)) => {
+                assert_eq!(result.text, "héllo");
+                assert!(result.is_final);
+                assert_eq!(result.confidence, Some(0.5));
+            }
+            other => panic!("expected a result, got {:?}", other),
+        }
+        assert!(reader.read_response().unwrap().is_none());
+    }
+
+    #[test]
+    fn only_ending_between_frames_is_clean() {
+        let bytes = frames_of(|writer| writer.write_request(&audio(vec![0.5; 8])).unwrap());
+
+        let mut reader = IpcReader::new(&[][..], IpcFormat::Binary);
+        assert!(reader.read_request().unwrap().is_none());
+
+        // Inside the length prefix, the header and the payload
+        for len in [2, 4 + HEADER_LEN / 2, bytes.len() - 1] {
+            let mut reader = IpcReader::new(&bytes[..len], IpcFormat::Binary);
+            let result = reader.read_request();
+            assert!(
+                matches!(result, Err(ProtocolError::Malformed(_))),
+                "{} bytes: {:?}",
+                len,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn oversized_frames_are_refused() {
+        let samples = vec![0.0; MAX_FRAME_LEN / 2];
+        let mut bytes = Vec::new();
+        let result = IpcWriter::new(&mut bytes, IpcFormat::Binary).write_request(&audio(samples));
+        assert!(matches!(result, Err(ProtocolError::Malformed(_))));
+        assert!(bytes.is_empty());
+
+        for frame_len in [MAX_FRAME_LEN + 1, HEADER_LEN - 1] {
+            let bytes = (frame_len as u32).to_le_bytes();
+            let mut reader = IpcReader::new(&bytes[..], IpcFormat::Binary);
+            assert!(matches!(
+                reader.read_request(),
+                Err(ProtocolError::Malformed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn other_versions_and_bad_payloads_are_refused() {
+        let mut bytes = frames_of(|writer| writer.write_request(&audio(vec![0.5; 2])).unwrap());
+        let mut odd = bytes.clone();
+        odd[0] += 1;
+        odd.push(0);
+        let mut reader = IpcReader::new(odd.as_slice(), IpcFormat::Binary);
+        assert!(matches!(
+            reader.read_request(),
+            Err(ProtocolError::Malformed(_))
+        ));
+
+        bytes[4] = PROTOCOL_VERSION + 1;
+        let mut reader = IpcReader::new(bytes.as_slice(), IpcFormat::Binary);
+        assert!(matches!(
+            reader.read_request(),
+            Err(ProtocolError::UnsupportedVersion(version)) if version == PROTOCOL_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        let mut bytes = Vec::new();
+        let mut writer = IpcWriter::new(&mut bytes, IpcFormat::Json);
+        writer.write_request(&audio(vec![0.25, -0.5])).unwrap();
+        writer.write_request(&IpcRequest::Finalize).unwrap();
+        bytes.extend_from_slice(b"\n  \n");
+
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert_eq!(
+            text.lines().filter(|line| !line.trim().is_empty()).count(),
+            2
+        );
+
+        let mut reader = IpcReader::new(bytes.as_slice(), IpcFormat::Json);
+        match reader.read_request().unwrap() {
+            Some(IpcRequest::Audio(chunk)) => assert_eq!(chunk.samples, [0.25, -0.5]),
+            other => panic!("expected audio, got {:?}", other),
+        }
+        assert!(matches!(
+            reader.read_request().unwrap(),
+            Some(IpcRequest::Finalize)
+        ));
+        assert!(reader.read_request().unwrap().is_none());
+    }
+
+    #[test]
+    fn bad_json_lines_are_recoverable() {
+        let bytes = b"{not json}\n{\"type\":\"finalize\"}\n";
+        let mut reader = IpcReader::new(&bytes[..], IpcFormat::Json);
+        let error = reader.read_request().unwrap_err();
+        assert!(error.is_recoverable());
+        assert!(matches!(
+            reader.read_request().unwrap(),
+            Some(IpcRequest::Finalize)
+        ));
+    }
+}
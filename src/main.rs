@@ -4,7 +4,9 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use whisperkey_core::{
-    init_core_actors, load_config, types::AppOutput, CoordinatorMsg, CoreHandles,
+    init_core_actors, load_config,
+    types::{AppOutput, Component, ListeningState},
+    CoordinatorMsg, CoreHandles,
 };
 
 mod settings;
@@ -15,6 +17,7 @@ enum AppInput {
     TestCore,
     StartListening,
     StopListening,
+    CancelTyping,
     ProcessOutput(AppOutput),
     UpdateCoreHandles,
     UpdateTextBuffer(String),
@@ -25,7 +28,10 @@ enum AppInput {
 struct AppModel {
     core_handles: Option<CoreHandles>,
     status_text: String,
+    input_device_text: String,
+    transcriber_text: String,
     transcription_text: String,
+    listening_state: ListeningState,
     keyboard_output_enabled: bool,
 }
 
@@ -49,7 +55,10 @@ impl SimpleComponent for AppModel {
         let model = Self {
             core_handles: None,
             status_text: "Starting...".to_string(),
+            input_device_text: "Input device: not started".to_string(),
+            transcriber_text: "Transcriber: loading...".to_string(),
             transcription_text: "".to_string(),
+            listening_state: ListeningState::Idle,
             keyboard_output_enabled: config.enable_keyboard_output,
         };
 
@@ -168,6 +177,18 @@ impl SimpleComponent for AppModel {
                         set_margin_bottom: 12,
                     },
 
+                    gtk::Label {
+                        #[watch]
+                        set_label: &model.input_device_text,
+                        set_margin_bottom: 4,
+                    },
+
+                    gtk::Label {
+                        #[watch]
+                        set_label: &model.transcriber_text,
+                        set_margin_bottom: 12,
+                    },
+
                     // Control buttons
                     gtk::Box {
                         set_orientation: gtk::Orientation::Horizontal,
@@ -180,12 +201,32 @@ impl SimpleComponent for AppModel {
                         },
                         gtk::Button {
                             set_label: "Start Listening",
+                            #[watch]
+                            // Starting while waiting for the wake word skips it
+                            set_sensitive: matches!(
+                                model.listening_state,
+                                ListeningState::Idle | ListeningState::WaitingForWakeWord
+                            ),
                             connect_clicked => AppInput::StartListening,
                         },
                         gtk::Button {
                             set_label: "Stop Listening",
+                            #[watch]
+                            set_sensitive: model.listening_state != ListeningState::Idle,
                             connect_clicked => AppInput::StopListening,
                         },
+                        gtk::Button {
+                            set_label: "Cancel Typing",
+                            #[watch]
+                            set_sensitive: model.listening_state == ListeningState::Typing,
+                            connect_clicked => AppInput::CancelTyping,
+                        },
+
+                        gtk::Label {
+                            #[watch]
+                            set_label: &format!("State: {}", model.listening_state),
+                            set_margin_start: 10,
+                        },
                     },
 
                     // Keyboard output toggle
@@ -277,6 +318,17 @@ impl SimpleComponent for AppModel {
                     self.status_text = "Core not ready yet".to_string();
                 }
             }
+            AppInput::CancelTyping => {
+                if let Some(handles) = &self.core_handles {
+                    handles
+                        .coordinator
+                        .send_message(CoordinatorMsg::CancelTyping)
+                        .unwrap();
+                    println!("Sent CancelTyping message to coordinator");
+                } else {
+                    self.status_text = "Core not ready yet".to_string();
+                }
+            }
             AppInput::ToggleKeyboardOutput(enable) => {
                 if let Some(handles) = &self.core_handles {
                     handles
@@ -293,6 +345,34 @@ impl SimpleComponent for AppModel {
                 AppOutput::UpdateStatus(status) => {
                     self.status_text = status;
                 }
+                AppOutput::InputDeviceChanged(name) => {
+                    self.input_device_text = format!("Input device: {}", name);
+                }
+                AppOutput::TranscriberReady(info) => {
+                    let mut text = format!("Transcriber: {}", info.backend);
+                    if let Some(model) = &info.model {
+                        text.push_str(&format!(" ({})", model));
+                    }
+                    if !info.partial_results {
+                        text.push_str(", results after each utterance");
+                    }
+                    self.transcriber_text = text;
+                }
+                AppOutput::ComponentFailed { component, error } => {
+                    self.status_text = format!("{} failed: {}", component, error);
+                    if component == Component::Transcriber {
+                        self.transcriber_text = "Transcriber: not running".to_string();
+                    }
+                }
+                AppOutput::StateChanged(listening_state) => {
+                    self.listening_state = listening_state;
+                }
+                AppOutput::ComponentRecovered(component) => {
+                    self.status_text = format!("{} recovered", component);
+                    if component == Component::Transcriber {
+                        self.transcriber_text = "Transcriber: loading...".to_string();
+                    }
+                }
                 AppOutput::UpdateTranscription(text) => {
                     if !text.is_empty() {
                         // Append to transcription text with a newline if not empty
@@ -7,7 +7,11 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::Arc;
 
-use whisperkey_core::{config::CommandAction, load_config, save_config, Settings, VadMode};
+use whisperkey_core::{
+    config::CommandAction, hotkey::Hotkey, list_input_devices, load_config, save_config,
+    ActivationMode, HotkeyBackend, HotkeyMode, InjectionBackend, OutputMethod, PasteShortcut,
+    Settings, TranscriptionBackend, VadMode,
+};
 
 pub fn show_settings_dialog(parent: &Window) -> bool {
     // Load current settings
@@ -44,7 +48,7 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
     let model_path_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
     model_path_box.set_margin_bottom(12);
 
-    let model_path_label = Label::new(Some("Vosk Model Path:"));
+    let model_path_label = Label::new(Some("Model Path:"));
     model_path_label.set_halign(gtk4::Align::Start);
     model_path_label.set_valign(gtk4::Align::Center);
     model_path_box.append(&model_path_label);
@@ -54,7 +58,7 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
 
     let model_path_entry = Entry::new();
     model_path_entry.set_hexpand(true);
-    model_path_entry.set_placeholder_text(Some("Path to Vosk model directory"));
+    model_path_entry.set_placeholder_text(Some("Vosk model directory or Whisper model file"));
     if let Some(path) = &settings.borrow().model_path {
         model_path_entry.set_text(path);
     }
@@ -66,6 +70,112 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
     model_path_box.append(&inner_box);
     content_area.append(&model_path_box);
 
+    // Transcription backend
+    let backend_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    backend_box.set_margin_bottom(6);
+
+    let backend_label = Label::new(Some("Transcription Backend:"));
+    backend_label.set_halign(gtk4::Align::Start);
+    backend_box.append(&backend_label);
+
+    let backend_combo = ComboBoxText::new();
+    backend_combo.append(Some("vosk"), "Vosk (streaming)");
+    backend_combo.append(Some("whisper"), "Whisper (per utterance)");
+    backend_combo.append(Some("stub"), "Stub (scripted text, for testing)");
+    backend_combo.set_active_id(Some(settings.borrow().transcription_backend.as_arg()));
+    backend_combo.set_margin_start(6);
+    backend_box.append(&backend_combo);
+    content_area.append(&backend_box);
+
+    // Whisper model size and quantization
+    let whisper_model_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    whisper_model_box.set_margin_bottom(6);
+    whisper_model_box.set_margin_start(24); // Indent
+
+    let whisper_model_label = Label::new(Some("Whisper Model:"));
+    whisper_model_label.set_halign(gtk4::Align::Start);
+    whisper_model_box.append(&whisper_model_label);
+
+    let whisper_model_entry = Entry::new();
+    whisper_model_entry.set_hexpand(true);
+    whisper_model_entry.set_placeholder_text(Some("e.g. base.en or small.en-q5_1"));
+    whisper_model_entry.set_text(&settings.borrow().whisper_model);
+    whisper_model_box.append(&whisper_model_entry);
+    content_area.append(&whisper_model_box);
+
+    // Whisper threads
+    let whisper_threads_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    whisper_threads_box.set_margin_bottom(6);
+    whisper_threads_box.set_margin_start(24); // Indent
+
+    let whisper_threads_label = Label::new(Some("Threads (0 = all cores):"));
+    whisper_threads_label.set_halign(gtk4::Align::Start);
+    whisper_threads_box.append(&whisper_threads_label);
+
+    let whisper_threads_spin = SpinButton::with_range(0.0, 64.0, 1.0);
+    whisper_threads_spin.set_value(settings.borrow().whisper_threads as f64);
+    whisper_threads_spin.set_margin_start(6);
+    whisper_threads_box.append(&whisper_threads_spin);
+    content_area.append(&whisper_threads_box);
+
+    // Whisper language
+    let language_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    language_box.set_margin_bottom(12);
+    language_box.set_margin_start(24); // Indent
+
+    let language_label = Label::new(Some("Language:"));
+    language_label.set_halign(gtk4::Align::Start);
+    language_box.append(&language_label);
+
+    let language_entry = Entry::new();
+    language_entry.set_placeholder_text(Some("en, de, ... or auto"));
+    language_entry.set_text(&settings.borrow().language);
+    language_box.append(&language_entry);
+    content_area.append(&language_box);
+
+    // Input device row
+    let device_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    device_box.set_margin_bottom(12);
+
+    let device_label = Label::new(Some("Input Device:"));
+    device_label.set_halign(gtk4::Align::Start);
+    device_label.set_valign(gtk4::Align::Center);
+    device_box.append(&device_label);
+
+    let device_combo = ComboBoxText::new();
+    device_combo.set_hexpand(true);
+    device_combo.append(Some(""), "System Default");
+
+    let mut device_names: Vec<String> = Vec::new();
+    for device in list_input_devices() {
+        // The same device can be listed by more than one host
+        if device_names.contains(&device.name) {
+            continue;
+        }
+        let mut label = format!("{} ({})", device.name, device.host);
+        if let (Some(rate), Some(channels)) = (device.default_sample_rate, device.default_channels)
+        {
+            label.push_str(&format!(" - {} Hz, {} ch", rate, channels));
+        }
+        if device.is_default {
+            label.push_str(" [default]");
+        }
+        device_combo.append(Some(device.name.as_str()), &label);
+        device_names.push(device.name);
+    }
+
+    // Keep a configured device selectable even if it's currently unplugged
+    let configured_device = settings.borrow().input_device.clone().unwrap_or_default();
+    if !configured_device.is_empty() && !device_names.contains(&configured_device) {
+        device_combo.append(
+            Some(configured_device.as_str()),
+            &format!("{} (not connected)", configured_device),
+        );
+    }
+    device_combo.set_active_id(Some(configured_device.as_str()));
+    device_box.append(&device_combo);
+    content_area.append(&device_box);
+
     // Audio Processing Section Header
     let audio_section_label = Label::new(Some("Audio Processing"));
     audio_section_label.set_halign(gtk4::Align::Start);
@@ -149,6 +259,72 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
     silence_box.append(&silence_spin);
     content_area.append(&silence_box);
 
+    // VAD frame length
+    let vad_frame_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    vad_frame_box.set_margin_bottom(6);
+    vad_frame_box.set_margin_start(24); // Indent
+
+    let vad_frame_label = Label::new(Some("VAD Frame Length:"));
+    vad_frame_label.set_halign(gtk4::Align::Start);
+    vad_frame_box.append(&vad_frame_label);
+
+    let vad_frame_combo = ComboBoxText::new();
+    vad_frame_combo.append(Some("10"), "10 ms");
+    vad_frame_combo.append(Some("20"), "20 ms");
+    vad_frame_combo.append(Some("30"), "30 ms");
+    let vad_frame_id = settings.borrow().vad_frame_ms.to_string();
+    if !vad_frame_combo.set_active_id(Some(vad_frame_id.as_str())) {
+        vad_frame_combo.set_active_id(Some("30"));
+    }
+    vad_frame_combo.set_margin_start(6);
+    vad_frame_box.append(&vad_frame_combo);
+    content_area.append(&vad_frame_box);
+
+    // Speech start delay
+    let speech_start_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    speech_start_box.set_margin_bottom(6);
+    speech_start_box.set_margin_start(24); // Indent
+
+    let speech_start_label = Label::new(Some("Speech Start (ms):"));
+    speech_start_label.set_halign(gtk4::Align::Start);
+    speech_start_box.append(&speech_start_label);
+
+    let speech_start_spin = SpinButton::with_range(10.0, 1000.0, 10.0);
+    speech_start_spin.set_value(settings.borrow().vad_speech_start_ms as f64);
+    speech_start_spin.set_margin_start(6);
+    speech_start_box.append(&speech_start_spin);
+    content_area.append(&speech_start_box);
+
+    // Speech end hangover
+    let speech_end_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    speech_end_box.set_margin_bottom(6);
+    speech_end_box.set_margin_start(24); // Indent
+
+    let speech_end_label = Label::new(Some("Speech End Hangover (ms):"));
+    speech_end_label.set_halign(gtk4::Align::Start);
+    speech_end_box.append(&speech_end_label);
+
+    let speech_end_spin = SpinButton::with_range(30.0, 3000.0, 30.0);
+    speech_end_spin.set_value(settings.borrow().vad_speech_end_ms as f64);
+    speech_end_spin.set_margin_start(6);
+    speech_end_box.append(&speech_end_spin);
+    content_area.append(&speech_end_box);
+
+    // Pre-roll
+    let pre_roll_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    pre_roll_box.set_margin_bottom(12);
+    pre_roll_box.set_margin_start(24); // Indent
+
+    let pre_roll_label = Label::new(Some("Pre-roll (ms):"));
+    pre_roll_label.set_halign(gtk4::Align::Start);
+    pre_roll_box.append(&pre_roll_label);
+
+    let pre_roll_spin = SpinButton::with_range(0.0, 2000.0, 50.0);
+    pre_roll_spin.set_value(settings.borrow().vad_pre_roll_ms as f64);
+    pre_roll_spin.set_margin_start(6);
+    pre_roll_box.append(&pre_roll_spin);
+    content_area.append(&pre_roll_box);
+
     // Keyboard Output Section Header
     let keyboard_section_label = Label::new(Some("Keyboard Output"));
     keyboard_section_label.set_halign(gtk4::Align::Start);
@@ -182,6 +358,168 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
     delay_box.append(&delay_spin);
     content_area.append(&delay_box);
 
+    // Typing pace, in chunks of characters with a pause in between
+    let chunk_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    chunk_box.set_margin_bottom(12);
+    chunk_box.set_margin_start(24); // Indent
+
+    let chunk_chars_label = Label::new(Some("Characters per chunk (0 = all):"));
+    chunk_chars_label.set_halign(gtk4::Align::Start);
+    chunk_box.append(&chunk_chars_label);
+
+    let chunk_chars_spin = SpinButton::with_range(0.0, 1000.0, 1.0);
+    chunk_chars_spin.set_value(settings.borrow().typing_chunk_chars as f64);
+    chunk_chars_spin.set_margin_start(6);
+    chunk_box.append(&chunk_chars_spin);
+
+    let chunk_delay_label = Label::new(Some("Pause (ms):"));
+    chunk_delay_label.set_halign(gtk4::Align::Start);
+    chunk_delay_label.set_margin_start(12);
+    chunk_box.append(&chunk_delay_label);
+
+    let chunk_delay_spin = SpinButton::with_range(0.0, 1000.0, 5.0);
+    chunk_delay_spin.set_value(settings.borrow().typing_chunk_delay_ms as f64);
+    chunk_delay_spin.set_margin_start(6);
+    chunk_box.append(&chunk_delay_spin);
+    content_area.append(&chunk_box);
+
+    // Text injection backend
+    let injection_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    injection_box.set_margin_bottom(6);
+    injection_box.set_margin_start(24); // Indent
+
+    let injection_label = Label::new(Some("Typing backend:"));
+    injection_label.set_halign(gtk4::Align::Start);
+    injection_box.append(&injection_label);
+
+    let injection_combo = ComboBoxText::new();
+    injection_combo.append(Some("auto"), "Automatic (detect the session type)");
+    injection_combo.append(Some("enigo"), "X11 (enigo)");
+    injection_combo.append(Some("virtual_keyboard"), "Wayland virtual keyboard");
+    injection_combo.append(
+        Some("input_method"),
+        "Wayland input method (text fields only)",
+    );
+    injection_combo.append(Some("uinput"), "uinput (needs access to /dev/uinput)");
+    injection_combo.append(Some("wtype"), "wtype");
+    injection_combo.append(Some("ydotool"), "ydotool");
+    let injection_id = match settings.borrow().injection_backend {
+        InjectionBackend::Auto => "auto",
+        InjectionBackend::Enigo => "enigo",
+        InjectionBackend::VirtualKeyboard => "virtual_keyboard",
+        InjectionBackend::InputMethod => "input_method",
+        InjectionBackend::Uinput => "uinput",
+        InjectionBackend::Wtype => "wtype",
+        InjectionBackend::Ydotool => "ydotool",
+    };
+    injection_combo.set_active_id(Some(injection_id));
+    injection_combo.set_margin_start(6);
+    injection_box.append(&injection_combo);
+    content_area.append(&injection_box);
+
+    // Output method
+    let output_method_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    output_method_box.set_margin_bottom(6);
+    output_method_box.set_margin_start(24); // Indent
+
+    let output_method_label = Label::new(Some("Output method:"));
+    output_method_label.set_halign(gtk4::Align::Start);
+    output_method_box.append(&output_method_label);
+
+    let output_method_combo = ComboBoxText::new();
+    output_method_combo.append(Some("type"), "Type each character");
+    output_method_combo.append(Some("paste"), "Paste from the clipboard");
+    output_method_combo.append(Some("auto"), "Automatic (paste long or non-ASCII text)");
+    match settings.borrow().output_method {
+        OutputMethod::Type => output_method_combo.set_active_id(Some("type")),
+        OutputMethod::Paste => output_method_combo.set_active_id(Some("paste")),
+        OutputMethod::Auto => output_method_combo.set_active_id(Some("auto")),
+    };
+    output_method_combo.set_margin_start(6);
+    output_method_box.append(&output_method_combo);
+    content_area.append(&output_method_box);
+
+    // Paste shortcut
+    let paste_shortcut_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    paste_shortcut_box.set_margin_bottom(6);
+    paste_shortcut_box.set_margin_start(24); // Indent
+
+    let paste_shortcut_label = Label::new(Some("Paste shortcut:"));
+    paste_shortcut_label.set_halign(gtk4::Align::Start);
+    paste_shortcut_box.append(&paste_shortcut_label);
+
+    let paste_shortcut_combo = ComboBoxText::new();
+    paste_shortcut_combo.append(Some("ctrl_v"), "Ctrl+V");
+    paste_shortcut_combo.append(Some("ctrl_shift_v"), "Ctrl+Shift+V (terminals)");
+    paste_shortcut_combo.append(Some("shift_insert"), "Shift+Insert");
+    match settings.borrow().paste_shortcut {
+        PasteShortcut::CtrlV => paste_shortcut_combo.set_active_id(Some("ctrl_v")),
+        PasteShortcut::CtrlShiftV => paste_shortcut_combo.set_active_id(Some("ctrl_shift_v")),
+        PasteShortcut::ShiftInsert => paste_shortcut_combo.set_active_id(Some("shift_insert")),
+    };
+    paste_shortcut_combo.set_margin_start(6);
+    paste_shortcut_box.append(&paste_shortcut_combo);
+    content_area.append(&paste_shortcut_box);
+
+    // Restore clipboard after pasting
+    let restore_clipboard_check = CheckButton::with_label("Restore the clipboard after pasting");
+    restore_clipboard_check.set_active(settings.borrow().restore_clipboard);
+    restore_clipboard_check.set_margin_start(24); // Indent
+    restore_clipboard_check.set_margin_bottom(12);
+    content_area.append(&restore_clipboard_check);
+
+    // Live typing of partial results
+    let live_typing_check =
+        CheckButton::with_label("Type while speaking (corrects words as they change)");
+    live_typing_check.set_active(settings.borrow().live_typing);
+    live_typing_check.set_margin_start(24); // Indent
+    live_typing_check.set_margin_bottom(6);
+    content_area.append(&live_typing_check);
+
+    let max_rewrite_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    max_rewrite_box.set_margin_bottom(12);
+    max_rewrite_box.set_margin_start(48); // Indent under the checkbox
+
+    let max_rewrite_label = Label::new(Some("Most characters to correct while speaking:"));
+    max_rewrite_label.set_halign(gtk4::Align::Start);
+    max_rewrite_box.append(&max_rewrite_label);
+
+    let max_rewrite_spin = SpinButton::with_range(0.0, 500.0, 1.0);
+    max_rewrite_spin.set_value(settings.borrow().live_typing_max_rewrite_chars as f64);
+    max_rewrite_spin.set_margin_start(6);
+    max_rewrite_box.append(&max_rewrite_spin);
+    content_area.append(&max_rewrite_box);
+
+    // Formatting applied to dictated text before it's typed
+    let format_label = Label::new(Some("Text formatting:"));
+    format_label.set_halign(gtk4::Align::Start);
+    format_label.set_margin_start(24); // Indent
+    format_label.set_margin_bottom(6);
+    content_area.append(&format_label);
+
+    let format_capitalize_check = CheckButton::with_label("Capitalize sentences");
+    format_capitalize_check.set_active(settings.borrow().format_capitalize);
+    format_capitalize_check.set_margin_start(48); // Indent under the label
+    content_area.append(&format_capitalize_check);
+
+    let format_punctuation_check =
+        CheckButton::with_label("Spoken punctuation (\"comma\", \"period\", \"question mark\")");
+    format_punctuation_check.set_active(settings.borrow().format_spoken_punctuation);
+    format_punctuation_check.set_margin_start(48);
+    content_area.append(&format_punctuation_check);
+
+    let format_spacing_check = CheckButton::with_label("Space between utterances");
+    format_spacing_check.set_active(settings.borrow().format_spacing);
+    format_spacing_check.set_margin_start(48);
+    content_area.append(&format_spacing_check);
+
+    let format_numbers_check =
+        CheckButton::with_label("Numbers as digits (\"twenty three\" as 23)");
+    format_numbers_check.set_active(settings.borrow().format_numbers);
+    format_numbers_check.set_margin_start(48);
+    format_numbers_check.set_margin_bottom(12);
+    content_area.append(&format_numbers_check);
+
     // Warning label for keyboard output
     let warning_label = Label::new(Some("Warning: Enabling keyboard output will type the transcribed text into any active application."));
     warning_label.set_margin_start(24);
@@ -191,6 +529,199 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
     warning_label.add_css_class("warning");
     content_area.append(&warning_label);
 
+    // Activation Section Header
+    let hotkey_section_label = Label::new(Some("Activation"));
+    hotkey_section_label.set_halign(gtk4::Align::Start);
+    hotkey_section_label.set_margin_top(12);
+    hotkey_section_label.set_margin_bottom(6);
+    content_area.append(&hotkey_section_label);
+
+    // Separator after section header
+    let hotkey_separator = gtk4::Separator::new(gtk4::Orientation::Horizontal);
+    hotkey_separator.set_margin_bottom(12);
+    content_area.append(&hotkey_separator);
+
+    // Activation mode
+    let activation_mode_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    activation_mode_box.set_margin_bottom(6);
+
+    let activation_mode_label = Label::new(Some("Listening:"));
+    activation_mode_label.set_halign(gtk4::Align::Start);
+    activation_mode_box.append(&activation_mode_label);
+
+    let activation_mode_combo = ComboBoxText::new();
+    activation_mode_combo.append(Some("manual"), "Until stopped");
+    activation_mode_combo.append(Some("auto_stop"), "Stop automatically after silence");
+    activation_mode_combo.append(Some("wake_word"), "Always on, start on a wake phrase");
+    match settings.borrow().activation_mode {
+        ActivationMode::Manual => activation_mode_combo.set_active_id(Some("manual")),
+        ActivationMode::AutoStop => activation_mode_combo.set_active_id(Some("auto_stop")),
+        ActivationMode::WakeWord => activation_mode_combo.set_active_id(Some("wake_word")),
+    };
+    activation_mode_combo.set_margin_start(6);
+    activation_mode_box.append(&activation_mode_combo);
+    content_area.append(&activation_mode_box);
+
+    let auto_stop_silence_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    auto_stop_silence_box.set_margin_bottom(6);
+    auto_stop_silence_box.set_margin_start(24); // Indent
+
+    let auto_stop_silence_label = Label::new(Some("Silence before stopping (ms):"));
+    auto_stop_silence_label.set_halign(gtk4::Align::Start);
+    auto_stop_silence_box.append(&auto_stop_silence_label);
+
+    let auto_stop_silence_spin = SpinButton::with_range(500.0, 30000.0, 250.0);
+    auto_stop_silence_spin.set_value(settings.borrow().auto_stop_silence_ms as f64);
+    auto_stop_silence_spin.set_margin_start(6);
+    auto_stop_silence_box.append(&auto_stop_silence_spin);
+    content_area.append(&auto_stop_silence_box);
+
+    let max_session_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    max_session_box.set_margin_bottom(12);
+    max_session_box.set_margin_start(24); // Indent
+
+    let max_session_label = Label::new(Some("Maximum session length (s, 0 = no limit):"));
+    max_session_label.set_halign(gtk4::Align::Start);
+    max_session_box.append(&max_session_label);
+
+    let max_session_spin = SpinButton::with_range(0.0, 3600.0, 30.0);
+    max_session_spin.set_value(settings.borrow().max_session_secs as f64);
+    max_session_spin.set_margin_start(6);
+    max_session_box.append(&max_session_spin);
+    content_area.append(&max_session_box);
+
+    // Wake phrases, comma separated
+    let wake_phrases_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    wake_phrases_box.set_margin_bottom(6);
+    wake_phrases_box.set_margin_start(24); // Indent
+
+    let wake_phrases_label = Label::new(Some("Wake phrases:"));
+    wake_phrases_label.set_halign(gtk4::Align::Start);
+    wake_phrases_box.append(&wake_phrases_label);
+
+    let wake_phrases_entry = Entry::new();
+    wake_phrases_entry.set_hexpand(true);
+    wake_phrases_entry.set_placeholder_text(Some("e.g. hey computer, start dictation"));
+    wake_phrases_entry.set_text(&settings.borrow().wake_phrases.join(", "));
+    wake_phrases_box.append(&wake_phrases_entry);
+    content_area.append(&wake_phrases_box);
+
+    let wake_sensitivity_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    wake_sensitivity_box.set_margin_bottom(6);
+    wake_sensitivity_box.set_margin_start(24); // Indent
+
+    let wake_sensitivity_label = Label::new(Some("Wake word sensitivity:"));
+    wake_sensitivity_label.set_halign(gtk4::Align::Start);
+    wake_sensitivity_box.append(&wake_sensitivity_label);
+
+    let wake_sensitivity_spin = SpinButton::with_range(0.0, 1.0, 0.05);
+    wake_sensitivity_spin.set_digits(2);
+    wake_sensitivity_spin.set_value(settings.borrow().wake_word_sensitivity as f64);
+    wake_sensitivity_spin.set_margin_start(6);
+    wake_sensitivity_box.append(&wake_sensitivity_spin);
+    content_area.append(&wake_sensitivity_box);
+
+    // Wake word model (empty uses the main Vosk model)
+    let wake_model_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    wake_model_box.set_margin_bottom(12);
+    wake_model_box.set_margin_start(24); // Indent
+
+    let wake_model_label = Label::new(Some("Wake word model:"));
+    wake_model_label.set_halign(gtk4::Align::Start);
+    wake_model_box.append(&wake_model_label);
+
+    let wake_model_entry = Entry::new();
+    wake_model_entry.set_hexpand(true);
+    wake_model_entry.set_placeholder_text(Some("Small Vosk model, empty to use the model above"));
+    wake_model_entry.set_text(
+        settings
+            .borrow()
+            .wake_word_model_path
+            .as_deref()
+            .unwrap_or(""),
+    );
+    wake_model_box.append(&wake_model_entry);
+    content_area.append(&wake_model_box);
+
+    // Hotkey combination (empty disables it)
+    let hotkey_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    hotkey_box.set_margin_bottom(6);
+
+    let hotkey_label = Label::new(Some("Hotkey:"));
+    hotkey_label.set_halign(gtk4::Align::Start);
+    hotkey_box.append(&hotkey_label);
+
+    let hotkey_entry = Entry::new();
+    hotkey_entry.set_hexpand(true);
+    hotkey_entry.set_placeholder_text(Some("e.g. Ctrl+Alt+Space or F9, empty to disable"));
+    hotkey_entry.set_text(settings.borrow().hotkey.as_deref().unwrap_or(""));
+    hotkey_box.append(&hotkey_entry);
+    content_area.append(&hotkey_box);
+
+    // Hotkey that cancels typing in progress (empty disables it)
+    let abort_hotkey_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    abort_hotkey_box.set_margin_bottom(6);
+
+    let abort_hotkey_label = Label::new(Some("Cancel typing hotkey:"));
+    abort_hotkey_label.set_halign(gtk4::Align::Start);
+    abort_hotkey_box.append(&abort_hotkey_label);
+
+    let abort_hotkey_entry = Entry::new();
+    abort_hotkey_entry.set_hexpand(true);
+    abort_hotkey_entry.set_placeholder_text(Some("e.g. Ctrl+Alt+X, empty to disable"));
+    abort_hotkey_entry.set_text(settings.borrow().abort_hotkey.as_deref().unwrap_or(""));
+    abort_hotkey_box.append(&abort_hotkey_entry);
+    content_area.append(&abort_hotkey_box);
+
+    // Hotkey mode
+    let hotkey_mode_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    hotkey_mode_box.set_margin_bottom(6);
+
+    let hotkey_mode_label = Label::new(Some("Mode:"));
+    hotkey_mode_label.set_halign(gtk4::Align::Start);
+    hotkey_mode_box.append(&hotkey_mode_label);
+
+    let hotkey_mode_combo = ComboBoxText::new();
+    hotkey_mode_combo.append(
+        Some("toggle"),
+        "Toggle (press to start, press again to stop)",
+    );
+    hotkey_mode_combo.append(Some("push_to_talk"), "Push to talk (hold while speaking)");
+    match settings.borrow().hotkey_mode {
+        HotkeyMode::Toggle => hotkey_mode_combo.set_active_id(Some("toggle")),
+        HotkeyMode::PushToTalk => hotkey_mode_combo.set_active_id(Some("push_to_talk")),
+    };
+    hotkey_mode_combo.set_margin_start(6);
+    hotkey_mode_box.append(&hotkey_mode_combo);
+    content_area.append(&hotkey_mode_box);
+
+    // Hotkey backend
+    let hotkey_backend_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    hotkey_backend_box.set_margin_bottom(6);
+
+    let hotkey_backend_label = Label::new(Some("Backend:"));
+    hotkey_backend_label.set_halign(gtk4::Align::Start);
+    hotkey_backend_box.append(&hotkey_backend_label);
+
+    let hotkey_backend_combo = ComboBoxText::new();
+    hotkey_backend_combo.append(Some("auto"), "Automatic");
+    hotkey_backend_combo.append(Some("evdev"), "evdev (needs the 'input' group)");
+    hotkey_backend_combo.append(Some("x11"), "X11 key grab");
+    match settings.borrow().hotkey_backend {
+        HotkeyBackend::Auto => hotkey_backend_combo.set_active_id(Some("auto")),
+        HotkeyBackend::Evdev => hotkey_backend_combo.set_active_id(Some("evdev")),
+        HotkeyBackend::X11 => hotkey_backend_combo.set_active_id(Some("x11")),
+    };
+    hotkey_backend_combo.set_margin_start(6);
+    hotkey_backend_box.append(&hotkey_backend_combo);
+    content_area.append(&hotkey_backend_box);
+
+    let hotkey_info_label = Label::new(Some("Hotkey changes apply after restarting WhisperKey."));
+    hotkey_info_label.set_margin_bottom(12);
+    hotkey_info_label.set_wrap(true);
+    hotkey_info_label.set_width_chars(40);
+    content_area.append(&hotkey_info_label);
+
     // Commands Section Header
     let commands_section_label = Label::new(Some("Voice Commands"));
     commands_section_label.set_halign(gtk4::Align::Start);
@@ -213,7 +744,7 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
 
     // Display existing commands in a text view
     let commands_info = format!(
-        "Default commands:\n\n{}\n\nCommands can be either Type or Exec and support {{args}} substitution.",
+        "Default commands:\n\n{}\n\nCommands can be Type, Exec, Keys (e.g. ctrl+shift+t or {{enter}}{{tab}}) or Edit (NewLine, NewParagraph, ScratchThat, Undo). Triggers can have slots like {{n:number}}, {{app:one_of(firefox,terminal)}} or {{name}} (any words), which Type and Exec fill in by name, along with {{args}} for anything said after the trigger.",
         settings
             .borrow()
             .commands
@@ -225,6 +756,12 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
                 CommandAction::Exec(template) => {
                     format!("• \"{}\" → Exec: {}", trigger, template)
                 }
+                CommandAction::Edit(edit) => {
+                    format!("• \"{}\" → Edit: {:?}", trigger, edit)
+                }
+                CommandAction::Keys(keys) => {
+                    format!("• \"{}\" → Keys: {}", trigger, keys)
+                }
             })
             .collect::<Vec<String>>()
             .join("\n")
@@ -242,11 +779,53 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
     let vad_mode_box_clone = vad_mode_box.clone();
     let energy_box_clone = energy_box.clone();
     let silence_box_clone = silence_box.clone();
+    let vad_frame_box_clone = vad_frame_box.clone();
+    let speech_start_box_clone = speech_start_box.clone();
+    let speech_end_box_clone = speech_end_box.clone();
+    let pre_roll_box_clone = pre_roll_box.clone();
     vad_check.connect_toggled(move |check| {
         let enabled = check.is_active();
         vad_mode_box_clone.set_sensitive(enabled);
         energy_box_clone.set_sensitive(enabled);
         silence_box_clone.set_sensitive(enabled);
+        vad_frame_box_clone.set_sensitive(enabled);
+        speech_start_box_clone.set_sensitive(enabled);
+        speech_end_box_clone.set_sensitive(enabled);
+        pre_roll_box_clone.set_sensitive(enabled);
+    });
+
+    // Handle activation mode change
+    let auto_stop_silence_box_clone = auto_stop_silence_box.clone();
+    let max_session_box_clone = max_session_box.clone();
+    let wake_phrases_box_clone = wake_phrases_box.clone();
+    let wake_sensitivity_box_clone = wake_sensitivity_box.clone();
+    let wake_model_box_clone = wake_model_box.clone();
+    activation_mode_combo.connect_changed(move |combo| {
+        let mode = combo.active_id();
+        let sessions = mode.as_deref() != Some("manual");
+        let wake_word = mode.as_deref() == Some("wake_word");
+        auto_stop_silence_box_clone.set_sensitive(sessions);
+        max_session_box_clone.set_sensitive(sessions);
+        wake_phrases_box_clone.set_sensitive(wake_word);
+        wake_sensitivity_box_clone.set_sensitive(wake_word);
+        wake_model_box_clone.set_sensitive(wake_word);
+    });
+    let activation_mode = settings.borrow().activation_mode;
+    auto_stop_silence_box.set_sensitive(activation_mode != ActivationMode::Manual);
+    max_session_box.set_sensitive(activation_mode != ActivationMode::Manual);
+    wake_phrases_box.set_sensitive(activation_mode == ActivationMode::WakeWord);
+    wake_sensitivity_box.set_sensitive(activation_mode == ActivationMode::WakeWord);
+    wake_model_box.set_sensitive(activation_mode == ActivationMode::WakeWord);
+
+    // Handle backend change
+    let whisper_model_box_clone = whisper_model_box.clone();
+    let whisper_threads_box_clone = whisper_threads_box.clone();
+    let language_box_clone = language_box.clone();
+    backend_combo.connect_changed(move |combo| {
+        let whisper = combo.active_id().as_deref() == Some("whisper");
+        whisper_model_box_clone.set_sensitive(whisper);
+        whisper_threads_box_clone.set_sensitive(whisper);
+        language_box_clone.set_sensitive(whisper);
     });
 
     // Handle keyboard output checkbox change
@@ -256,20 +835,57 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
         delay_box_clone.set_sensitive(enabled);
     });
 
+    // Handle output method change
+    let paste_shortcut_box_clone = paste_shortcut_box.clone();
+    let restore_clipboard_check_clone = restore_clipboard_check.clone();
+    output_method_combo.connect_changed(move |combo| {
+        let pastes = combo.active_id().as_deref() != Some("type");
+        paste_shortcut_box_clone.set_sensitive(pastes);
+        restore_clipboard_check_clone.set_sensitive(pastes);
+    });
+
+    // Handle live typing checkbox change
+    let max_rewrite_box_clone = max_rewrite_box.clone();
+    live_typing_check.connect_toggled(move |check| {
+        max_rewrite_box_clone.set_sensitive(check.is_active());
+    });
+
     // Initialize sensitivity
+    let whisper_selected = settings.borrow().transcription_backend == TranscriptionBackend::Whisper;
+    whisper_model_box.set_sensitive(whisper_selected);
+    whisper_threads_box.set_sensitive(whisper_selected);
+    language_box.set_sensitive(whisper_selected);
     vad_mode_box.set_sensitive(settings.borrow().enable_vad);
     energy_box.set_sensitive(settings.borrow().enable_vad);
     silence_box.set_sensitive(settings.borrow().enable_vad);
+    vad_frame_box.set_sensitive(settings.borrow().enable_vad);
+    speech_start_box.set_sensitive(settings.borrow().enable_vad);
+    speech_end_box.set_sensitive(settings.borrow().enable_vad);
+    pre_roll_box.set_sensitive(settings.borrow().enable_vad);
     delay_box.set_sensitive(settings.borrow().enable_keyboard_output);
+    let pastes = settings.borrow().output_method != OutputMethod::Type;
+    paste_shortcut_box.set_sensitive(pastes);
+    restore_clipboard_check.set_sensitive(pastes);
+    max_rewrite_box.set_sensitive(settings.borrow().live_typing);
 
-    // Handle browse button click
+    // Handle browse button click. Vosk models are directories, Whisper
+    // models are single ggml files.
     let model_path_entry_clone = model_path_entry.clone();
+    let backend_combo_clone = backend_combo.clone();
     let dialog_clone = dialog.clone();
     browse_button.connect_clicked(move |_| {
+        let (title, action) = if backend_combo_clone.active_id().as_deref() == Some("whisper") {
+            ("Select Whisper Model File", FileChooserAction::Open)
+        } else {
+            (
+                "Select Vosk Model Directory",
+                FileChooserAction::SelectFolder,
+            )
+        };
         let file_chooser = FileChooserDialog::new(
-            Some("Select Vosk Model Directory"),
+            Some(title),
             Some(&dialog_clone), // Set transient for the settings dialog
-            FileChooserAction::SelectFolder,
+            action,
             &[
                 ("Cancel", gtk4::ResponseType::Cancel),
                 ("Select", gtk4::ResponseType::Accept),
@@ -298,13 +914,44 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
 
     // Connect the response signal
     let model_path_entry_for_response = model_path_entry.clone();
+    let backend_combo_for_response = backend_combo.clone();
+    let whisper_model_entry_for_response = whisper_model_entry.clone();
+    let whisper_threads_spin_for_response = whisper_threads_spin.clone();
+    let language_entry_for_response = language_entry.clone();
+    let device_combo_for_response = device_combo.clone();
     let denoise_check_for_response = denoise_check.clone();
     let vad_check_for_response = vad_check.clone();
     let vad_mode_combo_for_response = vad_mode_combo.clone();
     let energy_spin_for_response = energy_spin.clone();
     let silence_spin_for_response = silence_spin.clone();
+    let vad_frame_combo_for_response = vad_frame_combo.clone();
+    let speech_start_spin_for_response = speech_start_spin.clone();
+    let speech_end_spin_for_response = speech_end_spin.clone();
+    let pre_roll_spin_for_response = pre_roll_spin.clone();
     let keyboard_check_for_response = keyboard_check.clone();
     let delay_spin_for_response = delay_spin.clone();
+    let chunk_chars_spin_for_response = chunk_chars_spin.clone();
+    let chunk_delay_spin_for_response = chunk_delay_spin.clone();
+    let injection_combo_for_response = injection_combo.clone();
+    let output_method_combo_for_response = output_method_combo.clone();
+    let paste_shortcut_combo_for_response = paste_shortcut_combo.clone();
+    let restore_clipboard_check_for_response = restore_clipboard_check.clone();
+    let live_typing_check_for_response = live_typing_check.clone();
+    let format_capitalize_check_for_response = format_capitalize_check.clone();
+    let format_punctuation_check_for_response = format_punctuation_check.clone();
+    let format_spacing_check_for_response = format_spacing_check.clone();
+    let format_numbers_check_for_response = format_numbers_check.clone();
+    let max_rewrite_spin_for_response = max_rewrite_spin.clone();
+    let activation_mode_combo_for_response = activation_mode_combo.clone();
+    let auto_stop_silence_spin_for_response = auto_stop_silence_spin.clone();
+    let max_session_spin_for_response = max_session_spin.clone();
+    let wake_phrases_entry_for_response = wake_phrases_entry.clone();
+    let wake_sensitivity_spin_for_response = wake_sensitivity_spin.clone();
+    let wake_model_entry_for_response = wake_model_entry.clone();
+    let hotkey_entry_for_response = hotkey_entry.clone();
+    let abort_hotkey_entry_for_response = abort_hotkey_entry.clone();
+    let hotkey_mode_combo_for_response = hotkey_mode_combo.clone();
+    let hotkey_backend_combo_for_response = hotkey_backend_combo.clone();
     let settings_clone = settings.clone();
 
     dialog.connect_response(move |dialog, response| {
@@ -320,6 +967,29 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
                 Some(model_path_text)
             };
 
+            // Transcription backend
+            new_settings.transcription_backend =
+                match backend_combo_for_response.active_id().as_deref() {
+                    Some("whisper") => TranscriptionBackend::Whisper,
+                    Some("stub") => TranscriptionBackend::Stub,
+                    _ => TranscriptionBackend::Vosk,
+                };
+            let whisper_model = whisper_model_entry_for_response.text().trim().to_string();
+            if !whisper_model.is_empty() {
+                new_settings.whisper_model = whisper_model;
+            }
+            new_settings.whisper_threads = whisper_threads_spin_for_response.value() as u32;
+            let language = language_entry_for_response.text().trim().to_string();
+            if !language.is_empty() {
+                new_settings.language = language;
+            }
+
+            // Input device (empty id = system default)
+            new_settings.input_device = device_combo_for_response
+                .active_id()
+                .map(|id| id.to_string())
+                .filter(|id| !id.is_empty());
+
             // Audio processing settings
             new_settings.enable_denoise = denoise_check_for_response.is_active();
             new_settings.enable_vad = vad_check_for_response.is_active();
@@ -339,9 +1009,104 @@ pub fn show_settings_dialog(parent: &Window) -> bool {
             // Silence threshold
             new_settings.silence_threshold_ms = silence_spin_for_response.value() as u32;
 
+            // VAD framing, hangover and pre-roll
+            new_settings.vad_frame_ms = vad_frame_combo_for_response
+                .active_id()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or(30);
+            new_settings.vad_speech_start_ms = speech_start_spin_for_response.value() as u32;
+            new_settings.vad_speech_end_ms = speech_end_spin_for_response.value() as u32;
+            new_settings.vad_pre_roll_ms = pre_roll_spin_for_response.value() as u32;
+
             // Keyboard output settings
             new_settings.enable_keyboard_output = keyboard_check_for_response.is_active();
             new_settings.keyboard_output_delay_ms = delay_spin_for_response.value() as u32;
+            new_settings.typing_chunk_chars = chunk_chars_spin_for_response.value() as u32;
+            new_settings.typing_chunk_delay_ms = chunk_delay_spin_for_response.value() as u32;
+            new_settings.injection_backend =
+                match injection_combo_for_response.active_id().as_deref() {
+                    Some("enigo") => InjectionBackend::Enigo,
+                    Some("virtual_keyboard") => InjectionBackend::VirtualKeyboard,
+                    Some("input_method") => InjectionBackend::InputMethod,
+                    Some("uinput") => InjectionBackend::Uinput,
+                    Some("wtype") => InjectionBackend::Wtype,
+                    Some("ydotool") => InjectionBackend::Ydotool,
+                    _ => InjectionBackend::Auto,
+                };
+            new_settings.output_method =
+                match output_method_combo_for_response.active_id().as_deref() {
+                    Some("paste") => OutputMethod::Paste,
+                    Some("auto") => OutputMethod::Auto,
+                    _ => OutputMethod::Type,
+                };
+            new_settings.paste_shortcut =
+                match paste_shortcut_combo_for_response.active_id().as_deref() {
+                    Some("ctrl_shift_v") => PasteShortcut::CtrlShiftV,
+                    Some("shift_insert") => PasteShortcut::ShiftInsert,
+                    _ => PasteShortcut::CtrlV,
+                };
+            new_settings.restore_clipboard = restore_clipboard_check_for_response.is_active();
+            new_settings.live_typing = live_typing_check_for_response.is_active();
+            new_settings.format_capitalize = format_capitalize_check_for_response.is_active();
+            new_settings.format_spoken_punctuation =
+                format_punctuation_check_for_response.is_active();
+            new_settings.format_spacing = format_spacing_check_for_response.is_active();
+            new_settings.format_numbers = format_numbers_check_for_response.is_active();
+            new_settings.live_typing_max_rewrite_chars =
+                max_rewrite_spin_for_response.value() as u32;
+
+            // Activation settings
+            new_settings.activation_mode =
+                match activation_mode_combo_for_response.active_id().as_deref() {
+                    Some("auto_stop") => ActivationMode::AutoStop,
+                    Some("wake_word") => ActivationMode::WakeWord,
+                    _ => ActivationMode::Manual,
+                };
+            new_settings.auto_stop_silence_ms = auto_stop_silence_spin_for_response.value() as u32;
+            new_settings.max_session_secs = max_session_spin_for_response.value() as u32;
+            new_settings.wake_phrases = wake_phrases_entry_for_response
+                .text()
+                .split(',')
+                .map(|phrase| phrase.trim().to_string())
+                .filter(|phrase| !phrase.is_empty())
+                .collect();
+            new_settings.wake_word_sensitivity = wake_sensitivity_spin_for_response.value() as f32;
+            let wake_model_text = wake_model_entry_for_response.text().trim().to_string();
+            new_settings.wake_word_model_path = if wake_model_text.is_empty() {
+                None
+            } else {
+                Some(wake_model_text)
+            };
+
+            // Hotkey settings, an invalid hotkey keeps the previous one
+            let hotkey = hotkey_entry_for_response.text().trim().to_string();
+            if hotkey.is_empty() {
+                new_settings.hotkey = None;
+            } else {
+                match hotkey.parse::<Hotkey>() {
+                    Ok(parsed) => new_settings.hotkey = Some(parsed.to_string()),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            let abort_hotkey = abort_hotkey_entry_for_response.text().trim().to_string();
+            if abort_hotkey.is_empty() {
+                new_settings.abort_hotkey = None;
+            } else {
+                match abort_hotkey.parse::<Hotkey>() {
+                    Ok(parsed) => new_settings.abort_hotkey = Some(parsed.to_string()),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            new_settings.hotkey_mode = match hotkey_mode_combo_for_response.active_id().as_deref() {
+                Some("push_to_talk") => HotkeyMode::PushToTalk,
+                _ => HotkeyMode::Toggle,
+            };
+            new_settings.hotkey_backend =
+                match hotkey_backend_combo_for_response.active_id().as_deref() {
+                    Some("evdev") => HotkeyBackend::Evdev,
+                    Some("x11") => HotkeyBackend::X11,
+                    _ => HotkeyBackend::Auto,
+                };
 
             // Save the settings
             if let Err(e) = save_config(&new_settings) {
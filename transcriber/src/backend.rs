@@ -0,0 +1,50 @@
+use std::path::Path;
+
+// The interface every speech recognition engine implements. The IPC loop in
+// main.rs only talks to this trait, so adding an engine doesn't touch the
+// protocol code.
+
+/// What an engine can do, used by the IPC loop to decide what to send.
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub name: &'static str,
+    /// Model file or directory name, if the engine uses one.
+    pub model: Option<String>,
+    /// Sample rate the engine expects its input at.
+    pub sample_rate: u32,
+    /// Languages the model can recognize, empty if unknown.
+    pub languages: Vec<String>,
+    /// Whether the engine produces partial results while audio arrives.
+    pub partial_results: bool,
+}
+
+/// A finished transcription of one utterance (or part of one).
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub text: String,
+    pub confidence: Option<f32>,
+}
+
+pub trait SpeechBackend {
+    fn capabilities(&self) -> Capabilities;
+
+    /// Feed mono samples at the engine's sample rate. Returns a transcript if
+    /// the engine decided on its own that an utterance is complete.
+    fn accept_samples(&mut self, samples: &[f32]) -> Result<Option<Transcript>, String>;
+
+    /// The current hypothesis for the utterance in progress, if the engine
+    /// supports partial results.
+    fn partial(&mut self) -> Option<String>;
+
+    /// End the current utterance and return its transcript.
+    fn finalize(&mut self) -> Result<Option<Transcript>, String>;
+
+    /// Drop any buffered audio without producing a result.
+    fn reset(&mut self);
+}
+
+// Name of a model file or directory, for reporting to the core
+pub fn model_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
@@ -1,33 +1,69 @@
-use clap::Parser;
-use serde::{Deserialize, Serialize};
-use std::io::{self, BufRead, Write};
+mod backend;
+mod stub;
+mod vosk;
+mod whisper;
+
+use clap::{Parser, ValueEnum};
+use std::io::{self, Write};
 use std::path::PathBuf;
-use vosk::{DecodingState, Model, Recognizer};
+use whisperkey_protocol::{
+    IpcFormat, IpcHello, IpcReader, IpcRequest, IpcResponse, IpcTranscriptionResult, IpcWriter,
+    PROTOCOL_VERSION,
+};
 
-// Define the same IPC structs as in the core library
-#[derive(Debug, Serialize, Deserialize)]
-struct IpcAudioChunk {
-    samples: Vec<f32>,
-    sample_rate: u32,
-}
+use crate::backend::{SpeechBackend, Transcript};
+use crate::stub::StubBackend;
+use crate::vosk::VoskBackend;
+use crate::whisper::{WhisperBackend, WhisperOptions, WHISPER_SAMPLE_RATE};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct IpcTranscriptionResult {
-    text: String,
-    is_final: bool,
-    confidence: Option<f32>,
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Backend {
+    /// Streaming recognition with partial results
+    Vosk,
+    /// Batch recognition of each utterance with whisper.cpp
+    Whisper,
+    /// Replays scripted text, for testing without a model
+    Stub,
 }
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about = "Vosk transcription service")]
+#[clap(author, version, about = "Speech transcription service")]
 struct Args {
-    /// Path to Vosk model directory
+    /// Speech recognition backend
+    #[clap(short, long, value_enum, default_value = "vosk")]
+    backend: Backend,
+
+    /// Path to the model: a Vosk model directory, or a ggml Whisper model file
     #[clap(short, long, value_parser)]
-    model_path: PathBuf,
+    model_path: Option<PathBuf>,
 
     /// Sample rate for the recognizer (must match input audio)
     #[clap(short, long, default_value = "16000")]
     sample_rate: u32,
+
+    /// Whisper model size and quantization, e.g. `base.en` or `small.en-q5_1`
+    #[clap(long, default_value = "base.en")]
+    whisper_model: String,
+
+    /// Directory to look for `ggml-<whisper-model>.bin` in
+    #[clap(long, value_parser)]
+    models_dir: Option<PathBuf>,
+
+    /// Number of threads for Whisper (0 = use all cores)
+    #[clap(short, long, default_value = "0")]
+    threads: u32,
+
+    /// Language for Whisper, or "auto" to detect it
+    #[clap(short, long, default_value = "en")]
+    language: String,
+
+    /// Wire format on stdin/stdout: `binary` frames, or `json` lines for debugging
+    #[clap(long, default_value = "binary")]
+    ipc_format: IpcFormat,
+
+    /// Text file for the stub backend, one utterance per line
+    #[clap(long, value_parser)]
+    script: Option<PathBuf>,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -37,56 +73,91 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Parse command line arguments
     let args = Args::parse();
-    tracing::info!("Using model path: {:?}", args.model_path);
+    tracing::info!("Backend: {:?}", args.backend);
     tracing::info!("Sample rate: {}", args.sample_rate);
 
-    // Initialize Vosk model
-    tracing::info!("Loading Vosk model...");
-    let model_path_str = args.model_path.to_str().ok_or_else(|| {
-        let err = "Invalid model path: Path contains invalid UTF-8 characters".to_string();
-        tracing::error!("{}", err);
-        err
+    let mut backend = create_backend(&args).map_err(|e| {
+        tracing::error!("{}", e);
+        e
     })?;
 
-    let model = Model::new(model_path_str).ok_or_else(|| {
-        let err = format!("Failed to load Vosk model from path: {}", model_path_str);
-        tracing::error!("{}", err);
-        err
-    })?;
+    run(backend.as_mut(), args.ipc_format)
+}
 
-    // Create recognizer
-    tracing::info!("Creating recognizer with sample rate {}", args.sample_rate);
-    let mut recognizer = Recognizer::new(&model, args.sample_rate as f32).ok_or_else(|| {
-        let err = format!(
-            "Failed to create recognizer with sample rate {}",
-            args.sample_rate
-        );
-        tracing::error!("{}", err);
-        err
-    })?;
+fn create_backend(args: &Args) -> Result<Box<dyn SpeechBackend>, String> {
+    match args.backend {
+        Backend::Vosk => {
+            let model_path = args
+                .model_path
+                .as_ref()
+                .ok_or("The Vosk backend requires --model-path")?;
+            Ok(Box::new(VoskBackend::new(model_path, args.sample_rate)?))
+        }
+        Backend::Whisper => {
+            if args.sample_rate != WHISPER_SAMPLE_RATE {
+                return Err(format!(
+                    "Whisper requires {} Hz audio, got --sample-rate {}",
+                    WHISPER_SAMPLE_RATE, args.sample_rate
+                ));
+            }
 
-    // Set recognizer parameters
-    recognizer.set_max_alternatives(1);
-    recognizer.set_words(true);
-    recognizer.set_partial_words(true);
+            let model_path = whisper::resolve_model_path(
+                args.model_path.as_deref(),
+                &args.whisper_model,
+                args.models_dir.as_deref(),
+            );
+            tracing::info!("Loading Whisper model from {:?}...", model_path);
 
-    // Get stdin as a buffered reader
-    let stdin = io::stdin();
-    let mut reader = stdin.lock().lines();
+            let options = WhisperOptions {
+                threads: args.threads,
+                language: args.language.clone(),
+            };
+            Ok(Box::new(WhisperBackend::new(&model_path, options)?))
+        }
+        Backend::Stub => Ok(Box::new(StubBackend::new(
+            args.script.as_deref(),
+            args.sample_rate,
+        )?)),
+    }
+}
 
-    // Get stdout for writing results
-    let mut stdout = io::stdout();
+// Main loop: read requests from stdin, feed the backend, write results to stdout
+fn run(
+    backend: &mut dyn SpeechBackend,
+    ipc_format: IpcFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let capabilities = backend.capabilities();
+    tracing::info!("Using backend: {:?}", capabilities);
+    tracing::info!("IPC format: {:?}", ipc_format);
+
+    let mut reader = IpcReader::new(io::stdin().lock(), ipc_format);
+    let mut writer = IpcWriter::new(io::stdout().lock(), ipc_format);
+
+    // Tell the core the model is loaded and what we can do
+    writer.write_response(&IpcResponse::Hello(IpcHello {
+        protocol_version: PROTOCOL_VERSION,
+        backend: capabilities.name.to_string(),
+        model: capabilities.model.clone(),
+        sample_rates: vec![capabilities.sample_rate],
+        languages: capabilities.languages.clone(),
+        partial_results: capabilities.partial_results,
+    }))?;
 
     let mut received_chunks = 0;
-    let mut prev_text = String::new();
+    // Last partial sent, so unchanged hypotheses aren't repeated
+    let mut prev_partial = String::new();
 
     tracing::info!("Transcriber ready, waiting for input on stdin...");
 
-    // Main loop: read lines from stdin, process, write to stdout
-    while let Some(Ok(line)) = reader.next() {
-        // Try to deserialize the line as an IpcAudioChunk
-        match serde_json::from_str::<IpcAudioChunk>(&line) {
-            Ok(chunk) => {
+    loop {
+        let transcript = match reader.read_request() {
+            Ok(None) => break,
+            Ok(Some(IpcRequest::Finalize)) => {
+                // End of utterance, flush whatever the backend has
+                tracing::debug!("Finalizing utterance");
+                backend.finalize()
+            }
+            Ok(Some(IpcRequest::Audio(chunk))) => {
                 received_chunks += 1;
 
                 // Log receipt (not too frequently)
@@ -99,78 +170,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     );
                 }
 
-                // Convert f32 samples to i16 samples for Vosk
-                let i16_samples: Vec<i16> = chunk
-                    .samples
-                    .iter()
-                    .map(|&s| (s * 32767.0) as i16)
-                    .collect();
-
-                // Process audio with Vosk
-                let state = match recognizer.accept_waveform(&i16_samples) {
-                    Ok(state) => state,
-                    Err(e) => {
-                        tracing::error!("Error processing audio chunk: {}", e);
-                        continue;
-                    }
-                };
-
-                let is_final = state == DecodingState::Finalized;
-
-                if is_final {
-                    // Extract final result
-                    let result = recognizer.final_result();
-                    match result.single() {
-                        Some(complete_result) => {
-                            // Get the text from the result and create a String from it
-                            let transcription_text = complete_result.text.to_string();
-
-                            if !transcription_text.is_empty() {
-                                let result = IpcTranscriptionResult {
-                                    text: transcription_text.clone(),
-                                    is_final: true,
-                                    confidence: None, // Vosk doesn't provide overall confidence
-                                };
-
-                                // Serialize to JSON and send
-                                let json = serde_json::to_string(&result)?;
-                                writeln!(stdout, "{}", json)?;
-                                stdout.flush()?;
-
-                                tracing::info!("Sent final result: {}", transcription_text);
-                                prev_text = transcription_text;
-                            }
-                        }
-                        None => {
-                            tracing::debug!("Received empty final result");
-                        }
-                    }
-                } else {
-                    // Get partial result
-                    let partial_result = recognizer.partial_result();
-                    // Create a String from the partial result text
-                    let transcription_text = partial_result.partial.to_string();
-
-                    if !transcription_text.is_empty() && transcription_text != prev_text {
-                        let result = IpcTranscriptionResult {
-                            text: transcription_text.clone(),
-                            is_final: false,
-                            confidence: None,
-                        };
-
-                        // Serialize to JSON and send
-                        let json = serde_json::to_string(&result)?;
-                        writeln!(stdout, "{}", json)?;
-                        stdout.flush()?;
-
-                        tracing::debug!("Sent partial result: {}", transcription_text);
-                        prev_text = transcription_text;
-                    }
+                if chunk.sample_rate != capabilities.sample_rate {
+                    tracing::warn!(
+                        "Audio chunk sample rate {} Hz doesn't match recognizer rate {} Hz",
+                        chunk.sample_rate,
+                        capabilities.sample_rate
+                    );
                 }
+
+                backend.accept_samples(&chunk.samples)
             }
-            Err(e) => {
+            Err(e) if e.is_recoverable() => {
                 tracing::error!("Failed to deserialize input: {}", e);
-                tracing::error!("Raw input: {}", line);
+                continue;
+            }
+            Err(e) => {
+                // The framing is lost, nothing after this can be trusted
+                tracing::error!("Failed to read input: {}", e);
+                return Err(e.into());
+            }
+        };
+
+        match transcript {
+            Ok(Some(transcript)) => {
+                send_final_result(&mut writer, transcript)?;
+                prev_partial.clear();
+            }
+            Ok(None) => {
+                if capabilities.partial_results {
+                    send_partial_result(backend, &mut writer, &mut prev_partial)?;
+                }
+            }
+            Err(e) => {
+                // Drop the failed utterance rather than mix it into the next
+                tracing::error!("{}", e);
+                backend.reset();
+                prev_partial.clear();
             }
         }
     }
@@ -178,3 +213,45 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("Input stream ended, shutting down");
     Ok(())
 }
+
+// Send the backend's current hypothesis if it changed since the last one
+fn send_partial_result(
+    backend: &mut dyn SpeechBackend,
+    writer: &mut IpcWriter<impl Write>,
+    prev_partial: &mut String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(text) = backend.partial() else {
+        return Ok(());
+    };
+    if text.is_empty() || text == *prev_partial {
+        return Ok(());
+    }
+
+    writer.write_response(&IpcResponse::Result(IpcTranscriptionResult {
+        text: text.clone(),
+        is_final: false,
+        confidence: None,
+    }))?;
+
+    tracing::debug!("Sent partial result: {}", text);
+    *prev_partial = text;
+    Ok(())
+}
+
+fn send_final_result(
+    writer: &mut IpcWriter<impl Write>,
+    transcript: Transcript,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if transcript.text.is_empty() {
+        return Ok(());
+    }
+
+    writer.write_response(&IpcResponse::Result(IpcTranscriptionResult {
+        text: transcript.text.clone(),
+        is_final: true,
+        confidence: transcript.confidence,
+    }))?;
+
+    tracing::info!("Sent final result: {}", transcript.text);
+    Ok(())
+}
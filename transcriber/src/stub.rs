@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use crate::backend::{Capabilities, SpeechBackend, Transcript};
+
+// Text replayed when no script is given
+const DEFAULT_SCRIPT: &str = "hello world";
+
+// Script directive that exits the process
+const EXIT_DIRECTIVE: &str = "!exit";
+
+// Seconds of audio per word revealed in partial results
+const SECONDS_PER_WORD: f32 = 0.3;
+
+/// Deterministic backend that ignores the audio and replays scripted text,
+/// for exercising the rest of the pipeline without a model.
+///
+/// Each utterance takes the next line of the script (wrapping around). Words
+/// of the line are revealed as partial results as audio arrives, and the whole
+/// line is returned when the utterance is finalized.
+///
+/// A line of the form `!exit <code>` makes the process exit with that code
+/// when the utterance is finalized, to exercise the core's restart handling.
+pub struct StubBackend {
+    lines: Vec<String>,
+    next_line: usize,
+    sample_rate: u32,
+    // Audio received for the current utterance
+    samples_seen: usize,
+}
+
+impl StubBackend {
+    pub fn new(script: Option<&Path>, sample_rate: u32) -> Result<Self, String> {
+        let text = match script {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read stub script {:?}: {}", path, e))?,
+            None => DEFAULT_SCRIPT.to_string(),
+        };
+
+        let lines: Vec<String> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if lines.is_empty() {
+            return Err("Stub script has no lines".to_string());
+        }
+
+        Ok(Self {
+            lines,
+            next_line: 0,
+            sample_rate,
+            samples_seen: 0,
+        })
+    }
+
+    fn current_line(&self) -> &str {
+        &self.lines[self.next_line % self.lines.len()]
+    }
+}
+
+impl SpeechBackend for StubBackend {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            name: "stub",
+            model: None,
+            sample_rate: self.sample_rate,
+            languages: Vec::new(),
+            partial_results: true,
+        }
+    }
+
+    fn accept_samples(&mut self, samples: &[f32]) -> Result<Option<Transcript>, String> {
+        self.samples_seen += samples.len();
+        Ok(None)
+    }
+
+    fn partial(&mut self) -> Option<String> {
+        if self.samples_seen == 0 || self.current_line().starts_with(EXIT_DIRECTIVE) {
+            return None;
+        }
+
+        let seconds = self.samples_seen as f32 / self.sample_rate as f32;
+        let words = (seconds / SECONDS_PER_WORD) as usize + 1;
+        let partial: Vec<&str> = self.current_line().split_whitespace().take(words).collect();
+        Some(partial.join(" "))
+    }
+
+    fn finalize(&mut self) -> Result<Option<Transcript>, String> {
+        // Nothing was said, so don't use up a line
+        if self.samples_seen == 0 {
+            return Ok(None);
+        }
+
+        let text = self.current_line().to_string();
+        self.next_line += 1;
+
+        if let Some(code) = text.strip_prefix(EXIT_DIRECTIVE) {
+            let code = code.trim().parse().unwrap_or(1);
+            tracing::warn!("Stub script says exit, exiting with code {}", code);
+            std::process::exit(code);
+        }
+        self.samples_seen = 0;
+
+        Ok(Some(Transcript {
+            text,
+            confidence: Some(1.0),
+        }))
+    }
+
+    fn reset(&mut self) {
+        self.samples_seen = 0;
+    }
+}
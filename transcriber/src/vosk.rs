@@ -0,0 +1,101 @@
+use ::vosk::{DecodingState, Model, Recognizer};
+use std::path::Path;
+
+use crate::backend::{model_name, Capabilities, SpeechBackend, Transcript};
+
+/// Streaming recognition with Vosk.
+pub struct VoskBackend {
+    _model: Model, // What `recognizer` was built from
+    recognizer: Recognizer,
+    model_name: Option<String>,
+    sample_rate: u32,
+}
+
+impl VoskBackend {
+    pub fn new(model_path: &Path, sample_rate: u32) -> Result<Self, String> {
+        let model_path_str = model_path
+            .to_str()
+            .ok_or("Invalid model path: Path contains invalid UTF-8 characters")?;
+
+        tracing::info!("Loading Vosk model from {}...", model_path_str);
+        let model = Model::new(model_path_str)
+            .ok_or_else(|| format!("Failed to load Vosk model from path: {}", model_path_str))?;
+
+        tracing::info!("Creating recognizer with sample rate {}", sample_rate);
+        let mut recognizer = Recognizer::new(&model, sample_rate as f32).ok_or_else(|| {
+            format!(
+                "Failed to create recognizer with sample rate {}",
+                sample_rate
+            )
+        })?;
+
+        // Set recognizer parameters
+        recognizer.set_max_alternatives(1);
+        recognizer.set_words(true);
+        recognizer.set_partial_words(true);
+
+        Ok(Self {
+            _model: model,
+            recognizer,
+            model_name: model_name(model_path),
+            sample_rate,
+        })
+    }
+}
+
+impl SpeechBackend for VoskBackend {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            name: "vosk",
+            model: self.model_name.clone(),
+            sample_rate: self.sample_rate,
+            // Vosk models don't say which language they're for
+            languages: Vec::new(),
+            partial_results: true,
+        }
+    }
+
+    fn accept_samples(&mut self, samples: &[f32]) -> Result<Option<Transcript>, String> {
+        // Convert f32 samples to i16 samples for Vosk
+        let i16_samples: Vec<i16> = samples.iter().map(|&s| (s * 32767.0) as i16).collect();
+
+        let state = self
+            .recognizer
+            .accept_waveform(&i16_samples)
+            .map_err(|e| format!("Error processing audio chunk: {}", e))?;
+
+        // Vosk ends utterances itself when it hears a long enough pause
+        if state == DecodingState::Finalized {
+            self.finalize()
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn partial(&mut self) -> Option<String> {
+        let partial = self.recognizer.partial_result().partial;
+        (!partial.is_empty()).then(|| partial.to_string())
+    }
+
+    fn finalize(&mut self) -> Result<Option<Transcript>, String> {
+        let result = self.recognizer.final_result();
+        let Some(complete_result) = result.single() else {
+            tracing::debug!("Received empty final result");
+            return Ok(None);
+        };
+
+        let text = complete_result.text.to_string();
+        if text.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Transcript {
+            text,
+            confidence: None, // Vosk doesn't provide overall confidence
+        }))
+    }
+
+    fn reset(&mut self) {
+        self.recognizer.reset();
+    }
+}
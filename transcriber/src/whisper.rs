@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
+
+use crate::backend::{model_name, Capabilities, SpeechBackend, Transcript};
+
+// Whisper only accepts 16 kHz mono audio
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+// Whisper works on windows of at most 30 seconds, so transcribe early
+// rather than let the buffer grow past that
+const MAX_BUFFER_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize * 30;
+
+// Ignore utterances too short to contain a word (100 ms)
+const MIN_UTTERANCE_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize / 10;
+
+pub struct WhisperOptions {
+    /// Number of threads to use, 0 for all available cores.
+    pub threads: u32,
+    /// Language code (e.g. "en"), or "auto" to let Whisper detect it.
+    pub language: String,
+}
+
+/// Batch Whisper recognizer: audio is buffered until the end of an utterance
+/// and then transcribed in one pass.
+pub struct WhisperBackend {
+    _context: WhisperContext, // The loaded model, which `state` runs
+    state: WhisperState,
+    options: WhisperOptions,
+    model_name: Option<String>,
+    languages: Vec<String>,
+    buffer: Vec<f32>,
+}
+
+impl WhisperBackend {
+    pub fn new(model_path: &Path, options: WhisperOptions) -> Result<Self, String> {
+        let model_path_str = model_path
+            .to_str()
+            .ok_or("Invalid model path: Path contains invalid UTF-8 characters")?;
+
+        let context =
+            WhisperContext::new_with_params(model_path_str, WhisperContextParameters::default())
+                .map_err(|e| format!("Failed to load Whisper model {}: {}", model_path_str, e))?;
+
+        let state = context
+            .create_state()
+            .map_err(|e| format!("Failed to create Whisper state: {}", e))?;
+
+        // English-only models (*.en) can't do anything else
+        let languages = if context.is_multilingual() {
+            (0..=whisper_rs::get_lang_max_id())
+                .filter_map(whisper_rs::get_lang_str)
+                .map(str::to_string)
+                .collect()
+        } else {
+            vec!["en".to_string()]
+        };
+
+        Ok(Self {
+            _context: context,
+            state,
+            options,
+            model_name: model_name(model_path),
+            languages,
+            buffer: Vec::new(),
+        })
+    }
+}
+
+impl SpeechBackend for WhisperBackend {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            name: "whisper",
+            model: self.model_name.clone(),
+            sample_rate: WHISPER_SAMPLE_RATE,
+            languages: self.languages.clone(),
+            partial_results: false,
+        }
+    }
+
+    // Audio is only buffered, unless it fills Whisper's 30 second window
+    fn accept_samples(&mut self, samples: &[f32]) -> Result<Option<Transcript>, String> {
+        self.buffer.extend_from_slice(samples);
+
+        if self.buffer.len() >= MAX_BUFFER_SAMPLES {
+            tracing::debug!("Whisper buffer full, transcribing early");
+            return self.finalize();
+        }
+        Ok(None)
+    }
+
+    // Transcribe everything buffered since the last call
+    fn finalize(&mut self) -> Result<Option<Transcript>, String> {
+        let samples = std::mem::take(&mut self.buffer);
+        if samples.len() < MIN_UTTERANCE_SAMPLES {
+            return Ok(None);
+        }
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        if self.options.threads > 0 {
+            params.set_n_threads(self.options.threads as i32);
+        }
+        if self.options.language == "auto" {
+            params.set_language(None);
+        } else {
+            params.set_language(Some(&self.options.language));
+        }
+        // Every utterance is independent, and we only want the text
+        params.set_no_context(true);
+        params.set_single_segment(true);
+        params.set_suppress_blank(true);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+
+        self.state
+            .full(params, &samples)
+            .map_err(|e| format!("Whisper transcription failed: {}", e))?;
+
+        let segments = self
+            .state
+            .full_n_segments()
+            .map_err(|e| format!("Failed to read Whisper segments: {}", e))?;
+
+        let mut text = String::new();
+        let mut probability_sum = 0.0;
+        let mut token_count = 0;
+
+        for segment in 0..segments {
+            let segment_text = self
+                .state
+                .full_get_segment_text(segment)
+                .map_err(|e| format!("Failed to read Whisper segment: {}", e))?;
+            text.push_str(&segment_text);
+
+            let tokens = self.state.full_n_tokens(segment).unwrap_or(0);
+            for token in 0..tokens {
+                if let Ok(probability) = self.state.full_get_token_prob(segment, token) {
+                    probability_sum += probability;
+                    token_count += 1;
+                }
+            }
+        }
+
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Transcript {
+            text,
+            // Mean token probability, as a rough confidence measure
+            confidence: (token_count > 0).then(|| probability_sum / token_count as f32),
+        }))
+    }
+
+    // Whisper can't stream, so there's never a partial result
+    fn partial(&mut self) -> Option<String> {
+        None
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+/// Find the ggml model to load: an explicit model file wins, otherwise
+/// `ggml-<model>.bin` is looked up in the models directory (the layout
+/// `scripts/download-whisper-model.sh` produces).
+pub fn resolve_model_path(
+    model_path: Option<&Path>,
+    model: &str,
+    models_dir: Option<&Path>,
+) -> PathBuf {
+    if let Some(path) = model_path {
+        if path.is_file() {
+            return path.to_path_buf();
+        }
+    }
+
+    let file_name = format!("ggml-{}.bin", model);
+
+    // A model directory passed as --model-path counts as a models directory
+    let dirs = [
+        model_path.filter(|p| p.is_dir()).map(Path::to_path_buf),
+        models_dir.map(Path::to_path_buf),
+        default_models_dir(),
+        Some(PathBuf::from("models")),
+    ];
+
+    dirs.iter()
+        .flatten()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+pub fn default_models_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("whisperkey").join("models"))
+}
@@ -0,0 +1,158 @@
+// The core's supervision of a crashing transcriber, using the stub backend
+// with a script that exits on every utterance
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ractor::{Actor, ActorProcessingErr, ActorRef};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use whisperkey_core::transcriber::TranscriberActor;
+use whisperkey_core::types::{AudioChunk, CoordinatorMsg, TranscriberMsg};
+use whisperkey_core::{Settings, TranscriptionBackend};
+
+const SAMPLE_RATE: u32 = 16000;
+const MAX_RESTARTS: u32 = 2;
+
+// Long enough for a debug build of the transcriber to start
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(20);
+
+// Stands in for the coordinator, passing on everything it's sent
+struct Recorder;
+
+#[ractor::async_trait]
+impl Actor for Recorder {
+    type Msg = CoordinatorMsg;
+    type State = UnboundedSender<CoordinatorMsg>;
+    type Arguments = UnboundedSender<CoordinatorMsg>;
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        sender: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(sender)
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        sender: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let _ = sender.send(message);
+        Ok(())
+    }
+}
+
+// Wait for the next message of interest, skipping status updates and the like
+async fn next_event(messages: &mut UnboundedReceiver<CoordinatorMsg>) -> CoordinatorMsg {
+    loop {
+        let message = tokio::time::timeout(MESSAGE_TIMEOUT, messages.recv())
+            .await
+            .expect("timed out waiting for the transcriber")
+            .expect("recorder stopped");
+        match message {
+            CoordinatorMsg::TranscriberReady(_)
+            | CoordinatorMsg::TranscriberRestarting { .. }
+            | CoordinatorMsg::TranscriberFailed { .. }
+            | CoordinatorMsg::TranscriptionResult(_) => return message,
+            _ => {}
+        }
+    }
+}
+
+fn exit_script() -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("stub-exit-script.txt");
+    std::fs::write(&path, "!exit 3\n").expect("failed to write stub script");
+    path
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crashed_transcriber_is_restarted_with_backoff_until_the_budget_runs_out() {
+    let config = Settings {
+        transcription_backend: TranscriptionBackend::Stub,
+        transcriber_path: Some(env!("CARGO_BIN_EXE_transcriber").to_string()),
+        stub_script: Some(exit_script().display().to_string()),
+        transcriber_max_restarts: MAX_RESTARTS,
+        ..Settings::default()
+    };
+
+    let (sender, mut messages) = unbounded_channel();
+    let (recorder, _) = Actor::spawn(None, Recorder, sender)
+        .await
+        .expect("failed to spawn recorder");
+    let (transcriber, _) = Actor::spawn(
+        None,
+        TranscriberActor {},
+        (recorder.clone(), SAMPLE_RATE, None, Arc::new(config)),
+    )
+    .await
+    .expect("failed to spawn transcriber actor");
+
+    let mut last_delay = Duration::ZERO;
+    let mut restarted_at: Option<(Instant, Duration)> = None;
+    for attempt in 1..=MAX_RESTARTS + 1 {
+        let event = next_event(&mut messages).await;
+        assert!(
+            matches!(event, CoordinatorMsg::TranscriberReady(_)),
+            "expected the transcriber to come up, got {:?}",
+            event
+        );
+        if let Some((at, delay)) = restarted_at {
+            let waited = at.elapsed();
+            assert!(
+                waited >= delay,
+                "restarted after {:?}, not {:?}",
+                waited,
+                delay
+            );
+        }
+
+        // An utterance makes the stub exit
+        transcriber
+            .send_message(TranscriberMsg::ProcessAudioChunk(AudioChunk::mono(
+                vec![0.0; SAMPLE_RATE as usize / 10],
+                SAMPLE_RATE,
+            )))
+            .unwrap();
+        transcriber
+            .send_message(TranscriberMsg::FinalizeUtterance)
+            .unwrap();
+
+        match next_event(&mut messages).await {
+            CoordinatorMsg::TranscriberRestarting {
+                attempt: reported,
+                max_attempts,
+                total_restarts,
+                last_exit,
+                delay,
+            } => {
+                assert!(attempt <= MAX_RESTARTS, "didn't give up");
+                assert_eq!(reported, attempt);
+                assert_eq!(max_attempts, MAX_RESTARTS);
+                assert_eq!(total_restarts, attempt);
+                assert!(last_exit.contains('3'), "last exit was {}", last_exit);
+                assert!(delay > last_delay, "{:?} isn't a backoff", delay);
+                last_delay = delay;
+                restarted_at = Some((Instant::now(), delay));
+            }
+            CoordinatorMsg::TranscriberFailed {
+                restarts,
+                last_exit,
+            } => {
+                assert_eq!(attempt, MAX_RESTARTS + 1, "gave up early");
+                assert_eq!(restarts, MAX_RESTARTS);
+                assert!(last_exit.contains('3'), "last exit was {}", last_exit);
+            }
+            event => panic!("expected the exit to be reported, got {:?}", event),
+        }
+    }
+
+    // Having given up, it stays down
+    let waited = tokio::time::timeout(last_delay * 2, next_event(&mut messages)).await;
+    assert!(waited.is_err(), "transcriber came back: {:?}", waited);
+
+    transcriber.stop(None);
+    recorder.stop(None);
+}